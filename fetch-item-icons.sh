@@ -0,0 +1,13 @@
+#!/usr/bin/env bash
+# Downloads the icons of all craftable items into assets/item-icons, where native builds load them from.
+# The web version fetches the icons from XIVAPI instead. Icons that are already downloaded are skipped.
+
+set -euo pipefail
+
+cargo run --quiet -p game-data --example item_icon_paths | while read -r path; do
+    target="assets/item-icons/$path"
+    if [ ! -f "$target" ]; then
+        mkdir -p "$(dirname "$target")"
+        curl -sSf "https://xivapi.com/i/$path" -o "$target"
+    fi
+done
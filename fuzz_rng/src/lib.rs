@@ -0,0 +1,46 @@
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+/// Seeded RNG for fuzz tests, so that a failing case can be reproduced.
+/// The seed is taken from the `RAPHAEL_FUZZ_SEED` environment variable if it is set, and random otherwise.
+/// It is printed to stderr when the RNG is dropped during a panic, i.e. only when the test fails.
+pub struct FuzzRng {
+    rng: StdRng,
+    seed: u64,
+}
+
+pub fn fuzz_rng() -> FuzzRng {
+    let seed = match std::env::var("RAPHAEL_FUZZ_SEED") {
+        Ok(seed) => seed.parse().expect("RAPHAEL_FUZZ_SEED must be a u64"),
+        Err(_) => rand::random(),
+    };
+    FuzzRng {
+        rng: StdRng::seed_from_u64(seed),
+        seed,
+    }
+}
+
+impl RngCore for FuzzRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl Drop for FuzzRng {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            eprintln!("Fuzz seed: RAPHAEL_FUZZ_SEED={}", self.seed);
+        }
+    }
+}
@@ -82,7 +82,7 @@ pub fn import_consumable_records(
     }
 
     // sort by item level decreasing
-    meals.sort_by(|a, b| b.item_level.cmp(&a.item_level));
+    meals.sort_by_key(|meal| std::cmp::Reverse(meal.item_level));
     let mut writer = BufWriter::new(
         File::create(Path::new(&std::env::var("OUT_DIR")?).join("meals.rs")).unwrap(),
     );
@@ -94,7 +94,7 @@ pub fn import_consumable_records(
     writeln!(writer, "]")?;
 
     // sort by item level decreasing
-    potions.sort_by(|a, b| b.item_level.cmp(&a.item_level));
+    potions.sort_by_key(|potion| std::cmp::Reverse(potion.item_level));
     let mut writer = BufWriter::new(
         File::create(Path::new(&std::env::var("OUT_DIR")?).join("potions.rs")).unwrap(),
     );
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
@@ -14,8 +14,9 @@ pub fn import_item_records(
         .filter(|item| relevant_items.contains(&item.id))
     {
         item_stats.entry(item.id, &format!(
-            "Item {{ item_level: {item_level}, can_be_hq: {can_be_hq}, is_collectable: {is_collectable} }}",
+            "Item {{ item_level: {item_level}, icon_id: {icon_id}, can_be_hq: {can_be_hq}, is_collectable: {is_collectable} }}",
             item_level = item.item_level,
+            icon_id = item.icon_id,
             can_be_hq = item.can_be_hq,
             is_collectable = item.is_collectable,
         ));
@@ -37,14 +38,29 @@ fn import_item_names(
     relevant_items: &HashSet<u32>,
     lang: &'static str,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut item_names = phf_codegen::Map::new();
-    for item in read_csv_data::<ItemRecord>(format!("data/{}/Item.csv", lang))
+    let items: Vec<(u32, String)> = read_csv_data::<ItemRecord>(format!("data/{}/Item.csv", lang))
         .filter(|item| relevant_items.contains(&item.id))
-    {
-        item_names.entry(
-            item.id,
-            &format!("\"{}\"", item.name.replace("<SoftHyphen/>", "")),
-        );
+        .map(|item| (item.id, item.name.replace("<SoftHyphen/>", "")))
+        .collect();
+    // names are shown to the user to tell items apart, so items that share a name get their id appended
+    let mut name_to_ids: HashMap<&str, Vec<u32>> = HashMap::new();
+    for (item_id, name) in items.iter() {
+        name_to_ids.entry(name).or_default().push(*item_id);
+    }
+    let mut item_names = phf_codegen::Map::new();
+    for (item_id, name) in items.iter() {
+        let item_ids = &name_to_ids[name.as_str()];
+        if item_ids.len() == 1 {
+            item_names.entry(*item_id, &format!("\"{}\"", name));
+        } else {
+            if item_ids[0] == *item_id {
+                println!(
+                    "cargo:warning=Items {:?} have the same {lang} name \"{name}\", appending the item id to tell them apart",
+                    item_ids
+                );
+            }
+            item_names.entry(*item_id, &format!("\"{} ({})\"", name, item_id));
+        }
     }
     let out_path = Path::new(&std::env::var("OUT_DIR")?).join(format!("item_names_{}.rs", lang));
     let mut writer = BufWriter::new(File::create(out_path).unwrap());
@@ -24,6 +24,7 @@ fn import_game_data() -> Result<(), Box<dyn std::error::Error>> {
 
     let rlvls = import_rlvl_records()?;
     import_recipe_records(&mut relevant_items, &rlvls)?;
+    import_hq_percentage_records()?;
 
     consumables::import_consumable_records(&mut relevant_items)?;
     items::import_item_records(relevant_items)?;
@@ -41,7 +42,7 @@ fn import_rlvl_records() -> Result<Vec<RecipeLevelRecord>, Box<dyn std::error::E
     );
     writeln!(writer, "[")?;
     for record in rlvl_records.iter() {
-        writeln!(writer, "RecipeLevel {{ progress_div: {}, quality_div: {}, progress_mod: {}, quality_mod: {} }},", record.progress_divider, record.quality_divider, record.progress_modifier, record.quality_modifier)?;
+        writeln!(writer, "RecipeLevel {{ level: {}, progress: {}, quality: {}, durability: {}, progress_div: {}, quality_div: {}, progress_mod: {}, quality_mod: {} }},", record.level, record.progress, record.quality, record.durability, record.progress_divider, record.quality_divider, record.progress_modifier, record.quality_modifier)?;
     }
     writeln!(writer, "]")?;
     Ok(rlvl_records)
@@ -114,3 +115,16 @@ fn import_recipe_records(
 
     Ok(())
 }
+
+fn import_hq_percentage_records() -> Result<(), Box<dyn std::error::Error>> {
+    let mut hq_percentages = Vec::new();
+    for record in read_csv_data::<HqPercentageRecord>("data/HqPercentage.csv") {
+        // the table is indexed by the quality percentage
+        assert_eq!(record.quality_percentage as usize, hq_percentages.len());
+        hq_percentages.push(record.hq_percentage.to_string());
+    }
+    let out_path = Path::new(&std::env::var("OUT_DIR")?).join("hq_percentages.rs");
+    let mut writer = BufWriter::new(File::create(out_path).unwrap());
+    writeln!(writer, "[{}]", hq_percentages.join(", "))?;
+    Ok(())
+}
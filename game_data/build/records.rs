@@ -73,6 +73,8 @@ pub struct ItemRecord {
     pub id: u32,
     #[serde(rename = "Name")]
     pub name: String,
+    #[serde(rename = "Icon")]
+    pub icon_id: u32,
     #[serde(rename = "Level{Item}")]
     pub item_level: u32,
     #[serde(rename = "ItemAction")]
@@ -152,3 +154,11 @@ pub struct RecipeLevelRecord {
     #[serde(rename = "QualityModifier")]
     pub quality_modifier: u32,
 }
+
+#[derive(Deserialize)]
+pub struct HqPercentageRecord {
+    #[serde(rename = "QualityPercentage")]
+    pub quality_percentage: u8,
+    #[serde(rename = "HqPercentage")]
+    pub hq_percentage: u8,
+}
@@ -0,0 +1,14 @@
+use std::collections::BTreeSet;
+
+use game_data::{item_icon_path, RECIPES};
+
+/// Prints the icon path of every craftable item, see `fetch-item-icons.sh`.
+fn main() {
+    let paths: BTreeSet<String> = RECIPES
+        .iter()
+        .filter_map(|recipe| item_icon_path(recipe.item_id))
+        .collect();
+    for path in paths {
+        println!("{path}");
+    }
+}
@@ -0,0 +1,96 @@
+use simulator::Action;
+
+/// Stable one-byte code of the action, used by `encode_actions`.
+/// Codes are never reused or reordered, new actions must be appended with the next free code.
+pub const fn action_code(action: Action) -> u8 {
+    match action {
+        Action::BasicSynthesis => 0,
+        Action::BasicTouch => 1,
+        Action::MasterMend => 2,
+        Action::Observe => 3,
+        Action::WasteNot => 4,
+        Action::Veneration => 5,
+        Action::StandardTouch => 6,
+        Action::ComboStandardTouch => 7,
+        Action::GreatStrides => 8,
+        Action::Innovation => 9,
+        Action::WasteNot2 => 10,
+        Action::ByregotsBlessing => 11,
+        Action::PreciseTouch => 12,
+        Action::MuscleMemory => 13,
+        Action::CarefulSynthesis => 14,
+        Action::Manipulation => 15,
+        Action::PrudentTouch => 16,
+        Action::AdvancedTouch => 17,
+        Action::ComboAdvancedTouch => 18,
+        Action::Reflect => 19,
+        Action::PreparatoryTouch => 20,
+        Action::Groundwork => 21,
+        Action::DelicateSynthesis => 22,
+        Action::IntensiveSynthesis => 23,
+        Action::HeartAndSoul => 24,
+        Action::PrudentSynthesis => 25,
+        Action::TrainedFinesse => 26,
+        Action::TrainedEye => 27,
+        Action::ComboRefinedTouch => 28,
+        Action::QuickInnovation => 29,
+        Action::ImmaculateMend => 30,
+        Action::TrainedPerfection => 31,
+        Action::TricksOfTheTrade => 32,
+        Action::CarefulObservation => 33,
+    }
+}
+
+/// Inverse of `action_code`, `None` if no action has this code.
+pub const fn action_from_code(code: u8) -> Option<Action> {
+    match code {
+        0 => Some(Action::BasicSynthesis),
+        1 => Some(Action::BasicTouch),
+        2 => Some(Action::MasterMend),
+        3 => Some(Action::Observe),
+        4 => Some(Action::WasteNot),
+        5 => Some(Action::Veneration),
+        6 => Some(Action::StandardTouch),
+        7 => Some(Action::ComboStandardTouch),
+        8 => Some(Action::GreatStrides),
+        9 => Some(Action::Innovation),
+        10 => Some(Action::WasteNot2),
+        11 => Some(Action::ByregotsBlessing),
+        12 => Some(Action::PreciseTouch),
+        13 => Some(Action::MuscleMemory),
+        14 => Some(Action::CarefulSynthesis),
+        15 => Some(Action::Manipulation),
+        16 => Some(Action::PrudentTouch),
+        17 => Some(Action::AdvancedTouch),
+        18 => Some(Action::ComboAdvancedTouch),
+        19 => Some(Action::Reflect),
+        20 => Some(Action::PreparatoryTouch),
+        21 => Some(Action::Groundwork),
+        22 => Some(Action::DelicateSynthesis),
+        23 => Some(Action::IntensiveSynthesis),
+        24 => Some(Action::HeartAndSoul),
+        25 => Some(Action::PrudentSynthesis),
+        26 => Some(Action::TrainedFinesse),
+        27 => Some(Action::TrainedEye),
+        28 => Some(Action::ComboRefinedTouch),
+        29 => Some(Action::QuickInnovation),
+        30 => Some(Action::ImmaculateMend),
+        31 => Some(Action::TrainedPerfection),
+        32 => Some(Action::TricksOfTheTrade),
+        33 => Some(Action::CarefulObservation),
+        _ => None,
+    }
+}
+
+/// Compact encoding of a macro with one byte per action, e.g. for permalinks.
+pub fn encode_actions(actions: &[Action]) -> Vec<u8> {
+    actions.iter().map(|action| action_code(*action)).collect()
+}
+
+/// Decodes a macro encoded with `encode_actions`.
+pub fn decode_actions(bytes: &[u8]) -> Result<Vec<Action>, &'static str> {
+    bytes
+        .iter()
+        .map(|code| action_from_code(*code).ok_or("Unknown action code"))
+        .collect()
+}
@@ -16,6 +16,16 @@ pub struct CrafterStats {
     pub heart_and_soul: bool,
     #[serde(default)]
     pub quick_innovation: bool,
+    #[serde(default)]
+    pub careful_observation: bool,
+}
+
+impl CrafterStats {
+    // upper limits for user input, comfortably above what fully melded gear of the current patch reaches
+    pub const MAX_CRAFTSMANSHIP: u16 = 6000;
+    pub const MAX_CONTROL: u16 = 6000;
+    pub const MAX_CP: u16 = 1000;
+    pub const MAX_LEVEL: u8 = 100;
 }
 
 impl Default for CrafterStats {
@@ -28,6 +38,7 @@ impl Default for CrafterStats {
             manipulation: true,
             heart_and_soul: false,
             quick_innovation: false,
+            careful_observation: false,
         }
     }
 }
@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use simulator::Settings;
+
+use crate::{get_game_settings, CrafterStats, Recipe};
 
 pub const MEALS: &[Consumable] = include!(concat!(env!("OUT_DIR"), "/meals.rs"));
 pub const POTIONS: &[Consumable] = include!(concat!(env!("OUT_DIR"), "/potions.rs"));
@@ -85,6 +88,82 @@ pub fn cp_bonus(base: u16, consumables: &[Option<Consumable>]) -> u16 {
         .sum()
 }
 
+/// Candidates of `suggest_consumables`: for each stat, the HQ consumable with the highest item level that increases it.
+/// Older consumables are strictly weaker, so leaving them out keeps the number of tested combinations small.
+fn suggestion_candidates(consumables: &[Consumable]) -> Vec<Consumable> {
+    let stat_selectors: [fn(&Consumable) -> u16; 3] = [
+        |consumable| consumable.craft_rel,
+        |consumable| consumable.control_rel,
+        |consumable| consumable.cp_rel,
+    ];
+    let mut candidates: Vec<Consumable> = Vec::new();
+    for stat in stat_selectors {
+        let best = consumables
+            .iter()
+            .filter(|consumable| consumable.hq && stat(consumable) != 0)
+            .max_by_key(|consumable| consumable.item_level);
+        if let Some(best) = best {
+            if !candidates
+                .iter()
+                .any(|candidate| candidate.item_id == best.item_id)
+            {
+                candidates.push(*best);
+            }
+        }
+    }
+    candidates
+}
+
+/// Food and medicine that would make the craft `is_sufficient`, e.g. able to finish or to reach a Quality target.
+/// The selected `food` and `potion` stay in use unless the suggestion replaces them, e.g. a suggested medicine is taken together with the selected food.
+/// Single consumables are preferred over a food and medicine pair, and food is listed first.
+/// `is_sufficient` is called with the settings from `get_game_settings` (without adversarial mode) for each tested combination.
+/// Returns an empty list if no combination of the candidates is sufficient, or if none is needed because the craft already is.
+pub fn suggest_consumables(
+    recipe: Recipe,
+    crafter_stats: CrafterStats,
+    food: Option<Consumable>,
+    potion: Option<Consumable>,
+    mut is_sufficient: impl FnMut(&Settings) -> bool,
+) -> Vec<Consumable> {
+    let mut test = |food: Option<Consumable>, potion: Option<Consumable>| {
+        get_game_settings(recipe, crafter_stats, food, potion, false)
+            .is_ok_and(|settings| is_sufficient(&settings))
+    };
+    if test(food, potion) {
+        return Vec::new();
+    }
+    // suggesting an already selected consumable wouldn't change anything
+    let is_selected = |consumable: &Consumable, selected: Option<Consumable>| {
+        selected.is_some_and(|selected| {
+            selected.item_id == consumable.item_id && selected.hq == consumable.hq
+        })
+    };
+    let meals: Vec<Consumable> = suggestion_candidates(MEALS)
+        .into_iter()
+        .filter(|meal| !is_selected(meal, food))
+        .collect();
+    let potions: Vec<Consumable> = suggestion_candidates(POTIONS)
+        .into_iter()
+        .filter(|candidate| !is_selected(candidate, potion))
+        .collect();
+    if let Some(meal) = meals.iter().find(|meal| test(Some(**meal), potion)) {
+        return vec![*meal];
+    }
+    if let Some(potion) = potions.iter().find(|potion| test(food, Some(**potion))) {
+        return vec![*potion];
+    }
+    for meal in meals.iter() {
+        if let Some(potion) = potions
+            .iter()
+            .find(|potion| test(Some(*meal), Some(**potion)))
+        {
+            return vec![*meal, *potion];
+        }
+    }
+    Vec::new()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{get_item_name, Locale};
@@ -124,4 +203,41 @@ mod tests {
             "Control +5% (50), CP +26% (26)"
         );
     }
+
+    #[test]
+    fn test_suggest_consumables() {
+        let recipe = *crate::RECIPES.last().unwrap();
+        let crafter_stats = CrafterStats::default();
+        // nothing to suggest if the craft is already sufficient, or if no consumable helps
+        assert!(suggest_consumables(recipe, crafter_stats, None, None, |_| true).is_empty());
+        assert!(suggest_consumables(recipe, crafter_stats, None, None, |_| false).is_empty());
+        // a single food is enough to get more CP
+        let suggestion = suggest_consumables(recipe, crafter_stats, None, None, |settings| {
+            settings.max_cp > crafter_stats.cp as i16
+        });
+        assert_eq!(suggestion.len(), 1);
+        assert!(MEALS
+            .iter()
+            .any(|meal| meal.item_id == suggestion[0].item_id));
+        assert!(suggestion[0].hq && suggestion[0].cp_rel != 0);
+        // more CP than the best food alone gives needs a medicine as well
+        let best_food_cp = cp_bonus(crafter_stats.cp, &[Some(suggestion[0])]);
+        let suggestion = suggest_consumables(recipe, crafter_stats, None, None, |settings| {
+            settings.max_cp > (crafter_stats.cp + best_food_cp) as i16
+        });
+        assert_eq!(suggestion.len(), 2);
+        assert!(suggestion.iter().all(|consumable| consumable.cp_rel != 0));
+        // with the food already selected, only the medicine is missing
+        let suggestion = suggest_consumables(
+            recipe,
+            crafter_stats,
+            Some(suggestion[0]),
+            None,
+            |settings| settings.max_cp > (crafter_stats.cp + best_food_cp) as i16,
+        );
+        assert_eq!(suggestion.len(), 1);
+        assert!(POTIONS
+            .iter()
+            .any(|potion| potion.item_id == suggestion[0].item_id));
+    }
 }
@@ -0,0 +1,244 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::{Ingredient, Item, Recipe, RecipeLevel};
+
+// share the CSV schema with the build script so that both stay in sync
+#[allow(dead_code)]
+#[path = "../build/records.rs"]
+mod records;
+use records::{ItemRecord, RecipeLevelRecord, RecipeRecord};
+
+#[derive(Debug)]
+pub struct DataError {
+    pub path: PathBuf,
+    /// Line number of the offending row (if the error can be attributed to a row)
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+impl std::fmt::Display for DataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+impl DataError {
+    fn new(path: &Path, line: Option<u64>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn from_csv(path: &Path, line: Option<u64>, error: csv::Error) -> Self {
+        Self::new(path, line, error.to_string())
+    }
+}
+
+/// Deserialize all rows of a CSV file, keeping track of the line number of each row.
+fn read_records<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<(u64, T)>, DataError> {
+    let mut reader =
+        csv::Reader::from_path(path).map_err(|err| DataError::from_csv(path, None, err))?;
+    let headers = reader
+        .headers()
+        .map_err(|err| DataError::from_csv(path, Some(1), err))?
+        .clone();
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row.map_err(|err| {
+            let line = err.position().map(|position| position.line());
+            DataError::from_csv(path, line, err)
+        })?;
+        let line = row.position().map_or(0, |position| position.line());
+        let record = row
+            .deserialize(Some(&headers))
+            .map_err(|err| DataError::from_csv(path, Some(line), err))?;
+        records.push((line, record));
+    }
+    Ok(records)
+}
+
+/// Game data loaded at runtime, as an alternative to the tables baked in at build time.
+pub struct DataSet {
+    items: HashMap<u32, Item>,
+    item_names: HashMap<u32, String>,
+    recipe_levels: Vec<RecipeLevel>,
+    recipes: Vec<Recipe>,
+}
+
+impl DataSet {
+    pub fn recipes(&self) -> &[Recipe] {
+        &self.recipes
+    }
+
+    /// Same as `RLVLS[rlvl]`, but from the loaded recipe level table.
+    pub fn get_recipe_level(&self, rlvl: u16) -> Option<&RecipeLevel> {
+        self.recipe_levels.get(rlvl as usize)
+    }
+
+    pub fn get_item(&self, item_id: u32) -> Option<&Item> {
+        self.items.get(&item_id)
+    }
+
+    pub fn get_item_name(&self, item_id: u32, hq: bool) -> String {
+        let item_name = self
+            .item_names
+            .get(&item_id)
+            .map(String::as_str)
+            .unwrap_or("Unknown item");
+        match hq {
+            true => format!("{} (HQ)", item_name),
+            false => item_name.to_string(),
+        }
+    }
+}
+
+/// Load items, recipes and recipe levels from CSV files in the same format as `data/<lang>/Item.csv`,
+/// `data/Recipe.csv` and `data/RecipeLevelTable.csv`.
+pub fn load_from_csv(
+    items: &Path,
+    recipes: &Path,
+    recipe_levels: &Path,
+) -> Result<DataSet, DataError> {
+    let recipe_levels = load_recipe_levels(recipe_levels)?;
+    let mut relevant_items: HashSet<u32> = HashSet::new();
+    let recipes = load_recipes(recipes, &recipe_levels, &mut relevant_items)?;
+
+    let mut item_stats = HashMap::new();
+    let mut item_names = HashMap::new();
+    for (_, item) in read_records::<ItemRecord>(items)? {
+        if !relevant_items.contains(&item.id) {
+            continue;
+        }
+        item_stats.insert(
+            item.id,
+            Item {
+                item_level: item.item_level as u16,
+                icon_id: item.icon_id,
+                can_be_hq: item.can_be_hq,
+                is_collectable: item.is_collectable,
+            },
+        );
+        item_names.insert(item.id, item.name.replace("<SoftHyphen/>", ""));
+    }
+
+    Ok(DataSet {
+        items: item_stats,
+        item_names,
+        recipe_levels,
+        recipes,
+    })
+}
+
+/// Rows are indexed by their position in the file, same as in the build script.
+fn load_recipe_levels(path: &Path) -> Result<Vec<RecipeLevel>, DataError> {
+    let mut recipe_levels = Vec::new();
+    for (line, record) in read_records::<RecipeLevelRecord>(path)? {
+        let to_u16 = |value: u32| {
+            u16::try_from(value)
+                .map_err(|_| DataError::new(path, Some(line), "recipe level stats out of range"))
+        };
+        recipe_levels.push(RecipeLevel {
+            level: record.level,
+            progress: to_u16(record.progress)?,
+            quality: to_u16(record.quality)?,
+            durability: to_u16(record.durability)?,
+            progress_div: to_u16(record.progress_divider)?,
+            quality_div: to_u16(record.quality_divider)?,
+            progress_mod: to_u16(record.progress_modifier)?,
+            quality_mod: to_u16(record.quality_modifier)?,
+        });
+    }
+    Ok(recipe_levels)
+}
+
+fn load_recipes(
+    path: &Path,
+    recipe_levels: &[RecipeLevel],
+    relevant_items: &mut HashSet<u32>,
+) -> Result<Vec<Recipe>, DataError> {
+    fn apply_factor(base: u16, factor: u32) -> u32 {
+        base as u32 * factor / 100
+    }
+
+    let mut recipes = Vec::new();
+    for (line, recipe_record) in read_records::<RecipeRecord>(path)? {
+        // skip the debug recipe (item id 0)
+        if recipe_record.resulting_item == 0 {
+            continue;
+        }
+        let Some(rlvl) = recipe_levels.get(recipe_record.recipe_level as usize) else {
+            return Err(DataError::new(
+                path,
+                Some(line),
+                format!("unknown recipe level {}", recipe_record.recipe_level),
+            ));
+        };
+        let ingredients = [
+            (
+                recipe_record.ingredient_id_0,
+                recipe_record.ingredient_amount_0,
+            ),
+            (
+                recipe_record.ingredient_id_1,
+                recipe_record.ingredient_amount_1,
+            ),
+            (
+                recipe_record.ingredient_id_2,
+                recipe_record.ingredient_amount_2,
+            ),
+            (
+                recipe_record.ingredient_id_3,
+                recipe_record.ingredient_amount_3,
+            ),
+            (
+                recipe_record.ingredient_id_4,
+                recipe_record.ingredient_amount_4,
+            ),
+            (
+                recipe_record.ingredient_id_5,
+                recipe_record.ingredient_amount_5,
+            ),
+        ]
+        .map(|(item_id, amount)| Ingredient { item_id, amount });
+
+        relevant_items.insert(recipe_record.resulting_item);
+        relevant_items.extend(ingredients.iter().map(|ingredient| ingredient.item_id));
+
+        let progress = apply_factor(rlvl.progress, recipe_record.progress_factor);
+        let quality = apply_factor(rlvl.quality, recipe_record.quality_factor);
+        let durability = apply_factor(rlvl.durability, recipe_record.durability_factor);
+        let (Ok(progress), Ok(quality), Ok(durability)) = (
+            u16::try_from(progress),
+            u16::try_from(quality),
+            u16::try_from(durability),
+        ) else {
+            return Err(DataError::new(
+                path,
+                Some(line),
+                "recipe stats out of range",
+            ));
+        };
+
+        recipes.push(Recipe {
+            job_id: recipe_record.job_id,
+            item_id: recipe_record.resulting_item,
+            level: rlvl.level,
+            recipe_level: recipe_record.recipe_level as u16,
+            progress,
+            quality,
+            durability,
+            material_quality_factor: recipe_record.material_quality_factor as u16,
+            ingredients,
+            is_expert: recipe_record.is_expert,
+        });
+    }
+    Ok(recipes)
+}
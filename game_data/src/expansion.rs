@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rlvl_to_clvl;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Expansion {
+    ARealmReborn,
+    Heavensward,
+    Stormblood,
+    Shadowbringers,
+    Endwalker,
+    Dawntrail,
+}
+
+impl Expansion {
+    /// All expansions, from oldest to newest.
+    pub const ALL: [Expansion; 6] = [
+        Expansion::ARealmReborn,
+        Expansion::Heavensward,
+        Expansion::Stormblood,
+        Expansion::Shadowbringers,
+        Expansion::Endwalker,
+        Expansion::Dawntrail,
+    ];
+
+    pub const fn short_name(self) -> &'static str {
+        match self {
+            Expansion::ARealmReborn => "ARR",
+            Expansion::Heavensward => "HW",
+            Expansion::Stormblood => "SB",
+            Expansion::Shadowbringers => "ShB",
+            Expansion::Endwalker => "EW",
+            Expansion::Dawntrail => "DT",
+        }
+    }
+}
+
+impl std::fmt::Display for Expansion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Expansion::ARealmReborn => "A Realm Reborn",
+            Expansion::Heavensward => "Heavensward",
+            Expansion::Stormblood => "Stormblood",
+            Expansion::Shadowbringers => "Shadowbringers",
+            Expansion::Endwalker => "Endwalker",
+            Expansion::Dawntrail => "Dawntrail",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Expansion that introduced recipes of the given recipe level.
+/// Each expansion raises the level cap by 10, so star recipes belong to the expansion of their class level.
+/// Returns `None` if the recipe level doesn't exist.
+pub fn expansion_for_rlvl(rlvl: u16) -> Option<Expansion> {
+    let expansion = match rlvl_to_clvl(rlvl)? {
+        0..=50 => Expansion::ARealmReborn,
+        51..=60 => Expansion::Heavensward,
+        61..=70 => Expansion::Stormblood,
+        71..=80 => Expansion::Shadowbringers,
+        81..=90 => Expansion::Endwalker,
+        _ => Expansion::Dawntrail,
+    };
+    Some(expansion)
+}
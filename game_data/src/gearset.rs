@@ -0,0 +1,63 @@
+use crate::CrafterStats;
+
+#[derive(Clone, Copy)]
+enum GearsetStat {
+    Craftsmanship,
+    Control,
+    Cp,
+}
+
+impl GearsetStat {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword.to_ascii_lowercase().as_str() {
+            "craftsmanship" | "craft" => Some(Self::Craftsmanship),
+            "control" => Some(Self::Control),
+            "cp" => Some(Self::Cp),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the Craftsmanship, Control and CP of a gearset exported as free text, e.g. `"Craftsmanship: 3858, Control: 4057, CP: 687"`.
+/// The stats may appear in any order and each stat name may be followed by any separator before its value.
+/// Only the three parsed stats are set in the returned `CrafterStats`, all other fields are left at their defaults.
+pub fn parse_gearset(text: &str) -> Result<CrafterStats, &'static str> {
+    let mut values: [Option<u16>; 3] = [None; 3];
+    let mut pending_stat: Option<GearsetStat> = None;
+    let tokens = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| !token.is_empty());
+    for token in tokens {
+        if token.bytes().all(|b| b.is_ascii_digit()) {
+            let Some(stat) = pending_stat.take() else {
+                return Err("Value without a stat name");
+            };
+            let value = token.parse().map_err(|_| "Value out of range")?;
+            if values[stat as usize].replace(value).is_some() {
+                return Err("Stat given more than once");
+            }
+        } else if let Some(stat) = GearsetStat::from_keyword(token) {
+            if pending_stat.replace(stat).is_some() {
+                return Err("Stat name without a value");
+            }
+        }
+    }
+    if pending_stat.is_some() {
+        return Err("Stat name without a value");
+    }
+    let [Some(craftsmanship), Some(control), Some(cp)] = values else {
+        return Err("Craftsmanship, Control and CP are required");
+    };
+    if craftsmanship > CrafterStats::MAX_CRAFTSMANSHIP
+        || control > CrafterStats::MAX_CONTROL
+        || cp > CrafterStats::MAX_CP
+    {
+        return Err("Value out of range");
+    }
+    Ok(CrafterStats {
+        craftsmanship,
+        control,
+        cp,
+        ..Default::default()
+    })
+}
@@ -7,12 +7,35 @@ pub use config::*;
 mod locales;
 pub use locales::*;
 
+mod dataset;
+pub use dataset::*;
+
+mod rotations;
+pub use rotations::*;
+
+mod teamcraft;
+pub use teamcraft::*;
+
+mod action_encoding;
+pub use action_encoding::*;
+
+mod expansion;
+pub use expansion::*;
+
+mod gearset;
+pub use gearset::*;
+
+mod trace_csv;
+pub use trace_csv::*;
+
 use serde::{Deserialize, Serialize};
-use simulator::{Action, ActionMask, Settings};
+use simulator::{Action, ActionMask, Condition, Settings, SimulationState};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Item {
     pub item_level: u16,
+    /// Id of the item's icon in the game files, see `item_icon_path`.
+    pub icon_id: u32,
     pub can_be_hq: bool,
     pub is_collectable: bool,
 }
@@ -25,6 +48,10 @@ pub struct Ingredient {
 
 #[derive(Debug, Clone, Copy)]
 pub struct RecipeLevel {
+    pub level: u8,
+    pub progress: u16,
+    pub quality: u16,
+    pub durability: u16,
     pub progress_div: u16,
     pub quality_div: u16,
     pub progress_mod: u16,
@@ -39,37 +66,125 @@ pub struct Recipe {
     pub recipe_level: u16,
     pub progress: u16,
     pub quality: u16,
-    pub durability: i8,
+    pub durability: u16,
     pub material_quality_factor: u16,
+    /// Recipes have at most 6 ingredients. Unused slots have an `item_id` and `amount` of 0, see `ingredients_iter`.
     pub ingredients: [Ingredient; 6],
     pub is_expert: bool,
 }
 
+impl Recipe {
+    /// The used ingredient slots, together with their index, which is also the index into per-ingredient arrays
+    /// such as the HQ amounts of `get_initial_quality`.
+    /// Slots with an `item_id` or `amount` of 0 are skipped.
+    pub fn ingredients_iter(&self) -> impl Iterator<Item = (usize, Ingredient)> {
+        self.ingredients
+            .into_iter()
+            .enumerate()
+            .filter(|(_, ingredient)| ingredient.item_id != 0 && ingredient.amount != 0)
+    }
+}
+
 pub const RLVLS: [RecipeLevel; 800] = include!(concat!(env!("OUT_DIR"), "/rlvls.rs"));
 pub const RECIPES: &[Recipe] = include!(concat!(env!("OUT_DIR"), "/recipes.rs"));
 
 pub static ITEMS: phf::OrderedMap<u32, Item> = include!(concat!(env!("OUT_DIR"), "/items.rs"));
 
+/// XIVAPI serves the game's icons as PNG files, grouped into folders of 1000 icons.
+const ICON_BASE_URL: &str = "https://xivapi.com/i";
+
+/// Path of the item's icon, relative to `ICON_BASE_URL` and to the `assets/item-icons` folder of native builds.
+/// Returns `None` for unknown items and items without an icon.
+pub fn item_icon_path(item_id: u32) -> Option<String> {
+    let icon_id = ITEMS.get(&item_id)?.icon_id;
+    if icon_id == 0 {
+        return None;
+    }
+    Some(format!("{:06}/{:06}.png", icon_id / 1000 * 1000, icon_id))
+}
+
+/// URL of the item's icon on XIVAPI, for builds that don't bundle the icons.
+/// Returns `None` for unknown items and items without an icon.
+pub fn item_icon_url(item_id: u32) -> Option<String> {
+    Some(format!("{}/{}", ICON_BASE_URL, item_icon_path(item_id)?))
+}
+
+/// Class level (the level shown in the in-game recipe list) of the given recipe level.
+/// Returns `None` if the recipe level doesn't exist.
+pub fn rlvl_to_clvl(rlvl: u16) -> Option<u8> {
+    RLVLS.get(rlvl as usize).map(|rlvl| rlvl.level)
+}
+
+/// Lowest recipe level that belongs to the given class level.
+/// Star recipes and recipes of later patches share their class level with this one but have a higher recipe level.
+pub fn clvl_to_rlvl(clvl: u8) -> u16 {
+    // class levels are non-decreasing in the recipe level table
+    let rlvl = RLVLS.partition_point(|rlvl| rlvl.level < clvl);
+    std::cmp::min(rlvl, RLVLS.len() - 1) as u16
+}
+
+/// All recipes that result in the given item.
+/// Many items can be crafted by more than one job, each with its own recipe.
+pub fn get_recipes_for_item(item_id: u32) -> impl Iterator<Item = &'static Recipe> {
+    RECIPES
+        .iter()
+        .filter(move |recipe| recipe.item_id == item_id)
+}
+
+/// Item whose recipe is selected before the user has picked a recipe.
+pub const DEFAULT_RECIPE_ITEM_ID: u32 = 41815;
+
+/// The recipe in `recipes` that crafts `DEFAULT_RECIPE_ITEM_ID`, or the first recipe if that item is no longer craftable.
+/// Returns `None` only if `recipes` is empty.
+pub fn default_recipe(recipes: &[Recipe]) -> Option<Recipe> {
+    recipes
+        .iter()
+        .find(|recipe| recipe.item_id == DEFAULT_RECIPE_ITEM_ID)
+        .or(recipes.first())
+        .copied()
+}
+
+/// Simulator settings for crafting `recipe` with the given stats and consumables.
+///
+/// The recipe level's progress and quality modifiers only apply while the crafter's level is at or below the recipe's level.
+/// Crafting a lower-level recipe uses the unmodified values; there is no further level-difference scaling.
+///
+/// Returns an error if the recipe's durability is less than 5, which is what a single action costs,
+/// if the recipe level doesn't exist, or if the stats aren't enough to make any Progress at all.
 pub fn get_game_settings(
     recipe: Recipe,
     crafter_stats: CrafterStats,
     food: Option<Consumable>,
     potion: Option<Consumable>,
     adversarial: bool,
-) -> Settings {
-    let rlvl = &RLVLS[recipe.recipe_level as usize];
+) -> Result<Settings, &'static str> {
+    if recipe.durability < 5 {
+        return Err("Recipe durability must be at least 5");
+    }
 
-    let craftsmanship = crafter_stats.craftsmanship
-        + craftsmanship_bonus(crafter_stats.craftsmanship, &[food, potion]);
-    let control = crafter_stats.control + control_bonus(crafter_stats.control, &[food, potion]);
-    let cp = crafter_stats.cp + cp_bonus(crafter_stats.cp, &[food, potion]);
+    let rlvl = RLVLS
+        .get(recipe.recipe_level as usize)
+        .ok_or("Unknown recipe level")?;
 
-    let mut base_progress = craftsmanship as f32 * 10.0 / rlvl.progress_div as f32 + 2.0;
-    let mut base_quality = control as f32 * 10.0 / rlvl.quality_div as f32 + 35.0;
-    if crafter_stats.level <= recipe.level {
-        base_progress = base_progress * rlvl.progress_mod as f32 / 100.0;
-        base_quality = base_quality * rlvl.quality_mod as f32 / 100.0;
-    }
+    let craftsmanship = crafter_stats
+        .craftsmanship
+        .saturating_add(craftsmanship_bonus(
+            crafter_stats.craftsmanship,
+            &[food, potion],
+        ));
+    let control = crafter_stats
+        .control
+        .saturating_add(control_bonus(crafter_stats.control, &[food, potion]));
+    let cp = crafter_stats
+        .cp
+        .saturating_add(cp_bonus(crafter_stats.cp, &[food, potion]));
+
+    let (base_progress, base_quality) = base_values(
+        craftsmanship,
+        control,
+        rlvl,
+        crafter_stats.level <= recipe.level,
+    )?;
 
     let mut allowed_actions = ActionMask::from_level(crafter_stats.level as _);
     if !crafter_stats.manipulation {
@@ -84,36 +199,75 @@ pub fn get_game_settings(
     if !crafter_stats.quick_innovation {
         allowed_actions = allowed_actions.remove(Action::QuickInnovation);
     }
+    if !crafter_stats.careful_observation {
+        allowed_actions = allowed_actions.remove(Action::CarefulObservation);
+    }
 
-    Settings {
-        max_cp: cp as _,
-        max_durability: recipe.durability as _,
+    Ok(Settings {
+        max_cp: i16::try_from(cp).unwrap_or(i16::MAX),
+        // working durability is signed in the simulator, so clamp instead of wrapping around
+        max_durability: i8::try_from(recipe.durability).unwrap_or(i8::MAX),
         max_progress: recipe.progress,
         max_quality: recipe.quality,
-        base_progress: base_progress as u16,
-        base_quality: base_quality as u16,
+        base_progress,
+        base_quality,
         job_level: crafter_stats.level,
         allowed_actions,
         adversarial,
+    })
+}
+
+/// Progress and Quality of a 100% efficiency action, before buffs and conditions.
+/// Returns an error for a recipe level with a divider of 0, which would divide by zero, and if no Progress can be made.
+fn base_values(
+    craftsmanship: u16,
+    control: u16,
+    rlvl: &RecipeLevel,
+    apply_modifiers: bool,
+) -> Result<(u16, u16), &'static str> {
+    if rlvl.progress_div == 0 || rlvl.quality_div == 0 {
+        return Err("Recipe level has a divider of 0");
+    }
+    let mut base_progress = craftsmanship as f32 * 10.0 / rlvl.progress_div as f32 + 2.0;
+    let mut base_quality = control as f32 * 10.0 / rlvl.quality_div as f32 + 35.0;
+    if apply_modifiers {
+        base_progress = base_progress * rlvl.progress_mod as f32 / 100.0;
+        base_quality = base_quality * rlvl.quality_mod as f32 / 100.0;
+    }
+    // 0 base Quality still allows a (NQ) craft, but without any Progress the craft is infeasible
+    if base_progress < 1.0 {
+        return Err("Craftsmanship is too low to make any Progress");
     }
+    Ok((
+        clamp_base_value(base_progress),
+        clamp_base_value(base_quality),
+    ))
 }
 
-pub fn get_initial_quality(recipe: Recipe, hq_ingredients: [u8; 6]) -> u16 {
-    let ingredients: Vec<(Item, u32)> = recipe
-        .ingredients
-        .iter()
-        .filter_map(|ingredient| match ingredient.item_id {
-            0 => None,
-            id => Some((*ITEMS.get(&id).unwrap(), ingredient.amount)),
-        })
-        .collect();
+/// Converts a base value to u16, clamping values that don't fit and mapping NaN to 0.
+fn clamp_base_value(value: f32) -> u16 {
+    if value.is_nan() {
+        0
+    } else if value >= u16::MAX as f32 {
+        u16::MAX
+    } else {
+        value.max(0.0) as u16
+    }
+}
 
+/// Quality contributed by the HQ ingredients, where `hq_ingredients[i]` is the number of HQ items used for the i-th ingredient.
+/// Ingredients that aren't in the item table (e.g. in a hand-edited recipe) don't contribute to the initial quality.
+pub fn get_initial_quality(recipe: Recipe, hq_ingredients: [u8; 6]) -> u16 {
     let mut max_ilvl = 0;
     let mut provided_ilvl = 0;
-    for (index, (item, max_amount)) in ingredients.into_iter().enumerate() {
+    for (index, ingredient) in recipe.ingredients_iter() {
+        let hq_amount = hq_ingredients[index];
+        let Some(item) = ITEMS.get(&ingredient.item_id) else {
+            continue;
+        };
         if item.can_be_hq {
-            max_ilvl += max_amount as u16 * item.item_level;
-            provided_ilvl += hq_ingredients[index] as u16 * item.item_level;
+            max_ilvl += ingredient.amount as u16 * item.item_level;
+            provided_ilvl += hq_amount as u16 * item.item_level;
         }
     }
 
@@ -126,16 +280,103 @@ pub fn get_initial_quality(recipe: Recipe, hq_ingredients: [u8; 6]) -> u16 {
     }
 }
 
-const HQ_LOOKUP: [u8; 101] = [
-    1, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 6, 6, 6, 6, 7, 7, 7, 7, 8, 8, 8,
-    9, 9, 9, 10, 10, 10, 11, 11, 11, 12, 12, 12, 13, 13, 13, 14, 14, 14, 15, 15, 15, 16, 16, 17,
-    17, 17, 18, 18, 18, 19, 19, 20, 20, 21, 22, 23, 24, 26, 28, 31, 34, 38, 42, 47, 52, 58, 64, 68,
-    71, 74, 76, 78, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 94, 96, 98, 100,
-];
+/// HQ chance for each percentage of max Quality, from 0% to 100%.
+pub const HQ_PERCENTAGES: [u8; 101] = include!(concat!(env!("OUT_DIR"), "/hq_percentages.rs"));
+
+/// Time the game adds on top of each action's wait time, in milliseconds.
+pub const DEFAULT_ACTION_OVERHEAD_MS: u16 = 1000;
+/// Time it takes to start the next macro when chaining macros, in milliseconds.
+pub const MACRO_CHAIN_DELAY_MS: u16 = 1500;
+
+/// Estimated time in seconds that the rotation takes in-game when split into `macro_count` macros.
+/// `action_overhead_ms` is added to every action to account for client and server latency.
+pub fn estimated_realtime_seconds(
+    actions: &[Action],
+    macro_count: usize,
+    action_overhead_ms: u16,
+) -> f32 {
+    let wait_time_ms: u64 = actions
+        .iter()
+        .map(|action| action.time_cost() as u64 * 1000 + action_overhead_ms as u64)
+        .sum();
+    let chain_delay_ms = macro_count.saturating_sub(1) as u64 * MACRO_CHAIN_DELAY_MS as u64;
+    (wait_time_ms + chain_delay_ms) as f32 / 1000.0
+}
+
+/// Sum of the CP costs of the actions.
+/// Combos are modeled as separate actions (e.g. `Action::ComboStandardTouch`), so their discount is already included,
+/// but the CP restored by Tricks of the Trade is not subtracted, see `cp_used`.
+pub fn total_cp_cost(actions: &[Action]) -> i16 {
+    actions.iter().map(|action| action.cp_cost()).sum()
+}
+
+/// CP that the rotation actually consumes when started from `initial_state`, i.e. `total_cp_cost` minus the CP
+/// restored by Tricks of the Trade, which can't restore more than `settings.max_cp`.
+/// Returns the error of the first action that can't be used.
+pub fn cp_used(
+    settings: &Settings,
+    initial_state: &SimulationState,
+    actions: &[Action],
+) -> Result<i16, &'static str> {
+    let mut state = *initial_state;
+    for action in actions {
+        state = state.use_action(*action, Condition::Normal, settings)?;
+    }
+    Ok(initial_state.cp - state.cp)
+}
 
 pub fn hq_percentage(quality: u16, max_quality: u16) -> u8 {
     // TODO: switch to std::num::NonZeroU32 at some point
     assert!(max_quality != 0, "max_quality must be non-zero");
     let ratio = std::cmp::min(quality, max_quality) as f64 / max_quality as f64;
-    HQ_LOOKUP[(ratio * 100.0).floor() as usize]
+    HQ_PERCENTAGES[(ratio * 100.0).floor() as usize]
+}
+
+/// Smallest Quality for which `hq_percentage` is at least `hq_percentage`.
+/// Clamped to `max_quality` if the percentage cannot be reached.
+pub fn quality_for_hq_percentage(hq_percentage: u8, max_quality: u16) -> u16 {
+    if max_quality == 0 {
+        return 0;
+    }
+    // hq_percentage is monotonic in quality, so search for the first quality that reaches the percentage
+    let (mut low, mut high) = (0, max_quality);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if self::hq_percentage(mid, max_quality) >= hq_percentage {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_values_zero_divider() {
+        let rlvl = RecipeLevel {
+            progress_div: 0,
+            ..RLVLS[1]
+        };
+        assert!(base_values(4000, 4000, &rlvl, false).is_err());
+        let rlvl = RecipeLevel {
+            quality_div: 0,
+            ..RLVLS[1]
+        };
+        assert!(base_values(4000, 4000, &rlvl, false).is_err());
+    }
+
+    #[test]
+    fn test_base_values_tiny_stats() {
+        // without any Craftsmanship, only the constant +2 is left, which the modifier can scale below 1
+        let rlvl = RecipeLevel {
+            progress_mod: 40,
+            ..RLVLS[1]
+        };
+        assert!(base_values(0, 0, &rlvl, true).is_err());
+        assert_eq!(base_values(0, 0, &rlvl, false), Ok((2, 35)));
+    }
 }
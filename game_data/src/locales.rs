@@ -43,30 +43,85 @@ static ITEM_NAMES_JP: phf::Map<u32, &'static str> =
     include!(concat!(env!("OUT_DIR"), "/item_names_jp.rs"));
 
 pub fn get_item_name(item_id: u32, hq: bool, locale: Locale) -> String {
-    let item_name = match locale {
-        Locale::EN => ITEM_NAMES_EN
-            .get(&item_id)
-            .copied()
-            .unwrap_or("Unknown item"),
-        Locale::DE => ITEM_NAMES_DE
-            .get(&item_id)
-            .copied()
-            .unwrap_or("Unknown item"),
-        Locale::FR => ITEM_NAMES_FR
-            .get(&item_id)
-            .copied()
-            .unwrap_or("Unknown item"),
-        Locale::JP => ITEM_NAMES_JP
-            .get(&item_id)
-            .copied()
-            .unwrap_or("Unknown item"),
-    };
+    let item_name = item_names(locale)
+        .get(&item_id)
+        .copied()
+        .unwrap_or("Unknown item");
     match hq {
         true => format!("{} (HQ)", item_name),
         false => item_name.to_string(),
     }
 }
 
+fn item_names(locale: Locale) -> &'static phf::Map<u32, &'static str> {
+    match locale {
+        Locale::EN => &ITEM_NAMES_EN,
+        Locale::DE => &ITEM_NAMES_DE,
+        Locale::FR => &ITEM_NAMES_FR,
+        Locale::JP => &ITEM_NAMES_JP,
+    }
+}
+
+/// Ids of all items with this name, in increasing order.
+/// The build script appends the item id to names that are shared by several items (e.g. "Name (1234)"),
+/// so the shared name resolves to all of those items and the disambiguated name to exactly one.
+pub fn resolve_name_to_ids(name: &str, locale: Locale) -> Vec<u32> {
+    let mut item_ids: Vec<u32> = item_names(locale)
+        .entries()
+        .filter(|(item_id, item_name)| {
+            **item_name == name
+                || item_name
+                    .strip_prefix(name)
+                    .is_some_and(|suffix| *suffix == format!(" ({})", item_id))
+        })
+        .map(|(item_id, _)| *item_id)
+        .collect();
+    item_ids.sort_unstable();
+    item_ids
+}
+
+/// Name of the craftable item that is closest to `query` in (case-insensitive) edit distance.
+/// Only names within an edit distance of a third of the query's length are considered.
+/// Returns `None` if no name is close enough.
+pub fn closest_recipe_name(query: &str, locale: Locale) -> Option<&'static str> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    let max_distance = std::cmp::max(1, query.len() / 3);
+    let item_names = item_names(locale);
+    let mut best_match: Option<(usize, &'static str)> = None;
+    for recipe in crate::RECIPES.iter() {
+        let Some(item_name) = item_names.get(&recipe.item_id).copied() else {
+            continue;
+        };
+        let name: Vec<char> = item_name.to_lowercase().chars().collect();
+        // the edit distance is at least the difference in length
+        if name.len().abs_diff(query.len()) > max_distance {
+            continue;
+        }
+        let distance = edit_distance(&query, &name);
+        if distance <= max_distance && best_match.is_none_or(|(best, _)| distance < best) {
+            best_match = Some((distance, item_name));
+        }
+    }
+    best_match.map(|(_, item_name)| item_name)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    let mut current_row = vec![0; b.len() + 1];
+    for (i, char_a) in a.iter().enumerate() {
+        current_row[0] = i + 1;
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution_cost = usize::from(char_a != char_b);
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    previous_row[b.len()]
+}
+
 pub const fn action_name(action: Action, locale: Locale) -> &'static str {
     match locale {
         Locale::EN => action_name_en(action),
@@ -106,6 +161,8 @@ const fn action_name_en(action: Action) -> &'static str {
         Action::ComboRefinedTouch => "Refined Touch",
         Action::ImmaculateMend => "Immaculate Mend",
         Action::TrainedPerfection => "Trained Perfection",
+        Action::TricksOfTheTrade => "Tricks of the Trade",
+        Action::CarefulObservation => "Careful Observation",
         Action::TrainedEye => "Trained Eye",
         Action::QuickInnovation => "Quick Innovation",
     }
@@ -141,6 +198,8 @@ const fn action_name_de(action: Action) -> &'static str {
         Action::ComboRefinedTouch => "Raffinierte Veredelung",
         Action::ImmaculateMend => "Winkelzug",
         Action::TrainedPerfection => "Meisters Beitrag",
+        Action::TricksOfTheTrade => "Kunstgriff",
+        Action::CarefulObservation => "Sorgfältige Beobachtung",
         Action::TrainedEye => "Flinke Hand",
         Action::QuickInnovation => "Spontane Innovation",
     }
@@ -176,6 +235,8 @@ const fn action_name_fr(action: Action) -> &'static str {
         Action::ComboRefinedTouch => "Ouvrage raffiné",
         Action::ImmaculateMend => "Réparation totale",
         Action::TrainedPerfection => "Main suprême",
+        Action::TricksOfTheTrade => "Ficelles du métier",
+        Action::CarefulObservation => "Observation attentive",
         Action::TrainedEye => "Main preste",
         Action::QuickInnovation => "Innovation instantanée",
     }
@@ -211,6 +272,8 @@ const fn action_name_jp(action: Action) -> &'static str {
         Action::ComboRefinedTouch => "洗練加工",
         Action::ImmaculateMend => "パーフェクトメンド",
         Action::TrainedPerfection => "匠の絶技",
+        Action::TricksOfTheTrade => "秘訣",
+        Action::CarefulObservation => "設計変更",
         Action::TrainedEye => "匠の早業",
         Action::QuickInnovation => "クイックイノベーション",
     }
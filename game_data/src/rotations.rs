@@ -0,0 +1,87 @@
+use simulator::Action;
+
+/// Hand-written rotation that can be used without running the solver.
+/// Whether it maxes out Progress and Quality depends on the crafter's stats and the recipe.
+#[derive(Debug, Clone, Copy)]
+pub struct ReferenceRotation {
+    pub name: &'static str,
+    /// Crafter level needed to use every action of the rotation
+    pub level: u8,
+    /// Recipe durability the rotation is designed for
+    pub durability: u16,
+    /// CP needed to use every action of the rotation
+    pub cp: u16,
+    pub actions: &'static [Action],
+}
+
+const REFERENCE_ROTATIONS: &[ReferenceRotation] = &[
+    ReferenceRotation {
+        name: "Standard 70-durability",
+        level: 92,
+        durability: 70,
+        cp: 580,
+        actions: &[
+            Action::MuscleMemory,
+            Action::Manipulation,
+            Action::Veneration,
+            Action::WasteNot2,
+            Action::Groundwork,
+            Action::Groundwork,
+            Action::Innovation,
+            Action::PreparatoryTouch,
+            Action::PreparatoryTouch,
+            Action::PreparatoryTouch,
+            Action::PreparatoryTouch,
+            Action::Innovation,
+            Action::BasicTouch,
+            Action::ComboRefinedTouch,
+            Action::PrudentTouch,
+            Action::GreatStrides,
+            Action::ByregotsBlessing,
+            Action::CarefulSynthesis,
+        ],
+    },
+    ReferenceRotation {
+        name: "CP-light 80-durability",
+        level: 72,
+        durability: 80,
+        cp: 258,
+        actions: &[
+            Action::MuscleMemory,
+            Action::Veneration,
+            Action::WasteNot,
+            Action::Groundwork,
+            Action::Groundwork,
+            Action::CarefulSynthesis,
+            Action::Innovation,
+            Action::BasicTouch,
+            Action::ComboStandardTouch,
+            Action::ComboAdvancedTouch,
+            Action::GreatStrides,
+            Action::ByregotsBlessing,
+            Action::CarefulSynthesis,
+        ],
+    },
+    ReferenceRotation {
+        name: "Reflect opener 40-durability",
+        level: 69,
+        durability: 40,
+        cp: 218,
+        actions: &[
+            Action::Reflect,
+            Action::Innovation,
+            Action::BasicTouch,
+            Action::ComboStandardTouch,
+            Action::MasterMend,
+            Action::GreatStrides,
+            Action::ByregotsBlessing,
+            Action::CarefulSynthesis,
+            Action::CarefulSynthesis,
+        ],
+    },
+];
+
+/// Curated rotations for common recipe durabilities, as a fallback for when the solver is too slow.
+pub fn reference_rotations() -> &'static [ReferenceRotation] {
+    REFERENCE_ROTATIONS
+}
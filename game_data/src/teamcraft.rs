@@ -0,0 +1,52 @@
+use simulator::Action;
+
+/// Teamcraft's crafting simulator, which can import a rotation exported with `to_teamcraft_rotation`.
+pub const TEAMCRAFT_SIMULATOR_URL: &str = "https://ffxivteamcraft.com/simulator/custom";
+
+/// Name of the action in Teamcraft's action registry.
+/// Teamcraft doesn't distinguish between the combo and out-of-combo versions of an action.
+pub const fn teamcraft_action_name(action: Action) -> &'static str {
+    match action {
+        Action::BasicSynthesis => "BasicSynthesis",
+        Action::BasicTouch => "BasicTouch",
+        Action::MasterMend => "MastersMend",
+        Action::Observe => "Observe",
+        Action::WasteNot => "WasteNot",
+        Action::Veneration => "Veneration",
+        Action::StandardTouch | Action::ComboStandardTouch => "StandardTouch",
+        Action::GreatStrides => "GreatStrides",
+        Action::Innovation => "Innovation",
+        Action::WasteNot2 => "WasteNotII",
+        Action::ByregotsBlessing => "ByregotsBlessing",
+        Action::PreciseTouch => "PreciseTouch",
+        Action::MuscleMemory => "MuscleMemory",
+        Action::CarefulSynthesis => "CarefulSynthesis",
+        Action::Manipulation => "Manipulation",
+        Action::PrudentTouch => "PrudentTouch",
+        Action::AdvancedTouch | Action::ComboAdvancedTouch => "AdvancedTouch",
+        Action::Reflect => "Reflect",
+        Action::PreparatoryTouch => "PreparatoryTouch",
+        Action::Groundwork => "Groundwork",
+        Action::DelicateSynthesis => "DelicateSynthesis",
+        Action::IntensiveSynthesis => "IntensiveSynthesis",
+        Action::HeartAndSoul => "HeartAndSoul",
+        Action::PrudentSynthesis => "PrudentSynthesis",
+        Action::TrainedFinesse => "TrainedFinesse",
+        Action::TrainedEye => "TrainedEye",
+        Action::ComboRefinedTouch => "RefinedTouch",
+        Action::QuickInnovation => "QuickInnovation",
+        Action::ImmaculateMend => "ImmaculateMend",
+        Action::TrainedPerfection => "TrainedPerfection",
+        Action::TricksOfTheTrade => "TricksOfTheTrade",
+        Action::CarefulObservation => "CarefulObservation",
+    }
+}
+
+/// Rotation in the format of Teamcraft's rotation import, i.e. a JSON array of action names.
+pub fn to_teamcraft_rotation(actions: &[Action]) -> String {
+    let names: Vec<String> = actions
+        .iter()
+        .map(|action| format!("\"{}\"", teamcraft_action_name(*action)))
+        .collect();
+    format!("[{}]", names.join(","))
+}
@@ -0,0 +1,42 @@
+use simulator::{Action, ActionReplay, Settings, SimulationState};
+
+use crate::{action_name, Locale};
+
+/// Header of the CSV produced by `to_trace_csv`. The column order is stable so that spreadsheets built on top of it keep working.
+pub const TRACE_CSV_HEADER: &str = "step,action,progress,quality,durability,cp,inner_quiet,innovation,veneration,great_strides,waste_not,manipulation,muscle_memory";
+
+/// Export the state after each step of the macro as CSV, one row per step, using the English action names.
+/// The macro is replayed from `initial_state`, and Quality includes `initial_quality`.
+/// The trace stops before the first action that can't be used.
+pub fn to_trace_csv(
+    settings: &Settings,
+    initial_state: &SimulationState,
+    initial_quality: u16,
+    actions: &[Action],
+) -> String {
+    let mut lines = vec![TRACE_CSV_HEADER.to_string()];
+    for (index, step) in ActionReplay::from_state(settings, *initial_state, actions.iter().copied())
+        .breakdown()
+        .enumerate()
+    {
+        let state = step.state;
+        let effects = state.effects;
+        lines.push(format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            index + 1,
+            action_name(step.action, Locale::EN),
+            state.progress,
+            state.get_quality() + initial_quality,
+            state.durability,
+            state.cp,
+            effects.inner_quiet(),
+            effects.innovation(),
+            effects.veneration(),
+            effects.great_strides(),
+            effects.waste_not(),
+            effects.manipulation(),
+            effects.muscle_memory(),
+        ));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,29 @@
+use game_data::{action_code, decode_actions, encode_actions};
+use simulator::{Action, ActionMask};
+
+#[test]
+fn test_round_trip() {
+    let actions: Vec<Action> = ActionMask::all().actions_iter().collect();
+    let encoded = encode_actions(&actions);
+    assert_eq!(encoded.len(), actions.len());
+    assert_eq!(decode_actions(&encoded), Ok(actions));
+}
+
+#[test]
+fn test_stable_codes() {
+    // existing codes must never change, otherwise old permalinks decode to different macros
+    assert_eq!(action_code(Action::BasicSynthesis), 0);
+    assert_eq!(action_code(Action::MuscleMemory), 13);
+    assert_eq!(action_code(Action::TricksOfTheTrade), 32);
+    assert_eq!(action_code(Action::CarefulObservation), 33);
+    let mut codes: Vec<u8> = ActionMask::all().actions_iter().map(action_code).collect();
+    codes.sort();
+    codes.dedup();
+    assert_eq!(codes.len(), ActionMask::all().actions_iter().count());
+}
+
+#[test]
+fn test_unknown_code() {
+    assert_eq!(decode_actions(&[0, 255]), Err("Unknown action code"));
+    assert_eq!(decode_actions(&[]), Ok(Vec::new()));
+}
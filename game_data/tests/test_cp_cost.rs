@@ -0,0 +1,78 @@
+use game_data::{cp_used, total_cp_cost};
+use simulator::{Action, ActionMask, Settings, SimulationState};
+
+const SETTINGS: Settings = Settings {
+    max_cp: 200,
+    max_durability: 80,
+    max_progress: 2000,
+    max_quality: 4000,
+    base_progress: 100,
+    base_quality: 100,
+    job_level: 100,
+    allowed_actions: ActionMask::all(),
+    adversarial: false,
+};
+
+/// CP used by a macro that starts with full CP
+fn cp_used_from_max_cp(actions: &[Action]) -> i16 {
+    cp_used(&SETTINGS, &SimulationState::new(&SETTINGS), actions).unwrap()
+}
+
+#[test]
+fn test_combo_cp_cost() {
+    let combo = [
+        Action::BasicTouch,
+        Action::ComboStandardTouch,
+        Action::ComboAdvancedTouch,
+    ];
+    let out_of_combo = [
+        Action::BasicTouch,
+        Action::StandardTouch,
+        Action::AdvancedTouch,
+    ];
+    assert_eq!(total_cp_cost(&combo), 18 + 18 + 18);
+    assert_eq!(cp_used_from_max_cp(&combo), total_cp_cost(&combo));
+    assert!(cp_used_from_max_cp(&combo) < total_cp_cost(&out_of_combo));
+    assert_eq!(cp_used_from_max_cp(&out_of_combo), 18 + 32 + 46);
+}
+
+#[test]
+fn test_tricks_of_the_trade_refund() {
+    let actions = [
+        Action::BasicTouch,
+        Action::ComboStandardTouch,
+        Action::HeartAndSoul,
+        Action::TricksOfTheTrade,
+    ];
+    assert_eq!(total_cp_cost(&actions), 36);
+    assert_eq!(cp_used_from_max_cp(&actions), 36 - 20);
+    // the refund can't go above max CP
+    let actions = [
+        Action::BasicTouch,
+        Action::HeartAndSoul,
+        Action::TricksOfTheTrade,
+    ];
+    assert_eq!(cp_used_from_max_cp(&actions), 0);
+}
+
+#[test]
+fn test_below_max_cp() {
+    let initial_state = SimulationState::with_initial_cp(&SETTINGS, 100);
+    let actions = [Action::BasicTouch, Action::ComboStandardTouch];
+    assert_eq!(cp_used(&SETTINGS, &initial_state, &actions), Ok(36));
+    // Tricks of the Trade can restore more CP than the rotation consumes when starting below max CP
+    let actions = [
+        Action::BasicTouch,
+        Action::HeartAndSoul,
+        Action::TricksOfTheTrade,
+    ];
+    assert_eq!(cp_used(&SETTINGS, &initial_state, &actions), Ok(18 - 20));
+}
+
+#[test]
+fn test_illegal_action() {
+    // Byregot's Blessing needs Inner Quiet
+    let actions = [Action::ByregotsBlessing, Action::BasicTouch];
+    let initial_state = SimulationState::new(&SETTINGS);
+    assert!(cp_used(&SETTINGS, &initial_state, &actions).is_err());
+}
@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+use game_data::{get_item_name, load_from_csv, Locale, ITEMS, RECIPES, RLVLS};
+
+fn data_path(file: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join(file)
+}
+
+/// Temporary directory that is unique to this test, so that parallel test runs don't overwrite each other's files
+fn temp_dir(test_name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("raphael_{}_{}", test_name, std::process::id()));
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+#[test]
+/// Test that loading the bundled CSV files at runtime yields the same data as the baked-in tables
+fn test_load_bundled_data() {
+    let dataset = load_from_csv(
+        &data_path("en/Item.csv"),
+        &data_path("Recipe.csv"),
+        &data_path("RecipeLevelTable.csv"),
+    )
+    .unwrap();
+    assert_eq!(dataset.recipes().len(), RECIPES.len());
+    for (loaded, baked) in dataset.recipes().iter().zip(RECIPES.iter()) {
+        assert_eq!(loaded.item_id, baked.item_id);
+        assert_eq!(loaded.level, baked.level);
+        assert_eq!(loaded.progress, baked.progress);
+        assert_eq!(loaded.quality, baked.quality);
+        assert_eq!(loaded.durability, baked.durability);
+        let item = dataset.get_item(loaded.item_id).unwrap();
+        assert_eq!(
+            item.item_level,
+            ITEMS.get(&baked.item_id).unwrap().item_level
+        );
+        assert_eq!(
+            dataset.get_item_name(loaded.item_id, true),
+            get_item_name(baked.item_id, true, Locale::EN)
+        );
+    }
+    for (rlvl, baked) in RLVLS.iter().enumerate() {
+        let loaded = dataset.get_recipe_level(rlvl as u16).unwrap();
+        assert_eq!(loaded.level, baked.level);
+        assert_eq!(loaded.progress_div, baked.progress_div);
+        assert_eq!(loaded.quality_mod, baked.quality_mod);
+    }
+    assert!(dataset.get_recipe_level(RLVLS.len() as u16).is_none());
+}
+
+#[test]
+/// Test that the recipe stats are computed from the given recipe level table instead of the baked-in one
+fn test_load_recipe_levels() {
+    let dir = temp_dir("test_load_recipe_levels");
+    let path = dir.join("RecipeLevelTable.csv");
+    let recipe_levels = std::fs::read_to_string(data_path("RecipeLevelTable.csv")).unwrap();
+    let rlvl = RECIPES[0].recipe_level as usize;
+    let baked = RLVLS[rlvl];
+    // double the Difficulty (base progress) of the first recipe's level
+    let lines: Vec<String> = recipe_levels
+        .lines()
+        .enumerate()
+        .map(|(index, line)| match index == rlvl + 1 {
+            true => {
+                let mut columns: Vec<String> = line.split(',').map(String::from).collect();
+                columns[4] = (baked.progress * 2).to_string();
+                columns.join(",")
+            }
+            false => line.to_string(),
+        })
+        .collect();
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    let dataset =
+        load_from_csv(&data_path("en/Item.csv"), &data_path("Recipe.csv"), &path).unwrap();
+    assert_eq!(
+        dataset.get_recipe_level(rlvl as u16).unwrap().progress,
+        baked.progress * 2
+    );
+    let loaded = dataset.recipes()[0];
+    assert_eq!(loaded.item_id, RECIPES[0].item_id);
+    assert_eq!(loaded.quality, RECIPES[0].quality);
+    assert!(loaded.progress > RECIPES[0].progress);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_row_level_error() {
+    let dir = temp_dir("test_row_level_error");
+    let path = dir.join("Recipe.csv");
+    let recipes = std::fs::read_to_string(data_path("Recipe.csv")).unwrap();
+    // corrupt a numeric column of the third recipe
+    let mut lines: Vec<String> = recipes.lines().take(4).map(String::from).collect();
+    lines[3] = lines[3].replacen(",1,", ",abc,", 1);
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    let error = match load_from_csv(
+        &data_path("en/Item.csv"),
+        &path,
+        &data_path("RecipeLevelTable.csv"),
+    ) {
+        Ok(_) => panic!("Expected error"),
+        Err(error) => error,
+    };
+    assert_eq!(error.line, Some(4));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
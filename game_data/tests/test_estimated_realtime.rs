@@ -0,0 +1,20 @@
+use game_data::{estimated_realtime_seconds, MACRO_CHAIN_DELAY_MS};
+use simulator::Action;
+
+#[test]
+fn test_estimated_realtime_seconds() {
+    let actions = [
+        Action::MuscleMemory,   // 3s
+        Action::Veneration,     // 2s
+        Action::BasicSynthesis, // 3s
+    ];
+    assert_eq!(estimated_realtime_seconds(&actions, 1, 0), 8.0);
+    assert_eq!(estimated_realtime_seconds(&actions, 1, 1000), 11.0);
+    assert_eq!(estimated_realtime_seconds(&actions, 1, 250), 8.75);
+    // every macro after the first adds the chaining delay
+    assert_eq!(
+        estimated_realtime_seconds(&actions, 3, 0),
+        8.0 + 2.0 * MACRO_CHAIN_DELAY_MS as f32 / 1000.0
+    );
+    assert_eq!(estimated_realtime_seconds(&[], 0, 1000), 0.0);
+}
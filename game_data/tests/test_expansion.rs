@@ -0,0 +1,44 @@
+use game_data::{clvl_to_rlvl, expansion_for_rlvl, Expansion, RECIPES};
+
+#[test]
+fn test_expansion_boundaries() {
+    assert_eq!(expansion_for_rlvl(1), Some(Expansion::ARealmReborn));
+    assert_eq!(
+        expansion_for_rlvl(clvl_to_rlvl(50)),
+        Some(Expansion::ARealmReborn)
+    );
+    assert_eq!(
+        expansion_for_rlvl(clvl_to_rlvl(51)),
+        Some(Expansion::Heavensward)
+    );
+    assert_eq!(
+        expansion_for_rlvl(clvl_to_rlvl(70)),
+        Some(Expansion::Stormblood)
+    );
+    assert_eq!(
+        expansion_for_rlvl(clvl_to_rlvl(71)),
+        Some(Expansion::Shadowbringers)
+    );
+    assert_eq!(
+        expansion_for_rlvl(clvl_to_rlvl(90)),
+        Some(Expansion::Endwalker)
+    );
+    assert_eq!(
+        expansion_for_rlvl(clvl_to_rlvl(91)),
+        Some(Expansion::Dawntrail)
+    );
+    // star recipes share the class level of the level cap
+    assert_eq!(expansion_for_rlvl(640), Some(Expansion::Endwalker));
+    assert_eq!(expansion_for_rlvl(690), Some(Expansion::Dawntrail));
+    assert_eq!(expansion_for_rlvl(u16::MAX), None);
+}
+
+#[test]
+fn test_expansion_matches_recipe_level() {
+    for recipe in RECIPES.iter() {
+        let expansion = expansion_for_rlvl(recipe.recipe_level).unwrap();
+        let max_level =
+            50 + 10 * Expansion::ALL.iter().position(|e| *e == expansion).unwrap() as u8;
+        assert!(recipe.level <= max_level, "{:?}", recipe);
+    }
+}
@@ -0,0 +1,27 @@
+use game_data::parse_gearset;
+
+#[test]
+fn test_parse_gearset() {
+    let stats = parse_gearset("Craftsmanship: 3858, Control: 4057, CP: 687").unwrap();
+    assert_eq!(
+        (stats.craftsmanship, stats.control, stats.cp),
+        (3858, 4057, 687)
+    );
+    // ordering, whitespace and separators don't matter
+    let stats = parse_gearset("  cp=687\n control 4057\tCRAFTSMANSHIP:3858 ").unwrap();
+    assert_eq!(
+        (stats.craftsmanship, stats.control, stats.cp),
+        (3858, 4057, 687)
+    );
+}
+
+#[test]
+fn test_parse_gearset_malformed() {
+    assert!(parse_gearset("").is_err());
+    assert!(parse_gearset("Craftsmanship: 3858, Control: 4057").is_err());
+    assert!(parse_gearset("Craftsmanship: 3858, Control: 4057, CP:").is_err());
+    assert!(parse_gearset("Craftsmanship: 3858, Control: 4057, CP: 687, CP: 600").is_err());
+    assert!(parse_gearset("Craftsmanship: 3858, Control: 4057, 687").is_err());
+    assert!(parse_gearset("Craftsmanship: 3858, Control: 4057, CP: 5000").is_err());
+    assert!(parse_gearset("Craftsmanship: 99999999, Control: 4057, CP: 687").is_err());
+}
@@ -1,7 +1,8 @@
 use game_data::{
-    get_game_settings, get_initial_quality, get_item_name, CrafterStats, Locale, Recipe, RECIPES,
+    clvl_to_rlvl, get_game_settings, get_initial_quality, get_item_name, get_recipes_for_item,
+    rlvl_to_clvl, CrafterStats, Locale, Recipe, MEALS, POTIONS, RECIPES, RLVLS,
 };
-use simulator::{Action, ActionMask, Settings};
+use simulator::{Action, ActionMask, Settings, SimulationState};
 
 fn find_recipe(item_name: &'static str) -> Option<Recipe> {
     for recipe in RECIPES.iter() {
@@ -14,12 +15,8 @@ fn find_recipe(item_name: &'static str) -> Option<Recipe> {
 
 fn ingredient_names(recipe: Recipe) -> Vec<String> {
     recipe
-        .ingredients
-        .into_iter()
-        .filter_map(|ingr| match ingr.item_id {
-            0 => None,
-            item_id => Some(get_item_name(item_id, false, Locale::EN)),
-        })
+        .ingredients_iter()
+        .map(|(_, ingredient)| get_item_name(ingredient.item_id, false, Locale::EN))
         .collect()
 }
 
@@ -46,8 +43,9 @@ fn test_roast_chicken() {
         manipulation: true,
         heart_and_soul: false,
         quick_innovation: false,
+        careful_observation: false,
     };
-    let settings = get_game_settings(recipe, crafter_stats, None, None, false);
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
     assert_eq!(
         settings,
         Settings {
@@ -61,6 +59,7 @@ fn test_roast_chicken() {
             allowed_actions: ActionMask::from_level(100)
                 .remove(Action::TrainedEye)
                 .remove(Action::HeartAndSoul)
+                .remove(Action::CarefulObservation)
                 .remove(Action::QuickInnovation),
             adversarial: false,
         }
@@ -88,8 +87,9 @@ fn test_turali_pineapple_ponzecake() {
         manipulation: true,
         heart_and_soul: true,
         quick_innovation: false,
+        careful_observation: false,
     };
-    let settings = get_game_settings(recipe, crafter_stats, None, None, false);
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
     assert_eq!(
         settings,
         Settings {
@@ -102,7 +102,8 @@ fn test_turali_pineapple_ponzecake() {
             job_level: 94,
             allowed_actions: ActionMask::from_level(94)
                 .remove(Action::TrainedEye)
-                .remove(Action::QuickInnovation),
+                .remove(Action::QuickInnovation)
+                .remove(Action::CarefulObservation),
             adversarial: false,
         }
     );
@@ -129,8 +130,9 @@ fn test_smaller_water_otter_hardware() {
         manipulation: true,
         heart_and_soul: false,
         quick_innovation: false,
+        careful_observation: false,
     };
-    let settings = get_game_settings(recipe, crafter_stats, None, None, false);
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
     assert_eq!(
         settings,
         Settings {
@@ -145,6 +147,7 @@ fn test_smaller_water_otter_hardware() {
             allowed_actions: ActionMask::from_level(100)
                 .remove(Action::TrainedEye)
                 .remove(Action::HeartAndSoul)
+                .remove(Action::CarefulObservation)
                 .remove(Action::QuickInnovation),
             adversarial: false,
         }
@@ -172,8 +175,9 @@ fn test_grade_8_tincture() {
         manipulation: true,
         heart_and_soul: true,
         quick_innovation: false,
+        careful_observation: true,
     };
-    let settings = get_game_settings(recipe, crafter_stats, None, None, false);
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
     assert_eq!(
         settings,
         Settings {
@@ -208,8 +212,9 @@ fn test_claro_walnut_spinning_wheel() {
         manipulation: true,
         heart_and_soul: false,
         quick_innovation: true,
+        careful_observation: false,
     };
-    let settings = get_game_settings(recipe, crafter_stats, None, None, false);
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
     assert_eq!(
         settings,
         Settings {
@@ -222,10 +227,250 @@ fn test_claro_walnut_spinning_wheel() {
             job_level: 99,
             allowed_actions: ActionMask::from_level(99)
                 .remove(Action::TrainedEye)
-                .remove(Action::HeartAndSoul),
+                .remove(Action::HeartAndSoul)
+                .remove(Action::CarefulObservation),
             adversarial: false,
         }
     );
     let initial_quality = get_initial_quality(recipe, [0, 0, 0, 0, 0, 0]);
     assert_eq!(initial_quality, 0);
 }
+
+#[test]
+fn test_level_difference() {
+    let recipe = find_recipe("Chondrite Saw").unwrap();
+    assert_eq!(recipe.level, 90);
+    let base_values = |level| {
+        let crafter_stats = CrafterStats {
+            craftsmanship: 4000,
+            control: 3962,
+            cp: 594,
+            level,
+            ..Default::default()
+        };
+        let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
+        (settings.base_progress, settings.base_quality)
+    };
+    // the recipe level's modifiers (90% progress, 80% quality) apply at or below the recipe's level
+    assert_eq!(base_values(89), (278, 303));
+    assert_eq!(base_values(90), (278, 303));
+    // and are dropped entirely as soon as the crafter out-levels the recipe
+    assert_eq!(base_values(91), (309, 379));
+    assert_eq!(base_values(100), (309, 379));
+}
+
+#[test]
+fn test_item_with_multiple_recipes() {
+    let recipe = find_recipe("Maraging Steel Ingot").unwrap();
+    let job_ids: Vec<u8> = get_recipes_for_item(recipe.item_id)
+        .map(|recipe| recipe.job_id)
+        .collect();
+    // Blacksmith and Armorer
+    assert_eq!(job_ids, [1, 2]);
+    // items with a single recipe are unaffected
+    let recipe = find_recipe("Roast Chicken").unwrap();
+    assert_eq!(get_recipes_for_item(recipe.item_id).count(), 1);
+}
+
+#[test]
+fn test_recipe_durability_conversion() {
+    let recipe = find_recipe("Claro Walnut Spinning Wheel").unwrap();
+    assert_eq!(recipe.durability, 80);
+    let settings = get_game_settings(recipe, CrafterStats::default(), None, None, false).unwrap();
+    assert_eq!(settings.max_durability, 80);
+    // durability values outside of the simulator's range are clamped instead of wrapping around
+    let recipe = Recipe {
+        durability: 200,
+        ..recipe
+    };
+    let settings = get_game_settings(recipe, CrafterStats::default(), None, None, false).unwrap();
+    assert_eq!(settings.max_durability, i8::MAX);
+}
+
+#[test]
+fn test_invalid_recipe_durability() {
+    let recipe = find_recipe("Claro Walnut Spinning Wheel").unwrap();
+    let recipe = Recipe {
+        durability: 0,
+        ..recipe
+    };
+    let settings = get_game_settings(recipe, CrafterStats::default(), None, None, false);
+    assert_eq!(settings, Err("Recipe durability must be at least 5"));
+    // a single action's worth of durability is the minimum
+    let recipe = Recipe {
+        durability: 5,
+        ..recipe
+    };
+    let settings = get_game_settings(recipe, CrafterStats::default(), None, None, false).unwrap();
+    assert_eq!(settings.max_durability, 5);
+}
+
+#[test]
+fn test_extreme_stats() {
+    let recipe = find_recipe("Roast Chicken").unwrap();
+    let crafter_stats = CrafterStats {
+        craftsmanship: u16::MAX,
+        control: u16::MAX,
+        cp: u16::MAX,
+        level: 100,
+        ..Default::default()
+    };
+    // the consumables' bonuses would overflow the stats
+    let food = MEALS.iter().max_by_key(|meal| meal.control_max).copied();
+    let potion = POTIONS
+        .iter()
+        .max_by_key(|potion| potion.craft_max)
+        .copied();
+    let settings = get_game_settings(recipe, crafter_stats, food, potion, false).unwrap();
+    assert_eq!(settings.max_cp, i16::MAX);
+    assert!(settings.base_progress > 0);
+    // low-level recipes with a tiny divider have the largest base values, which saturate instead of overflowing
+    for recipe in RECIPES.iter() {
+        let _ = get_game_settings(*recipe, crafter_stats, None, None, false);
+    }
+}
+
+#[test]
+fn test_low_level_recipe_adversarial() {
+    let recipe = *RECIPES
+        .iter()
+        .find(|recipe| recipe.recipe_level == 1 && recipe.quality == 80)
+        .unwrap();
+    let crafter_stats = CrafterStats {
+        craftsmanship: 4000,
+        control: 4000,
+        cp: 600,
+        level: 100,
+        ..Default::default()
+    };
+    let settings = get_game_settings(recipe, crafter_stats, None, None, true).unwrap();
+    // the base values exceed the recipe's totals, which still matters for Quality overflow and for Poor steps
+    assert!(settings.base_quality > recipe.quality);
+    let actions = [Action::Observe, Action::Observe, Action::BasicTouch];
+    let state = SimulationState::from_macro(&settings, &actions).unwrap();
+    // the Basic Touch may be Poor, which halves its Quality, but half of the base Quality still maxes out the recipe
+    assert_eq!(state.get_quality(), 684);
+    assert!(state.get_quality() >= settings.max_quality);
+}
+
+#[test]
+fn test_minimum_stats() {
+    let recipe = find_recipe("Roast Chicken").unwrap();
+    let crafter_stats = CrafterStats {
+        craftsmanship: 0,
+        control: 0,
+        cp: 0,
+        level: 100,
+        ..Default::default()
+    };
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
+    assert_eq!(settings.max_cp, 0);
+    assert!(settings.base_progress >= 1);
+}
+
+#[test]
+fn test_recipe_level_conversion() {
+    assert_eq!(clvl_to_rlvl(1), 1);
+    assert_eq!(clvl_to_rlvl(50), 50);
+    assert_eq!(clvl_to_rlvl(90), 560);
+    assert_eq!(clvl_to_rlvl(100), 690);
+    for clvl in 1..=100 {
+        assert_eq!(rlvl_to_clvl(clvl_to_rlvl(clvl)), Some(clvl));
+    }
+    // star recipes share the class level of their non-star counterpart
+    assert_eq!(rlvl_to_clvl(570), Some(90));
+    assert_eq!(rlvl_to_clvl(799), Some(100));
+    assert_eq!(rlvl_to_clvl(RLVLS.len() as u16), None);
+    assert_eq!(rlvl_to_clvl(u16::MAX), None);
+    // the level of every recipe is derived from its recipe level
+    for recipe in RECIPES.iter() {
+        assert_eq!(rlvl_to_clvl(recipe.recipe_level), Some(recipe.level));
+        assert!(clvl_to_rlvl(recipe.level) <= recipe.recipe_level);
+    }
+}
+
+#[test]
+fn test_manipulation_not_learned() {
+    let recipe = find_recipe("Roast Chicken").unwrap();
+    let crafter_stats = CrafterStats {
+        level: 100,
+        manipulation: false,
+        ..Default::default()
+    };
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
+    // Manipulation is removed even though the crafter's level is high enough
+    assert!(!settings.allowed_actions.has(Action::Manipulation));
+    assert!(settings.allowed_actions.has(Action::MasterMend));
+    assert!(settings.allowed_actions.has(Action::ImmaculateMend));
+    let crafter_stats = CrafterStats {
+        manipulation: true,
+        ..crafter_stats
+    };
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
+    assert!(settings.allowed_actions.has(Action::Manipulation));
+}
+
+#[test]
+fn test_max_crafter_stats() {
+    let recipe = find_recipe("Roast Chicken").unwrap();
+    let crafter_stats = CrafterStats {
+        craftsmanship: CrafterStats::MAX_CRAFTSMANSHIP,
+        control: CrafterStats::MAX_CONTROL,
+        cp: CrafterStats::MAX_CP,
+        level: CrafterStats::MAX_LEVEL,
+        ..Default::default()
+    };
+    // the largest stats the GUI accepts must not overflow the simulator's settings
+    let settings = get_game_settings(recipe, crafter_stats, None, None, false).unwrap();
+    assert_eq!(settings.max_cp, CrafterStats::MAX_CP as i16);
+    assert_eq!(settings.base_progress, 319);
+    assert_eq!(settings.base_quality, 326);
+}
+
+#[test]
+fn test_initial_quality_unknown_ingredient() {
+    let recipe = find_recipe("Turali Pineapple Ponzecake").unwrap();
+    let mut ingredients = recipe.ingredients;
+    // replace the first ingredient (which cannot be HQ) with an item id that doesn't exist
+    ingredients[0].item_id = u32::MAX;
+    let recipe = Recipe {
+        ingredients,
+        ..recipe
+    };
+    // instead of panicking, the unknown ingredient is skipped and the others still line up with their HQ amounts
+    let initial_quality = get_initial_quality(recipe, [0, 0, 1, 0, 0, 0]);
+    assert_eq!(initial_quality, 2180);
+    assert_eq!(get_initial_quality(recipe, [1, 0, 0, 0, 0, 0]), 0);
+}
+
+#[test]
+fn test_unknown_recipe_level() {
+    let recipe = Recipe {
+        recipe_level: u16::MAX,
+        ..find_recipe("Roast Chicken").unwrap()
+    };
+    // a hand-edited recipe with an out-of-range recipe level is rejected instead of panicking
+    let result = get_game_settings(recipe, CrafterStats::default(), None, None, false);
+    assert_eq!(result, Err("Unknown recipe level"));
+}
+
+#[test]
+fn test_fewer_than_6_ingredients() {
+    let recipe = find_recipe("Bronze Cross-pein Hammer").unwrap();
+    assert_eq!(
+        ingredient_names(recipe),
+        ["Bronze Ingot", "Undyed Hempen Cloth", "Maple Lumber"]
+    );
+    let indices: Vec<usize> = recipe.ingredients_iter().map(|(index, _)| index).collect();
+    assert_eq!(indices, [0, 1, 2]);
+    // a slot with an item but no amount is unused, so HQ amounts given for it don't count
+    let mut ingredients = recipe.ingredients;
+    ingredients[3].item_id = ingredients[0].item_id;
+    let recipe = Recipe {
+        ingredients,
+        ..recipe
+    };
+    assert_eq!(recipe.ingredients_iter().count(), 3);
+    assert!(get_initial_quality(recipe, [1, 0, 0, 0, 0, 0]) > 0);
+    assert_eq!(get_initial_quality(recipe, [0, 0, 0, 1, 0, 0]), 0);
+}
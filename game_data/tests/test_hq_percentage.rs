@@ -0,0 +1,51 @@
+use game_data::{hq_percentage, quality_for_hq_percentage, HQ_PERCENTAGES};
+
+const MAX_QUALITIES: [u16; 5] = [1, 99, 5000, 11400, 40000];
+
+#[test]
+fn test_quality_for_hq_percentage_round_trip() {
+    for max_quality in MAX_QUALITIES {
+        for percentage in 0..=100 {
+            let quality = quality_for_hq_percentage(percentage, max_quality);
+            assert!(quality <= max_quality);
+            assert!(hq_percentage(quality, max_quality) >= percentage);
+            // no lower quality reaches the same percentage
+            if quality != 0 {
+                assert!(hq_percentage(quality - 1, max_quality) < percentage);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_quality_for_hq_percentage_monotonic() {
+    for max_quality in MAX_QUALITIES {
+        for percentage in 1..=100 {
+            assert!(
+                quality_for_hq_percentage(percentage - 1, max_quality)
+                    <= quality_for_hq_percentage(percentage, max_quality)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_quality_for_hq_percentage_clamped() {
+    assert_eq!(quality_for_hq_percentage(0, 5000), 0);
+    assert_eq!(quality_for_hq_percentage(1, 5000), 0);
+    assert_eq!(quality_for_hq_percentage(100, 5000), 5000);
+    // unreachable percentages are clamped to max quality
+    assert_eq!(quality_for_hq_percentage(101, 5000), 5000);
+    assert_eq!(quality_for_hq_percentage(50, 0), 0);
+}
+
+#[test]
+fn test_hq_percentage_table() {
+    assert_eq!(HQ_PERCENTAGES.len(), 101);
+    assert_eq!(HQ_PERCENTAGES[0], 1);
+    assert_eq!(HQ_PERCENTAGES[50], 15);
+    assert_eq!(HQ_PERCENTAGES[100], 100);
+    assert!(HQ_PERCENTAGES.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(hq_percentage(0, 5000), 1);
+    assert_eq!(hq_percentage(5000, 5000), 100);
+}
@@ -1,13 +1,14 @@
-use game_data::{get_item_name, Locale, ITEMS, RECIPES};
+use game_data::{
+    closest_recipe_name, default_recipe, get_item_name, item_icon_path, item_icon_url,
+    resolve_name_to_ids, Locale, Recipe, DEFAULT_RECIPE_ITEM_ID, ITEMS, RECIPES,
+};
 
 #[test]
 /// Test that all ingredients have an entry in the ITEMS table
 fn test_recipe_ingredients_have_valid_id() {
     for recipe in RECIPES.iter() {
-        for ingredient in recipe.ingredients.iter() {
-            if ingredient.item_id != 0 {
-                assert!(ITEMS.contains_key(&ingredient.item_id));
-            }
+        for (_, ingredient) in recipe.ingredients_iter() {
+            assert!(ITEMS.contains_key(&ingredient.item_id));
         }
     }
 }
@@ -51,3 +52,79 @@ fn test_item_name_44232_hq() {
         ]
     );
 }
+
+#[test]
+fn test_closest_recipe_name() {
+    assert_eq!(
+        closest_recipe_name("bronze crosspein hamer", Locale::EN),
+        Some("Bronze Cross-pein Hammer")
+    );
+    assert_eq!(
+        closest_recipe_name("Roast Chikcen", Locale::EN),
+        Some("Roast Chicken")
+    );
+    assert_eq!(
+        closest_recipe_name("bronze-kreuzschlaghamer", Locale::DE),
+        Some("Bronze-Kreuzschlaghammer")
+    );
+    assert_eq!(
+        closest_recipe_name("qwertyuiop asdfghjkl", Locale::EN),
+        None
+    );
+}
+
+#[test]
+fn test_item_icon_url() {
+    assert_eq!(
+        item_icon_url(5057).as_deref(), // Iron Ingot
+        Some("https://xivapi.com/i/020000/020801.png")
+    );
+    assert_eq!(item_icon_url(u32::MAX), None);
+    assert_eq!(item_icon_path(5057).as_deref(), Some("020000/020801.png"));
+    assert_eq!(item_icon_path(u32::MAX), None);
+}
+
+#[test]
+fn test_expert_recipes() {
+    let find_recipe = |item_id| {
+        RECIPES
+            .iter()
+            .find(|recipe| recipe.item_id == item_id)
+            .unwrap()
+    };
+    assert!(find_recipe(29832).is_expert); // Grade 2 Artisanal Skybuilders' Wardrobe
+    assert!(!find_recipe(2341).is_expert); // Bronze Cross-pein Hammer
+    assert_eq!(
+        RECIPES.iter().filter(|recipe| recipe.is_expert).count(),
+        104
+    );
+}
+
+#[test]
+fn test_resolve_name_to_ids() {
+    assert_eq!(
+        resolve_name_to_ids("Bronze Cross-pein Hammer", Locale::EN),
+        [2341]
+    );
+    assert!(resolve_name_to_ids("Not an item", Locale::EN).is_empty());
+    // every item is found by its own name, even if other items share it
+    for recipe in RECIPES.iter().step_by(100) {
+        let item_name = get_item_name(recipe.item_id, false, Locale::EN);
+        assert!(resolve_name_to_ids(&item_name, Locale::EN).contains(&recipe.item_id));
+    }
+}
+
+#[test]
+fn test_default_recipe() {
+    let recipe = default_recipe(RECIPES).unwrap();
+    assert_eq!(recipe.item_id, DEFAULT_RECIPE_ITEM_ID);
+    // fall back to the first recipe if the default item is removed from the data
+    let recipes: Vec<Recipe> = RECIPES
+        .iter()
+        .filter(|recipe| recipe.item_id != DEFAULT_RECIPE_ITEM_ID)
+        .copied()
+        .collect();
+    let recipe = default_recipe(&recipes).unwrap();
+    assert_eq!(recipe.item_id, RECIPES[0].item_id);
+    assert!(default_recipe(&[]).is_none());
+}
@@ -0,0 +1,30 @@
+use game_data::reference_rotations;
+use simulator::{ActionMask, Settings, SimulationState};
+
+#[test]
+fn test_reference_rotations_are_usable() {
+    for rotation in reference_rotations() {
+        let settings = Settings {
+            max_cp: rotation.cp as i16,
+            max_durability: rotation.durability as i8,
+            max_progress: u16::MAX,
+            max_quality: u16::MAX,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: rotation.level,
+            allowed_actions: ActionMask::from_level(rotation.level),
+            adversarial: false,
+        };
+        let state = SimulationState::from_macro(&settings, rotation.actions)
+            .unwrap_or_else(|err| panic!("{}: {}", rotation.name, err));
+        // the stated CP is exactly what the rotation needs
+        assert_eq!(state.cp, 0, "{}", rotation.name);
+        let level = rotation
+            .actions
+            .iter()
+            .map(|action| action.level_requirement())
+            .max()
+            .unwrap();
+        assert_eq!(level, rotation.level, "{}", rotation.name);
+    }
+}
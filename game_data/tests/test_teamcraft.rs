@@ -0,0 +1,35 @@
+use game_data::{action_name, teamcraft_action_name, to_teamcraft_rotation, Locale};
+use simulator::{Action, ActionMask};
+
+#[test]
+fn test_teamcraft_action_names() {
+    for action in ActionMask::all().actions_iter() {
+        // Teamcraft's names are the English in-game names without spaces and punctuation
+        let in_game_name: String = action_name(action, Locale::EN)
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect();
+        assert!(
+            teamcraft_action_name(action).eq_ignore_ascii_case(&in_game_name),
+            "{:?}: {}",
+            action,
+            teamcraft_action_name(action)
+        );
+    }
+}
+
+#[test]
+fn test_teamcraft_rotation() {
+    let actions = [
+        Action::MuscleMemory,
+        Action::WasteNot2,
+        Action::BasicTouch,
+        Action::ComboStandardTouch,
+        Action::ByregotsBlessing,
+    ];
+    assert_eq!(
+        to_teamcraft_rotation(&actions),
+        r#"["MuscleMemory","WasteNotII","BasicTouch","StandardTouch","ByregotsBlessing"]"#
+    );
+    assert_eq!(to_teamcraft_rotation(&[]), "[]");
+}
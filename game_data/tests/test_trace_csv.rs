@@ -0,0 +1,60 @@
+use game_data::{to_trace_csv, TRACE_CSV_HEADER};
+use simulator::{Action, ActionMask, Settings, SimulationState};
+
+const SETTINGS: Settings = Settings {
+    max_cp: 200,
+    max_durability: 80,
+    max_progress: 2000,
+    max_quality: 4000,
+    base_progress: 100,
+    base_quality: 100,
+    job_level: 100,
+    allowed_actions: ActionMask::all(),
+    adversarial: false,
+};
+
+#[test]
+fn test_trace_csv() {
+    let initial_state = SimulationState::new(&SETTINGS);
+    let actions = [Action::MuscleMemory, Action::Innovation, Action::BasicTouch];
+    let csv = to_trace_csv(&SETTINGS, &initial_state, 50, &actions);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            TRACE_CSV_HEADER,
+            "1,Muscle Memory,300,50,70,194,0,0,0,0,0,0,5",
+            "2,Innovation,300,50,70,176,0,4,0,0,0,0,4",
+            "3,Basic Touch,300,200,60,158,1,3,0,0,0,0,3",
+        ]
+    );
+}
+
+#[test]
+fn test_trace_csv_stops_at_error() {
+    // Muscle Memory can only be used as the first step
+    let initial_state = SimulationState::new(&SETTINGS);
+    let actions = [Action::BasicTouch, Action::MuscleMemory, Action::BasicTouch];
+    let csv = to_trace_csv(&SETTINGS, &initial_state, 0, &actions);
+    assert_eq!(csv.lines().count(), 2);
+    assert_eq!(
+        to_trace_csv(&SETTINGS, &initial_state, 0, &[]),
+        TRACE_CSV_HEADER
+    );
+}
+
+#[test]
+fn test_trace_csv_below_max_cp() {
+    let initial_state = SimulationState::with_initial_cp(&SETTINGS, 100);
+    let actions = [Action::BasicTouch, Action::ComboStandardTouch];
+    let csv = to_trace_csv(&SETTINGS, &initial_state, 0, &actions);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            TRACE_CSV_HEADER,
+            "1,Basic Touch,0,100,70,82,1,0,0,0,0,0,0",
+            "2,Standard Touch,0,237,60,64,2,0,0,0,0,0,0",
+        ]
+    );
+}
@@ -2,7 +2,12 @@ use crate::{Condition, Effects, SingleUse};
 
 use super::Settings;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+/// Actions whose outcome depends on a success rate (Rapid Synthesis, Hasty Touch) are not modeled,
+/// and neither is Daring Touch, which can only be used after a successful Hasty Touch.
+/// Actions are ordered by declaration, which is used to break ties between otherwise equivalent macros.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum Action {
     BasicSynthesis,
     BasicTouch,
@@ -36,9 +41,11 @@ pub enum Action {
     QuickInnovation,
     ImmaculateMend,
     TrainedPerfection,
+    TricksOfTheTrade,
+    CarefulObservation,
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum Combo {
     None,
     SynthesisBegin,
@@ -101,6 +108,8 @@ impl Action {
             Action::QuickInnovation => 96,
             Action::ImmaculateMend => 98,
             Action::TrainedPerfection => 100,
+            Action::TricksOfTheTrade => 13,
+            Action::CarefulObservation => 55,
         }
     }
 
@@ -138,6 +147,8 @@ impl Action {
             Action::TrainedPerfection => 3,
             Action::TrainedEye => 3,
             Action::QuickInnovation => 3,
+            Action::TricksOfTheTrade => 3,
+            Action::CarefulObservation => 3,
         }
     }
 
@@ -175,6 +186,8 @@ impl Action {
             Action::TrainedPerfection => 0,
             Action::TrainedEye => 250,
             Action::QuickInnovation => 0,
+            Action::TricksOfTheTrade => 0,
+            Action::CarefulObservation => 0,
         }
     }
 
@@ -212,6 +225,8 @@ impl Action {
             Action::TrainedPerfection => 0,
             Action::TrainedEye => 0,
             Action::QuickInnovation => 0,
+            Action::TricksOfTheTrade => 0,
+            Action::CarefulObservation => 0,
         }
     }
 
@@ -225,6 +240,23 @@ impl Action {
         }
     }
 
+    /// CP cost of the action under the given condition. Pliant halves the CP cost, rounded up.
+    pub const fn cp_cost_in_condition(self, condition: Condition) -> i16 {
+        match condition {
+            Condition::Pliant => (self.cp_cost() + 1) / 2,
+            _ => self.cp_cost(),
+        }
+    }
+
+    /// Durability cost of the action under the given condition.
+    /// Sturdy halves the durability cost on top of Waste Not, rounded up.
+    pub const fn durability_cost_in_condition(self, effects: &Effects, condition: Condition) -> i8 {
+        match condition {
+            Condition::Sturdy => (self.durability_cost(effects) + 1) / 2,
+            _ => self.durability_cost(effects),
+        }
+    }
+
     pub const fn progress_efficiency(self, job_level: u8) -> u64 {
         match self {
             Action::BasicSynthesis => {
@@ -293,6 +325,15 @@ impl Action {
         }
     }
 
+    /// Quality increase of the action given the current effects and condition.
+    /// Innovation (+50%) and Great Strides (+100%) are added together into a single effect modifier,
+    /// which is then multiplied with the efficiency, condition and Inner Quiet (+10% per stack) modifiers.
+    /// For Byregot's Blessing the efficiency itself also scales with Inner Quiet.
+    ///
+    /// In full, with every modifier in percent and the result rounded down:
+    /// `base_quality * efficiency * condition * (100 + innovation + great_strides) * (100 + 10 * inner_quiet) / 100^4`.
+    /// Inner Quiet multiplies the Quality increase directly instead of adding to Control,
+    /// so 10 stacks exactly double it (up to rounding) regardless of the crafter's Control.
     pub const fn quality_increase(
         self,
         settings: &Settings,
@@ -305,6 +346,7 @@ impl Action {
         let efficieny_mod = self.quality_efficiency(effects.inner_quiet());
         let condition_mod = match condition {
             Condition::Good => 150,
+            Condition::SplendorousGood => 200,
             Condition::Excellent => 400,
             Condition::Poor => 50,
             _ => 100,
@@ -2,6 +2,30 @@
 pub enum Condition {
     Normal,
     Good,
+    /// Good condition with Splendorous tools, which double Quality instead of the usual 1.5x.
+    /// The tools are part of the crafter's configuration rather than `Settings`,
+    /// so condition-aware callers pick this variant through `Condition::good`.
+    SplendorousGood,
     Excellent,
     Poor,
+    Sturdy,
+    Pliant,
+}
+
+impl Condition {
+    pub const fn good(splendorous: bool) -> Self {
+        if splendorous {
+            Condition::SplendorousGood
+        } else {
+            Condition::Good
+        }
+    }
+
+    /// Precise Touch and Intensive Synthesis can be used without Heart and Soul in these conditions
+    pub const fn is_good_or_excellent(self) -> bool {
+        matches!(
+            self,
+            Condition::Good | Condition::SplendorousGood | Condition::Excellent
+        )
+    }
 }
@@ -25,7 +25,7 @@ impl SingleUse {
 }
 
 #[bitfield_struct::bitfield(u32)]
-#[derive(PartialEq, Eq, Hash)]
+#[derive(PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Effects {
     #[bits(2, default=SingleUse::Available)]
     pub trained_perfection: SingleUse,
@@ -47,6 +47,10 @@ pub struct Effects {
     pub muscle_memory: u8,
     #[bits(4)]
     pub manipulation: u8,
+    /// Only used in adversarial mode. While non-zero, the current step cannot be Poor.
+    /// The first step is always Normal, so the synthesis starts with 2 to protect the second step as well.
+    /// After a Quality-increasing action it is set to 1, because the branches in which the next step is Poor
+    /// are already accounted for by `SimulationState::unreliable_quality`.
     #[bits(2)]
     pub guard: u8,
     #[bits(1)]
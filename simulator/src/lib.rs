@@ -1,3 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod actions;
 pub use actions::{Action, Combo};
 
@@ -8,7 +12,10 @@ mod effects;
 pub use effects::{Effects, SingleUse};
 
 pub mod state;
-pub use state::SimulationState;
+pub use state::{ActionReplay, EffectsApplied, SimulationState, StepBreakdown, SynthesisStatus};
 
 mod settings;
 pub use settings::{ActionMask, Settings};
+
+mod quality_ceiling;
+pub use quality_ceiling::{durability_cost, max_theoretical_quality};
@@ -0,0 +1,107 @@
+use crate::{Action, Settings};
+
+/// Actions that can only be used as the first step of a synthesis
+const OPENER_ACTIONS: [Action; 2] = [Action::Reflect, Action::TrainedEye];
+
+/// Upper-bound on the Quality that can be reached with the CP and durability available, ignoring Progress entirely.
+///
+/// Durability is converted to CP using the cheapest available way of restoring durability,
+/// every Quality action is assumed to have maxed-out Inner Quiet, and Innovation and Great Strides
+/// are charged only for the actions they buff. All conditions are assumed to be Normal.
+/// The ceiling is not tight, but it holds for any sequence of actions.
+pub fn max_theoretical_quality(settings: &Settings) -> u16 {
+    let durability_cost = durability_cost(settings) as f64;
+    let mut budget =
+        settings.max_cp as f64 + (settings.max_durability / 5 + 1) as f64 * durability_cost;
+    if settings.allowed_actions.has(Action::TrainedPerfection) {
+        budget += 4.0 * durability_cost;
+    }
+    // Heart and Soul lets Tricks of the Trade be used once without a Good or Excellent condition
+    if settings.allowed_actions.has(Action::HeartAndSoul)
+        && settings.allowed_actions.has(Action::TricksOfTheTrade)
+    {
+        budget += 20.0;
+    }
+
+    let innovation = settings.allowed_actions.has(Action::Innovation);
+    let great_strides = settings.allowed_actions.has(Action::GreatStrides);
+    // Innovation lasts for 4 steps, Great Strides is used up by the next Quality action
+    let buffs = [
+        (true, 100.0, 0.0),
+        (innovation, 150.0, Action::Innovation.cp_cost() as f64 / 4.0),
+        (great_strides, 200.0, Action::GreatStrides.cp_cost() as f64),
+        (
+            innovation && great_strides,
+            250.0,
+            Action::Innovation.cp_cost() as f64 / 4.0 + Action::GreatStrides.cp_cost() as f64,
+        ),
+    ];
+
+    let mut max_action_quality: f64 = 0.0;
+    let mut max_quality_per_cp: f64 = 0.0;
+    for action in settings.allowed_actions.actions_iter() {
+        let efficiency = action.quality_efficiency(10) as f64;
+        if efficiency == 0.0 {
+            continue;
+        }
+        let quality = settings.base_quality as f64 * efficiency / 100.0 * 2.0;
+        max_action_quality = max_action_quality.max(quality * 2.5);
+        if OPENER_ACTIONS.contains(&action) {
+            continue;
+        }
+        // Waste Not can at most halve the durability cost
+        let durability_units = ((action.base_durability_cost() + 9) / 10) as f64;
+        let cost = action.cp_cost() as f64 + durability_units * durability_cost;
+        for (allowed, effect_mod, buff_cost) in buffs {
+            if allowed {
+                max_quality_per_cp =
+                    max_quality_per_cp.max(quality * effect_mod / 100.0 / (cost + buff_cost));
+            }
+        }
+    }
+
+    let mut ceiling = budget * max_quality_per_cp;
+    // opener actions can only be used once
+    let mut opener_quality: f64 = 0.0;
+    if settings.allowed_actions.has(Action::TrainedEye) {
+        opener_quality = settings.max_quality as f64;
+    }
+    if settings.allowed_actions.has(Action::Reflect) {
+        opener_quality = opener_quality.max(max_action_quality);
+    }
+    ceiling += opener_quality;
+    // Quick Innovation gives Innovation for a single action
+    if settings.allowed_actions.has(Action::QuickInnovation) {
+        ceiling += max_action_quality * 0.2;
+    }
+    ceil(ceiling).min(u16::MAX as f64) as u16
+}
+
+/// `f64::ceil` for non-negative values, which isn't available without std
+fn ceil(value: f64) -> f64 {
+    let truncated = value as u64 as f64;
+    if truncated < value {
+        truncated + 1.0
+    } else {
+        truncated
+    }
+}
+
+/// CP cost of restoring 5 durability with the cheapest available action.
+/// This must never overestimate the actual cost, otherwise bounds built on top of it are not sound.
+/// Immaculate Mend is assumed to refill durability completely from the lowest possible durability (5),
+/// i.e. it restores `max_durability - 5` durability.
+pub fn durability_cost(settings: &Settings) -> i16 {
+    let mut cost = Action::MasterMend.cp_cost() / 6;
+    if settings.allowed_actions.has(Action::Manipulation) {
+        cost = core::cmp::min(cost, Action::Manipulation.cp_cost() / 8);
+    }
+    // below 10 durability, Immaculate Mend doesn't restore a full 5 durability
+    if settings.allowed_actions.has(Action::ImmaculateMend) && settings.max_durability >= 10 {
+        cost = core::cmp::min(
+            cost,
+            Action::ImmaculateMend.cp_cost() / (settings.max_durability as i16 / 5 - 1),
+        );
+    }
+    cost
+}
@@ -11,6 +11,8 @@ pub struct Settings {
     pub adversarial: bool,
 }
 
+use alloc::{boxed::Box, format, string::String};
+
 use crate::Action;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
@@ -27,6 +29,16 @@ impl ActionMask {
         Self { mask: u64::MAX }
     }
 
+    pub const fn from_actions(actions: &[Action]) -> Self {
+        let mut result = Self::none();
+        let mut index = 0;
+        while index < actions.len() {
+            result = result.add(actions[index]);
+            index += 1;
+        }
+        result
+    }
+
     pub fn from_level(level: u8) -> Self {
         let mut result = Self::none();
         for action in ALL_ACTIONS {
@@ -81,6 +93,8 @@ impl ActionMask {
             .collect()
     }
 
+    /// Iterate over the enabled actions in a fixed order that does not depend on how the mask was built.
+    /// See `ALL_ACTIONS` for the order.
     pub fn actions_iter(self) -> impl Iterator<Item = Action> {
         ALL_ACTIONS
             .iter()
@@ -89,6 +103,37 @@ impl ActionMask {
     }
 }
 
+/// Comma-separated list of the enabled actions, e.g. "BasicSynthesis,MuscleMemory"
+impl core::fmt::Display for ActionMask {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (index, action) in self.actions_iter().enumerate() {
+            if index != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{:?}", action)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for ActionMask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut result = Self::none();
+        for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            match ALL_ACTIONS
+                .iter()
+                .find(|action| format!("{:?}", action) == name)
+            {
+                Some(action) => result = result.add(*action),
+                None => return Err(format!("Unknown action: \"{}\"", name)),
+            }
+        }
+        Ok(result)
+    }
+}
+
 #[macro_export]
 macro_rules! action_mask {
     ( $( $x:expr ),* ) => {
@@ -102,37 +147,42 @@ macro_rules! action_mask {
     };
 }
 
+/// All actions, ordered roughly best-first: openers and high-efficiency Progress and Quality actions
+/// come before cheaper actions, buffs and durability restoration.
+/// Solvers that stop exploring a state once Progress and Quality are maxed out benefit from this order.
 const ALL_ACTIONS: &[Action] = &[
-    Action::BasicSynthesis,
-    Action::BasicTouch,
-    Action::MasterMend,
-    Action::Observe,
-    Action::WasteNot,
-    Action::Veneration,
-    Action::StandardTouch,
-    Action::ComboStandardTouch,
-    Action::GreatStrides,
-    Action::Innovation,
-    Action::WasteNot2,
-    Action::ByregotsBlessing,
-    Action::PreciseTouch,
-    Action::MuscleMemory,
-    Action::CarefulSynthesis,
-    Action::Manipulation,
-    Action::PrudentTouch,
     Action::TrainedEye,
     Action::Reflect,
+    Action::MuscleMemory,
+    Action::ByregotsBlessing,
     Action::PreparatoryTouch,
     Action::Groundwork,
-    Action::DelicateSynthesis,
     Action::IntensiveSynthesis,
-    Action::AdvancedTouch,
+    Action::PreciseTouch,
+    Action::ComboRefinedTouch,
     Action::ComboAdvancedTouch,
-    Action::HeartAndSoul,
-    Action::PrudentSynthesis,
+    Action::AdvancedTouch,
     Action::TrainedFinesse,
-    Action::ComboRefinedTouch,
-    Action::ImmaculateMend,
-    Action::TrainedPerfection,
+    Action::DelicateSynthesis,
+    Action::CarefulSynthesis,
+    Action::PrudentSynthesis,
+    Action::ComboStandardTouch,
+    Action::StandardTouch,
+    Action::PrudentTouch,
+    Action::BasicSynthesis,
+    Action::BasicTouch,
     Action::QuickInnovation,
+    Action::Innovation,
+    Action::GreatStrides,
+    Action::Veneration,
+    Action::WasteNot2,
+    Action::WasteNot,
+    Action::TrainedPerfection,
+    Action::HeartAndSoul,
+    Action::Manipulation,
+    Action::ImmaculateMend,
+    Action::MasterMend,
+    Action::TricksOfTheTrade,
+    Action::Observe,
+    Action::CarefulObservation,
 ];
@@ -1,6 +1,20 @@
+use alloc::vec::Vec;
+
 use crate::{effects::SingleUse, Action, Combo, Condition, Effects, Settings};
 
+/// Whether a synthesis can still be continued, see `SimulationState::status`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SynthesisStatus {
+    /// Durability is left and Progress isn't maxed out yet, so more actions can be used.
+    /// This includes states that already max out Quality but intentionally leave Progress incomplete.
+    InProgress,
+    /// Progress is maxed out. This takes precedence over running out of durability on the same step.
+    Completed,
+    /// Durability ran out before Progress was maxed out, so the item is lost.
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct SimulationState {
     pub cp: i16,
     pub durability: i8,
@@ -11,6 +25,14 @@ pub struct SimulationState {
     // 0 while forcing the previous un-Guarded action to be Normal
     pub effects: Effects,
     pub combo: Combo,
+    /// Number of times Careful Observation has been used, at most 3 uses are allowed per synthesis.
+    /// Kept out of `Effects`, whose bits are all taken, and left out of the serialized state while it is 0.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub careful_observation_uses: u8,
+}
+
+fn is_zero(value: &u8) -> bool {
+    *value == 0
 }
 
 impl SimulationState {
@@ -22,6 +44,25 @@ impl SimulationState {
             unreliable_quality: [0; 2],
             effects: Effects::default().with_guard(if settings.adversarial { 2 } else { 0 }),
             combo: Combo::SynthesisBegin,
+            careful_observation_uses: 0,
+        }
+    }
+
+    /// Initial state of a synthesis that doesn't start at full durability, e.g. a craft that is resumed mid-way.
+    /// Durability is clamped to `settings.max_durability`, which is still the cap for durability restoration.
+    pub fn with_initial_durability(settings: &Settings, durability: i8) -> Self {
+        Self {
+            durability: core::cmp::min(durability, settings.max_durability),
+            ..Self::new(settings)
+        }
+    }
+
+    /// Initial state of a synthesis that doesn't start with full CP, e.g. because CP was spent on a previous craft.
+    /// CP is clamped to `settings.max_cp`, which is still the cap for CP restoration.
+    pub fn with_initial_cp(settings: &Settings, cp: i16) -> Self {
+        Self {
+            cp: core::cmp::min(cp, settings.max_cp),
+            ..Self::new(settings)
         }
     }
 
@@ -54,6 +95,24 @@ impl SimulationState {
         (state, errors)
     }
 
+    /// Applies the actions in order, each one with the condition at the same position in `conditions`.
+    /// Extra conditions are ignored, but there must be at least one condition per action.
+    pub fn use_actions_with_conditions(
+        self,
+        actions: &[Action],
+        conditions: &[Condition],
+        settings: &Settings,
+    ) -> Result<SimulationState, &'static str> {
+        if conditions.len() < actions.len() {
+            return Err("Fewer conditions than actions");
+        }
+        let mut state = self;
+        for (action, condition) in actions.iter().zip(conditions.iter()) {
+            state = state.use_action(*action, *condition, settings)?;
+        }
+        Ok(state)
+    }
+
     pub fn get_quality(&self) -> u16 {
         #[cfg(test)]
         assert!(self.unreliable_quality[0] >= self.unreliable_quality[1]);
@@ -64,6 +123,17 @@ impl SimulationState {
         self.durability <= 0 || self.progress >= settings.max_progress
     }
 
+    /// Distinguishes the two kinds of final states, see `SynthesisStatus`.
+    pub fn status(&self, settings: &Settings) -> SynthesisStatus {
+        if self.progress >= settings.max_progress {
+            SynthesisStatus::Completed
+        } else if self.durability <= 0 {
+            SynthesisStatus::Failed
+        } else {
+            SynthesisStatus::InProgress
+        }
+    }
+
     pub fn can_use_action(
         &self,
         action: Action,
@@ -76,7 +146,7 @@ impl SimulationState {
         if !settings.allowed_actions.has(action) {
             return Err("Action not enabled");
         }
-        if action.cp_cost() > self.cp {
+        if action.cp_cost_in_condition(condition) > self.cp {
             return Err("Not enough CP");
         }
         if !action.combo_fulfilled(self.combo) {
@@ -89,14 +159,16 @@ impl SimulationState {
             Action::PrudentSynthesis | Action::PrudentTouch if self.effects.waste_not() != 0 => {
                 Err("Action cannot be used during Waste Not")
             }
-            Action::IntensiveSynthesis | Action::PreciseTouch
+            Action::IntensiveSynthesis | Action::PreciseTouch | Action::TricksOfTheTrade
                 if self.effects.heart_and_soul() != SingleUse::Active
-                    && condition != Condition::Good
-                    && condition != Condition::Excellent =>
+                    && !condition.is_good_or_excellent() =>
             {
                 Err("Requires condition to be Good or Excellent")
             }
-            Action::Groundwork if self.durability < action.durability_cost(&self.effects) => {
+            Action::Groundwork
+                if self.durability
+                    < action.durability_cost_in_condition(&self.effects, condition) =>
+            {
                 Err("Not enough durability")
             }
             Action::TrainedFinesse if self.effects.inner_quiet() < 10 => {
@@ -116,6 +188,9 @@ impl SimulationState {
             Action::QuickInnovation if self.effects.innovation() != 0 => {
                 Err("Action cannot be used when Innovation is active")
             }
+            Action::CarefulObservation if self.careful_observation_uses >= 3 => {
+                Err("Action can only be used 3 times per synthesis")
+            }
             _ => Ok(()),
         }
     }
@@ -129,8 +204,8 @@ impl SimulationState {
         self.can_use_action(action, condition, settings)?;
         let mut state = self;
 
-        let cp_cost = action.cp_cost();
-        let durability_cost = action.durability_cost(&state.effects);
+        let cp_cost = action.cp_cost_in_condition(condition);
+        let durability_cost = action.durability_cost_in_condition(&state.effects, condition);
         let progress_increase = action.progress_increase(settings, &state.effects);
         let quality_increase = if settings.adversarial && state.effects.guard() == 0 {
             action.quality_increase(settings, &state.effects, Condition::Poor)
@@ -172,7 +247,7 @@ impl SimulationState {
                     Action::ComboRefinedTouch => 2,
                     _ => 1,
                 };
-                state.effects.set_inner_quiet(std::cmp::min(
+                state.effects.set_inner_quiet(core::cmp::min(
                     10,
                     state.effects.inner_quiet() + inner_quiet_bonus,
                 ));
@@ -190,10 +265,10 @@ impl SimulationState {
                 // append new info
                 let saved = state.unreliable_quality[0];
                 state.unreliable_quality[0] =
-                    std::cmp::min(state.unreliable_quality[1], state.unreliable_quality[0])
+                    core::cmp::min(state.unreliable_quality[1], state.unreliable_quality[0])
                         + quality_delta;
                 state.unreliable_quality[1] =
-                    std::cmp::min(saved, state.unreliable_quality[1] + quality_delta);
+                    core::cmp::min(saved, state.unreliable_quality[1] + quality_delta);
             }
         }
 
@@ -201,15 +276,23 @@ impl SimulationState {
             return Ok(state);
         }
 
-        state.combo = action.to_combo();
+        // Careful Observation isn't a step, so it doesn't break the combo
+        if action != Action::CarefulObservation {
+            state.combo = action.to_combo();
+        }
 
         // skip processing effects for actions that do not increase turn count
-        if !matches!(action, Action::HeartAndSoul | Action::QuickInnovation) {
+        // Careful Observation rerolls the condition though, which still advances the guard
+        if action == Action::CarefulObservation {
+            state
+                .effects
+                .set_guard(state.effects.guard().saturating_sub(1));
+        } else if !matches!(action, Action::HeartAndSoul | Action::QuickInnovation) {
             if action == Action::Manipulation {
                 state.effects.set_manipulation(0);
             }
             if state.effects.manipulation() > 0 {
-                state.durability = std::cmp::min(state.durability + 5, settings.max_durability);
+                state.durability = core::cmp::min(state.durability + 5, settings.max_durability);
             }
             state.effects.tick_down();
         }
@@ -228,18 +311,25 @@ impl SimulationState {
             Action::WasteNot2 => state.effects.set_waste_not(8),
             Action::Manipulation => state.effects.set_manipulation(8),
             Action::MasterMend => {
-                state.durability = std::cmp::min(settings.max_durability, state.durability + 30)
+                state.durability = core::cmp::min(settings.max_durability, state.durability + 30)
             }
             Action::ByregotsBlessing => state.effects.set_inner_quiet(0),
             Action::ImmaculateMend => state.durability = settings.max_durability,
+            Action::TricksOfTheTrade => {
+                state.cp = core::cmp::min(settings.max_cp, state.cp + 20);
+                if !condition.is_good_or_excellent() {
+                    state.effects.set_heart_and_soul(SingleUse::Unavailable)
+                }
+            }
             Action::TrainedPerfection => state.effects.set_trained_perfection(SingleUse::Active),
             Action::HeartAndSoul => state.effects.set_heart_and_soul(SingleUse::Active),
+            Action::CarefulObservation => state.careful_observation_uses += 1,
             Action::QuickInnovation => {
                 state.effects.set_innovation(1);
                 state.effects.set_quick_innovation_used(true);
             }
             Action::IntensiveSynthesis | Action::PreciseTouch
-                if condition != Condition::Good && condition != Condition::Excellent =>
+                if !condition.is_good_or_excellent() =>
             {
                 state.effects.set_heart_and_soul(SingleUse::Unavailable)
             }
@@ -249,3 +339,155 @@ impl SimulationState {
         Ok(state)
     }
 }
+
+/// Iterator that lazily applies a sequence of actions, assuming Normal condition on every step.
+/// Yields each action together with the state after using it.
+/// Iteration ends after the first action that cannot be used.
+///
+/// ```
+/// use simulator::{Action, ActionMask, ActionReplay, Settings};
+///
+/// let settings = Settings {
+///     max_cp: 100,
+///     max_durability: 40,
+///     max_progress: 1000,
+///     max_quality: 1000,
+///     base_progress: 100,
+///     base_quality: 100,
+///     job_level: 100,
+///     allowed_actions: ActionMask::all(),
+///     adversarial: false,
+/// };
+/// let actions = [Action::BasicTouch, Action::BasicSynthesis, Action::ByregotsBlessing];
+/// let mut replay = ActionReplay::new(&settings, actions);
+/// let (action, state) = replay.next().unwrap();
+/// assert_eq!(action, Action::BasicTouch);
+/// assert_eq!(state.get_quality(), 100);
+/// assert_eq!(replay.count(), 2);
+///
+/// // stops at the first action that fails
+/// let mut replay = ActionReplay::new(&settings, [Action::ByregotsBlessing, Action::BasicTouch]);
+/// assert!(replay.next().is_none());
+/// assert!(replay.error().is_some());
+/// ```
+pub struct ActionReplay<'a, I> {
+    settings: &'a Settings,
+    state: SimulationState,
+    actions: I,
+    error: Option<&'static str>,
+}
+
+impl<'a, I: Iterator<Item = Action>> ActionReplay<'a, I> {
+    pub fn new(settings: &'a Settings, actions: impl IntoIterator<IntoIter = I>) -> Self {
+        Self::from_state(settings, SimulationState::new(settings), actions)
+    }
+
+    /// Replay the actions starting from `state` instead of the initial state of the synthesis,
+    /// e.g. one from `SimulationState::with_initial_cp`.
+    pub fn from_state(
+        settings: &'a Settings,
+        state: SimulationState,
+        actions: impl IntoIterator<IntoIter = I>,
+    ) -> Self {
+        Self {
+            settings,
+            state,
+            actions: actions.into_iter(),
+            error: None,
+        }
+    }
+
+    /// State after the last successfully used action
+    pub fn state(&self) -> SimulationState {
+        self.state
+    }
+
+    /// Error of the action that ended the replay, if any
+    pub fn error(&self) -> Option<&'static str> {
+        self.error
+    }
+}
+
+impl<'a, I: Iterator<Item = Action>> Iterator for ActionReplay<'a, I> {
+    type Item = (Action, SimulationState);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
+        let action = self.actions.next()?;
+        match self
+            .state
+            .use_action(action, Condition::Normal, self.settings)
+        {
+            Ok(state) => {
+                self.state = state;
+                Some((action, state))
+            }
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+}
+
+/// Buffs that boosted the Progress or Quality of a single step, see `StepBreakdown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EffectsApplied {
+    /// Inner Quiet stacks, each adding 10% to the Quality increase
+    pub inner_quiet: u8,
+    pub innovation: bool,
+    pub great_strides: bool,
+    pub veneration: bool,
+    pub muscle_memory: bool,
+}
+
+/// Progress and Quality gained by a single step, together with the buffs that were in effect for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepBreakdown {
+    pub action: Action,
+    /// State after the action
+    pub state: SimulationState,
+    pub progress_delta: u16,
+    pub quality_delta: u16,
+    pub effects_applied: EffectsApplied,
+}
+
+impl StepBreakdown {
+    /// Breakdown of the step that turned `before` into `after` by using `action`.
+    pub fn new(action: Action, before: &SimulationState, after: &SimulationState) -> Self {
+        let progress_delta = after.progress - before.progress;
+        let quality_delta = after.get_quality() - before.get_quality();
+        let increases_progress = progress_delta != 0;
+        let increases_quality = quality_delta != 0;
+        Self {
+            action,
+            state: *after,
+            progress_delta,
+            quality_delta,
+            effects_applied: EffectsApplied {
+                inner_quiet: match increases_quality {
+                    true => before.effects.inner_quiet(),
+                    false => 0,
+                },
+                innovation: increases_quality && before.effects.innovation() != 0,
+                great_strides: increases_quality && before.effects.great_strides() != 0,
+                veneration: increases_progress && before.effects.veneration() != 0,
+                muscle_memory: increases_progress && before.effects.muscle_memory() != 0,
+            },
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = Action> + 'a> ActionReplay<'a, I> {
+    /// Same as iterating over the replay, but yields the breakdown of each step instead of just the state after it.
+    pub fn breakdown(self) -> impl Iterator<Item = StepBreakdown> + 'a {
+        let mut previous = self.state;
+        self.map(move |(action, state)| {
+            let step = StepBreakdown::new(action, &previous, &state);
+            previous = state;
+            step
+        })
+    }
+}
@@ -0,0 +1,58 @@
+use simulator::{Action, ActionMask};
+
+#[test]
+fn test_from_actions() {
+    let mask = ActionMask::from_actions(&[Action::MuscleMemory, Action::Veneration]);
+    assert_eq!(
+        mask,
+        ActionMask::none()
+            .add(Action::MuscleMemory)
+            .add(Action::Veneration)
+    );
+    assert_eq!(ActionMask::from_actions(&[]), ActionMask::none());
+}
+
+#[test]
+fn test_parse() {
+    let mask: ActionMask = "MuscleMemory, Veneration,BasicSynthesis".parse().unwrap();
+    assert_eq!(
+        mask,
+        ActionMask::from_actions(&[
+            Action::BasicSynthesis,
+            Action::MuscleMemory,
+            Action::Veneration
+        ])
+    );
+    assert_eq!("".parse::<ActionMask>().unwrap(), ActionMask::none());
+    assert_eq!(
+        "MuscleMemory,Foo".parse::<ActionMask>(),
+        Err("Unknown action: \"Foo\"".to_string())
+    );
+}
+
+#[test]
+fn test_display_round_trip() {
+    for level in 1..=100 {
+        let mask = ActionMask::from_level(level).remove(Action::Manipulation);
+        assert_eq!(mask.to_string().parse::<ActionMask>().unwrap(), mask);
+    }
+    assert_eq!(ActionMask::none().to_string(), "");
+    assert_eq!(
+        ActionMask::from_actions(&[Action::Veneration, Action::BasicSynthesis]).to_string(),
+        "BasicSynthesis,Veneration"
+    );
+}
+
+#[test]
+fn test_actions_iter_order() {
+    // the order of iteration doesn't depend on the order in which actions were added
+    let forward = ActionMask::from_actions(&[Action::BasicSynthesis, Action::MuscleMemory]);
+    let backward = ActionMask::from_actions(&[Action::MuscleMemory, Action::BasicSynthesis]);
+    assert!(forward.actions_iter().eq(backward.actions_iter()));
+    assert_eq!(
+        forward.actions_iter().collect::<Vec<_>>(),
+        [Action::MuscleMemory, Action::BasicSynthesis]
+    );
+    // every action is visited exactly once
+    assert_eq!(ActionMask::all().actions_iter().count(), 34);
+}
@@ -1,4 +1,4 @@
-use simulator::{Action, ActionMask, Combo, Settings, SimulationState, SingleUse};
+use simulator::{Action, ActionMask, Combo, Condition, Settings, SimulationState, SingleUse};
 
 const SETTINGS: Settings = Settings {
     max_cp: 250,
@@ -44,7 +44,7 @@ fn test_redundant_half_efficiency_groundwork() {
 fn test_standard_touch_combo() {
     let state =
         SimulationState::from_macro(&SETTINGS, &[Action::BasicTouch, Action::ComboStandardTouch]);
-    assert!(matches!(state, Ok(_)));
+    assert!(state.is_ok());
     let state = SimulationState::from_macro(&SETTINGS, &[Action::ComboStandardTouch]);
     assert!(matches!(state, Err("Combo requirement not fulfilled")));
 }
@@ -59,10 +59,10 @@ fn test_advanced_touch_combo() {
             Action::ComboAdvancedTouch,
         ],
     );
-    assert!(matches!(state, Ok(_)));
+    assert!(state.is_ok());
     let state =
         SimulationState::from_macro(&SETTINGS, &[Action::Observe, Action::ComboAdvancedTouch]);
-    assert!(matches!(state, Ok(_)));
+    assert!(state.is_ok());
     let state = SimulationState::from_macro(&SETTINGS, &[Action::ComboAdvancedTouch]);
     assert!(matches!(state, Err("Combo requirement not fulfilled")));
 }
@@ -70,7 +70,7 @@ fn test_advanced_touch_combo() {
 #[test]
 fn test_reflect_opener() {
     let state = SimulationState::from_macro(&SETTINGS, &[Action::Reflect]);
-    assert!(matches!(state, Ok(_)));
+    assert!(state.is_ok());
     let state = SimulationState::from_macro(&SETTINGS, &[Action::BasicTouch, Action::Reflect]);
     assert!(matches!(state, Err("Combo requirement not fulfilled")));
 }
@@ -78,15 +78,48 @@ fn test_reflect_opener() {
 #[test]
 fn test_muscle_memory_opener() {
     let state = SimulationState::from_macro(&SETTINGS, &[Action::MuscleMemory]);
-    assert!(matches!(state, Ok(_)));
+    assert!(state.is_ok());
     let state = SimulationState::from_macro(&SETTINGS, &[Action::BasicTouch, Action::MuscleMemory]);
     assert!(matches!(state, Err("Combo requirement not fulfilled")));
 }
 
+#[test]
+fn test_muscle_memory_expiry() {
+    // Observe doesn't increase Progress or use durability, so it only lets time pass
+    let waiting_actions = [Action::Observe; 5];
+    let state = SimulationState::from_macro(&SETTINGS, &[Action::MuscleMemory]).unwrap();
+    assert_eq!(state.effects.muscle_memory(), 5);
+    // the buff lasts for the next 5 steps, so the 5th step still benefits from it
+    let mut active_state = state;
+    for action in waiting_actions[..4].iter() {
+        active_state = active_state
+            .use_action(*action, Condition::Normal, &SETTINGS)
+            .unwrap();
+    }
+    assert_eq!(active_state.effects.muscle_memory(), 1);
+    let active_state = active_state
+        .use_action(Action::BasicSynthesis, Condition::Normal, &SETTINGS)
+        .unwrap();
+    assert_eq!(active_state.progress - state.progress, 240);
+    assert_eq!(active_state.effects.muscle_memory(), 0);
+    // the buff expires unused after 5 steps
+    let mut expired_state = state;
+    for action in waiting_actions.iter() {
+        expired_state = expired_state
+            .use_action(*action, Condition::Normal, &SETTINGS)
+            .unwrap();
+    }
+    assert_eq!(expired_state.effects.muscle_memory(), 0);
+    let expired_state = expired_state
+        .use_action(Action::BasicSynthesis, Condition::Normal, &SETTINGS)
+        .unwrap();
+    assert_eq!(expired_state.progress - state.progress, 120);
+}
+
 #[test]
 fn test_trained_eye_opener() {
     let state = SimulationState::from_macro(&SETTINGS, &[Action::TrainedEye]);
-    assert!(matches!(state, Ok(_)));
+    assert!(state.is_ok());
     let state = state.unwrap();
     assert_eq!(state.get_quality(), SETTINGS.max_quality);
     assert_eq!(state.effects.inner_quiet(), 1);
@@ -95,6 +128,22 @@ fn test_trained_eye_opener() {
     assert!(matches!(state, Err("Combo requirement not fulfilled")));
 }
 
+#[test]
+fn test_openers_after_buff() {
+    // actions that don't touch Progress, Quality or durability still end the opening step
+    for action in [Action::Veneration, Action::WasteNot, Action::Manipulation] {
+        for opener in [Action::Reflect, Action::MuscleMemory] {
+            let state = SimulationState::from_macro(&SETTINGS, &[action, opener]);
+            assert!(
+                matches!(state, Err("Combo requirement not fulfilled")),
+                "{:?} after {:?}",
+                opener,
+                action
+            );
+        }
+    }
+}
+
 #[test]
 fn test_manipulation() {
     let state = SimulationState::from_macro(
@@ -118,6 +167,35 @@ fn test_prudent_touch() {
     ));
 }
 
+#[test]
+fn test_prudent_actions_blocked_by_waste_not() {
+    for waste_not in [Action::WasteNot, Action::WasteNot2] {
+        let state = SimulationState::from_macro(&SETTINGS, &[waste_not]).unwrap();
+        for prudent_action in [Action::PrudentTouch, Action::PrudentSynthesis] {
+            assert_eq!(
+                state.use_action(prudent_action, Condition::Normal, &SETTINGS),
+                Err("Action cannot be used during Waste Not")
+            );
+        }
+    }
+    // Prudent actions can be used again once Waste Not runs out
+    let state = SimulationState::from_macro(
+        &SETTINGS,
+        &[
+            Action::WasteNot,
+            Action::Observe,
+            Action::Observe,
+            Action::Observe,
+            Action::Observe,
+        ],
+    )
+    .unwrap();
+    assert_eq!(state.effects.waste_not(), 0);
+    assert!(state
+        .use_action(Action::PrudentTouch, Condition::Normal, &SETTINGS)
+        .is_ok());
+}
+
 #[test]
 fn test_groundwork() {
     let settings = Settings {
@@ -171,9 +249,55 @@ fn test_refined_touch() {
         }
         Err(e) => panic!("Unexpected error: {}", e),
     }
-    assert!(matches!(state, Ok(_)));
+    assert!(state.is_ok());
     let state = SimulationState::from_macro(&SETTINGS, &[Action::ComboRefinedTouch]);
     assert!(matches!(state, Err("Combo requirement not fulfilled")));
+    // unlike Advanced Touch, Refined Touch cannot be comboed from Observe
+    let state =
+        SimulationState::from_macro(&SETTINGS, &[Action::Observe, Action::ComboRefinedTouch]);
+    assert!(matches!(state, Err("Combo requirement not fulfilled")));
+    // any action in between breaks the combo
+    let state = SimulationState::from_macro(
+        &SETTINGS,
+        &[
+            Action::BasicTouch,
+            Action::Veneration,
+            Action::ComboRefinedTouch,
+        ],
+    );
+    assert!(matches!(state, Err("Combo requirement not fulfilled")));
+    // Refined Touch consumes the Basic Touch combo
+    let state = SimulationState::from_macro(
+        &SETTINGS,
+        &[
+            Action::BasicTouch,
+            Action::ComboRefinedTouch,
+            Action::ComboStandardTouch,
+        ],
+    );
+    assert!(matches!(state, Err("Combo requirement not fulfilled")));
+}
+
+#[test]
+fn test_great_strides_byregots_blessing() {
+    let state = SimulationState::from_macro(
+        &SETTINGS,
+        &[
+            Action::Reflect,
+            Action::GreatStrides,
+            Action::Innovation,
+            Action::ByregotsBlessing,
+        ],
+    );
+    match state {
+        Ok(state) => {
+            // 100 * (100% + 2 * 20%) * (100% + 50% + 100%) * (100% + 2 * 10%) = 420
+            assert_eq!(state.get_quality(), 300 + 420);
+            assert_eq!(state.effects.inner_quiet(), 0);
+            assert_eq!(state.effects.great_strides(), 0);
+        }
+        Err(e) => panic!("Unexpected error: {}", e),
+    }
 }
 
 #[test]
@@ -288,6 +412,161 @@ fn test_precise_touch() {
     ));
 }
 
+#[test]
+fn test_splendorous_good_condition() {
+    let initial_state = SimulationState::new(&SETTINGS);
+    let quality = |condition| {
+        initial_state
+            .use_action(Action::BasicTouch, condition, &SETTINGS)
+            .unwrap()
+            .get_quality()
+    };
+    assert_eq!(quality(Condition::good(false)), 150);
+    assert_eq!(quality(Condition::good(true)), 200);
+    // same as Good otherwise
+    for action in [Action::IntensiveSynthesis, Action::PreciseTouch] {
+        assert!(initial_state
+            .use_action(action, Condition::SplendorousGood, &SETTINGS)
+            .is_ok());
+    }
+}
+
+#[test]
+fn test_condition_gated_actions() {
+    let initial_state = SimulationState::new(&SETTINGS);
+    for action in [Action::IntensiveSynthesis, Action::PreciseTouch] {
+        for condition in [Condition::Normal, Condition::Poor] {
+            assert_eq!(
+                initial_state.use_action(action, condition, &SETTINGS),
+                Err("Requires condition to be Good or Excellent")
+            );
+        }
+    }
+    // Intensive Synthesis is not affected by the condition beyond the gate
+    for condition in [Condition::Good, Condition::Excellent] {
+        let state = initial_state
+            .use_action(Action::IntensiveSynthesis, condition, &SETTINGS)
+            .unwrap();
+        assert_eq!(state.progress, 400);
+    }
+    // Precise Touch gets the Quality bonus of the condition
+    let state = initial_state
+        .use_action(Action::PreciseTouch, Condition::Good, &SETTINGS)
+        .unwrap();
+    assert_eq!(state.get_quality(), 225);
+    assert_eq!(state.effects.inner_quiet(), 2);
+    let state = initial_state
+        .use_action(Action::PreciseTouch, Condition::Excellent, &SETTINGS)
+        .unwrap();
+    assert_eq!(state.get_quality(), 600);
+    assert_eq!(state.effects.inner_quiet(), 2);
+}
+
+#[test]
+fn test_tricks_of_the_trade() {
+    // spend some CP so that the restored CP isn't capped
+    let state = SimulationState::from_macro(&SETTINGS, &[Action::Manipulation]).unwrap();
+    assert_eq!(state.cp, 154);
+    for condition in [Condition::Good, Condition::Excellent] {
+        let state = state
+            .use_action(Action::TricksOfTheTrade, condition, &SETTINGS)
+            .unwrap();
+        assert_eq!(state.cp, 174);
+    }
+    for condition in [Condition::Normal, Condition::Poor] {
+        assert_eq!(
+            state.use_action(Action::TricksOfTheTrade, condition, &SETTINGS),
+            Err("Requires condition to be Good or Excellent")
+        );
+    }
+    // Heart and Soul allows Tricks of the Trade under Normal condition
+    let state = state
+        .use_action(Action::HeartAndSoul, Condition::Normal, &SETTINGS)
+        .unwrap()
+        .use_action(Action::TricksOfTheTrade, Condition::Normal, &SETTINGS)
+        .unwrap();
+    assert_eq!(state.cp, 174);
+    assert_eq!(state.effects.heart_and_soul(), SingleUse::Unavailable);
+    // restored CP is capped at max_cp
+    let state = SimulationState::new(&SETTINGS)
+        .use_action(Action::TricksOfTheTrade, Condition::Good, &SETTINGS)
+        .unwrap();
+    assert_eq!(state.cp, SETTINGS.max_cp);
+}
+
+#[test]
+fn test_careful_observation() {
+    let initial_state = SimulationState::from_macro(
+        &SETTINGS,
+        &[Action::Manipulation, Action::Veneration, Action::BasicTouch],
+    )
+    .unwrap();
+    let mut state = initial_state;
+    for _ in 0..3 {
+        state = state
+            .use_action(Action::CarefulObservation, Condition::Poor, &SETTINGS)
+            .unwrap();
+    }
+    // no CP, durability, progress or quality change, and effects are not ticked down
+    assert_eq!(state.cp, initial_state.cp);
+    assert_eq!(state.durability, initial_state.durability);
+    assert_eq!(state.progress, initial_state.progress);
+    assert_eq!(state.get_quality(), initial_state.get_quality());
+    assert_eq!(
+        state.effects.manipulation(),
+        initial_state.effects.manipulation()
+    );
+    assert_eq!(
+        state.effects.veneration(),
+        initial_state.effects.veneration()
+    );
+    assert_eq!(
+        state.effects.inner_quiet(),
+        initial_state.effects.inner_quiet()
+    );
+    // limited to 3 uses per synthesis
+    assert_eq!(
+        state.use_action(Action::CarefulObservation, Condition::Normal, &SETTINGS),
+        Err("Action can only be used 3 times per synthesis")
+    );
+}
+
+#[test]
+fn test_careful_observation_keeps_combo() {
+    let state = SimulationState::from_macro(
+        &SETTINGS,
+        &[
+            Action::BasicTouch,
+            Action::CarefulObservation,
+            Action::ComboStandardTouch,
+        ],
+    );
+    assert!(state.is_ok());
+}
+
+#[test]
+fn test_heart_and_soul_not_used_up_by_good_condition() {
+    let state = SimulationState::from_macro(&SETTINGS, &[Action::HeartAndSoul]).unwrap();
+    for action in [
+        Action::IntensiveSynthesis,
+        Action::PreciseTouch,
+        Action::TricksOfTheTrade,
+    ] {
+        let state = state
+            .use_action(action, Condition::Good, &SETTINGS)
+            .unwrap();
+        assert_eq!(state.effects.heart_and_soul(), SingleUse::Active);
+        let state = state
+            .use_action(action, Condition::Poor, &SETTINGS)
+            .unwrap();
+        assert_eq!(state.effects.heart_and_soul(), SingleUse::Unavailable);
+        assert_eq!(
+            state.use_action(action, Condition::Normal, &SETTINGS),
+            Err("Requires condition to be Good or Excellent")
+        );
+    }
+}
+
 #[test]
 fn test_heart_and_soul() {
     let settings = Settings {
@@ -399,3 +678,82 @@ fn test_quick_innovation() {
         Err("Action cannot be used when Innovation is active")
     ));
 }
+
+#[test]
+fn test_sturdy_durability_cost() {
+    let state = SimulationState::new(&SETTINGS);
+    let after_synthesis = state
+        .use_action(Action::BasicSynthesis, Condition::Sturdy, &SETTINGS)
+        .unwrap();
+    assert_eq!(after_synthesis.durability, SETTINGS.max_durability - 5);
+    // 5 durability halved is rounded up
+    let after_prudent = state
+        .use_action(Action::PrudentTouch, Condition::Sturdy, &SETTINGS)
+        .unwrap();
+    assert_eq!(after_prudent.durability, SETTINGS.max_durability - 3);
+    // Sturdy stacks with Waste Not: 10 * 50% * 50% = 2.5, rounded up
+    let after_waste_not = state
+        .use_action(Action::WasteNot, Condition::Normal, &SETTINGS)
+        .unwrap()
+        .use_action(Action::BasicTouch, Condition::Sturdy, &SETTINGS)
+        .unwrap();
+    assert_eq!(after_waste_not.durability, SETTINGS.max_durability - 3);
+    // Sturdy doesn't affect the CP cost
+    assert_eq!(after_waste_not.cp, SETTINGS.max_cp - 56 - 18);
+}
+
+#[test]
+fn test_pliant_cp_cost() {
+    let state = SimulationState::new(&SETTINGS);
+    let after_touch = state
+        .use_action(Action::BasicTouch, Condition::Pliant, &SETTINGS)
+        .unwrap();
+    assert_eq!(after_touch.cp, SETTINGS.max_cp - 9);
+    // Pliant doesn't affect the durability cost
+    assert_eq!(after_touch.durability, SETTINGS.max_durability - 10);
+    // 7 CP halved is rounded up
+    let after_observe = state
+        .use_action(Action::Observe, Condition::Pliant, &SETTINGS)
+        .unwrap();
+    assert_eq!(after_observe.cp, SETTINGS.max_cp - 4);
+    // Pliant can enable an action that would otherwise cost too much CP
+    let low_cp_state = SimulationState { cp: 50, ..state };
+    assert!(low_cp_state
+        .use_action(Action::Manipulation, Condition::Normal, &SETTINGS)
+        .is_err());
+    let after_manipulation = low_cp_state
+        .use_action(Action::Manipulation, Condition::Pliant, &SETTINGS)
+        .unwrap();
+    assert_eq!(after_manipulation.cp, 50 - 48);
+}
+
+#[test]
+fn test_inner_quiet_quality_scaling() {
+    for base_quality in [100, 257, 333] {
+        let settings = Settings {
+            base_quality,
+            ..SETTINGS
+        };
+        let state = SimulationState::new(&settings);
+        let full_inner_quiet = SimulationState {
+            effects: state.effects.with_inner_quiet(10),
+            ..state
+        };
+        for action in [
+            Action::BasicTouch,
+            Action::StandardTouch,
+            Action::AdvancedTouch,
+        ] {
+            let quality_0 = state
+                .use_action(action, Condition::Normal, &settings)
+                .unwrap()
+                .get_quality();
+            let quality_10 = full_inner_quiet
+                .use_action(action, Condition::Normal, &settings)
+                .unwrap()
+                .get_quality();
+            // 10 stacks double the Quality increase, the only difference is rounding
+            assert!(quality_10.abs_diff(2 * quality_0) <= 1);
+        }
+    }
+}
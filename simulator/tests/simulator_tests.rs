@@ -1,13 +1,18 @@
-use simulator::{Action, ActionMask, Condition, Settings, SimulationState};
+use fuzz_rng::fuzz_rng;
+use rand::{seq::SliceRandom, Rng};
+use simulator::{
+    Action, ActionMask, ActionReplay, Condition, EffectsApplied, Settings, SimulationState,
+    SynthesisStatus,
+};
 
 fn simulate(
     settings: &Settings,
     steps: impl Iterator<Item = (Action, Condition)>,
 ) -> Result<Vec<SimulationState>, &'static str> {
-    let mut state = SimulationState::new(&settings);
+    let mut state = SimulationState::new(settings);
     let mut result = Vec::new();
     for (action, condition) in steps {
-        state = state.use_action(action, condition, &settings)?;
+        state = state.use_action(action, condition, settings)?;
         result.push(state);
     }
     Ok(result)
@@ -396,3 +401,362 @@ fn test_ingame_f9f0dac7() {
     ];
     assert_eq!(states, expected);
 }
+
+#[test]
+/// Test that random action sequences never exceed the theoretical Quality ceiling
+fn test_max_theoretical_quality_fuzz() {
+    let settings = Settings {
+        max_cp: 600,
+        max_durability: 70,
+        max_progress: 60000,
+        max_quality: 60000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::all(),
+        adversarial: false,
+    };
+    let ceiling = simulator::max_theoretical_quality(&settings);
+    let actions: Vec<Action> = settings.allowed_actions.actions_iter().collect();
+    let mut rng = fuzz_rng();
+    for _ in 0..10000 {
+        let mut state = SimulationState::new(&settings);
+        for _ in 0..100 {
+            if state.is_final(&settings) {
+                break;
+            }
+            let action = *actions.choose(&mut rng).unwrap();
+            if let Ok(new_state) = state.use_action(action, Condition::Normal, &settings) {
+                state = new_state;
+            }
+        }
+        assert!(state.get_quality() <= ceiling);
+    }
+}
+
+#[test]
+/// Test that all entry points into the simulator agree on the outcome of random action sequences.
+/// The solvers chain `use_action` while the GUI uses `from_macro` and friends, so any divergence would make them disagree.
+fn test_entry_points_agree_fuzz() {
+    let settings = Settings {
+        max_cp: 300,
+        max_durability: 40,
+        // low enough for random sequences to regularly complete the craft
+        max_progress: 1000,
+        max_quality: 10000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::all(),
+        adversarial: false,
+    };
+    let actions: Vec<Action> = settings.allowed_actions.actions_iter().collect();
+    let mut rng = fuzz_rng();
+    let (mut completed, mut failed, mut errors) = (0, 0, 0);
+    for _ in 0..10000 {
+        // mostly legal actions, with the occasional action that may be illegal in the current state
+        let mut state = SimulationState::new(&settings);
+        let mut sequence = Vec::new();
+        while !state.is_final(&settings) && sequence.len() < 50 {
+            let action = *actions.choose(&mut rng).unwrap();
+            match state.use_action(action, Condition::Normal, &settings) {
+                Ok(new_state) => state = new_state,
+                Err(_) if rng.gen::<u8>() < 16 => {}
+                Err(_) => continue,
+            }
+            sequence.push(action);
+        }
+
+        let mut expected: Result<SimulationState, &'static str> =
+            Ok(SimulationState::new(&settings));
+        for action in sequence.iter() {
+            expected =
+                expected.and_then(|state| state.use_action(*action, Condition::Normal, &settings));
+        }
+        assert_eq!(SimulationState::from_macro(&settings, &sequence), expected);
+
+        let mut replay = ActionReplay::new(&settings, sequence.iter().copied());
+        let replayed_steps = replay.by_ref().count();
+        let (state, step_results) =
+            SimulationState::from_macro_continue_on_error(&settings, &sequence);
+        match expected {
+            Ok(expected) => {
+                assert_eq!(replay.state(), expected);
+                assert_eq!(replay.error(), None);
+                assert_eq!(replayed_steps, sequence.len());
+                assert_eq!(state, expected);
+                assert!(step_results.iter().all(Result::is_ok));
+                match expected.progress >= settings.max_progress {
+                    true => completed += 1,
+                    false if expected.durability <= 0 => failed += 1,
+                    false => (),
+                }
+            }
+            Err(error) => {
+                assert_eq!(replay.error(), Some(error));
+                // every entry point fails on the same action with the same error
+                let first_error = step_results.iter().position(Result::is_err).unwrap();
+                assert_eq!(replayed_steps, first_error);
+                assert_eq!(step_results[first_error], Err(error));
+                assert_eq!(
+                    Ok(replay.state()),
+                    SimulationState::from_macro(&settings, &sequence[..first_error])
+                );
+                errors += 1;
+            }
+        }
+    }
+    // the sequences cover completed crafts, broken crafts and illegal actions
+    assert!(completed > 0 && failed > 0 && errors > 0);
+}
+
+#[test]
+fn test_initial_durability() {
+    let settings = Settings {
+        max_cp: 300,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 10000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::all(),
+        adversarial: false,
+    };
+    let state = SimulationState::with_initial_durability(&settings, 20);
+    assert_eq!(state.durability, 20);
+    assert_eq!(state.cp, settings.max_cp);
+    // restoration is still capped at max_durability
+    let state = state
+        .use_action(Action::MasterMend, Condition::Normal, &settings)
+        .unwrap();
+    assert_eq!(state.durability, 50);
+    let state = state
+        .use_action(Action::MasterMend, Condition::Normal, &settings)
+        .unwrap();
+    assert_eq!(state.durability, 60);
+    // durability can't start above max_durability
+    let state = SimulationState::with_initial_durability(&settings, 80);
+    assert_eq!(state.durability, 60);
+}
+
+#[test]
+fn test_initial_cp() {
+    let settings = Settings {
+        max_cp: 300,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 10000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::all(),
+        adversarial: false,
+    };
+    let state = SimulationState::with_initial_cp(&settings, 20);
+    assert_eq!(state.cp, 20);
+    assert_eq!(state.durability, settings.max_durability);
+    let after_touch = state
+        .use_action(Action::BasicTouch, Condition::Normal, &settings)
+        .unwrap();
+    assert_eq!(after_touch.cp, 2);
+    // actions that cost more than the starting CP can't be used, even though max_cp would allow them
+    assert!(state
+        .use_action(Action::PreparatoryTouch, Condition::Normal, &settings)
+        .is_err());
+    // CP can't start above max_cp
+    let state = SimulationState::with_initial_cp(&settings, 500);
+    assert_eq!(state.cp, 300);
+}
+
+#[test]
+fn test_actions_with_conditions() {
+    let settings = Settings {
+        max_cp: 250,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 40000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::all(),
+        adversarial: false,
+    };
+    let actions = [Action::BasicTouch, Action::BasicTouch, Action::BasicTouch];
+    // each step gets the multiplier of its own condition, on top of the Inner Quiet stacks
+    let steps = [
+        (Condition::Good, 150),
+        (Condition::Excellent, 400 * 11 / 10),
+        (Condition::Poor, 50 * 12 / 10),
+    ];
+    let mut state = SimulationState::new(&settings);
+    let conditions = steps.map(|(condition, _)| condition);
+    for (i, (_, quality_increase)) in steps.into_iter().enumerate() {
+        let new_state = SimulationState::new(&settings)
+            .use_actions_with_conditions(&actions[..=i], &conditions, &settings)
+            .unwrap();
+        assert_eq!(
+            new_state.get_quality(),
+            state.get_quality() + quality_increase
+        );
+        state = new_state;
+    }
+    assert_eq!(state.get_quality(), 150 + 440 + 60);
+    // the same actions with Normal condition on every step
+    let normal = SimulationState::from_macro(&settings, &actions).unwrap();
+    assert_eq!(
+        SimulationState::new(&settings).use_actions_with_conditions(
+            &actions,
+            &[Condition::Normal; 3],
+            &settings
+        ),
+        Ok(normal)
+    );
+    assert_eq!(
+        SimulationState::new(&settings).use_actions_with_conditions(
+            &actions,
+            &conditions[..2],
+            &settings
+        ),
+        Err("Fewer conditions than actions")
+    );
+}
+
+#[test]
+fn test_step_breakdown() {
+    let settings = Settings {
+        max_cp: 500,
+        max_durability: 80,
+        max_progress: 2000,
+        max_quality: 40000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::all(),
+        adversarial: false,
+    };
+    let actions = [
+        Action::MuscleMemory,
+        Action::Veneration,
+        Action::Groundwork,
+        Action::Innovation,
+        Action::BasicTouch,
+        Action::GreatStrides,
+        Action::ByregotsBlessing,
+        Action::BasicSynthesis,
+    ];
+    let steps: Vec<_> = ActionReplay::new(&settings, actions).breakdown().collect();
+    assert_eq!(steps.len(), actions.len());
+    let final_state = SimulationState::from_macro(&settings, &actions).unwrap();
+    assert_eq!(
+        steps.iter().map(|step| step.progress_delta).sum::<u16>(),
+        final_state.progress
+    );
+    assert_eq!(
+        steps.iter().map(|step| step.quality_delta).sum::<u16>(),
+        final_state.get_quality()
+    );
+    assert_eq!(steps.last().unwrap().state, final_state);
+
+    // Groundwork with Muscle Memory and Veneration
+    assert_eq!(steps[2].progress_delta, 360 * 25 / 10);
+    assert_eq!(
+        steps[2].effects_applied,
+        EffectsApplied {
+            veneration: true,
+            muscle_memory: true,
+            ..Default::default()
+        }
+    );
+    // Byregot's Blessing with 1 Inner Quiet, Innovation and Great Strides
+    assert_eq!(steps[6].quality_delta, 120 * 11 / 10 * 25 / 10);
+    assert_eq!(
+        steps[6].effects_applied,
+        EffectsApplied {
+            inner_quiet: 1,
+            innovation: true,
+            great_strides: true,
+            ..Default::default()
+        }
+    );
+    // buff actions don't gain anything themselves
+    assert_eq!(steps[3].quality_delta, 0);
+    assert_eq!(steps[3].effects_applied, EffectsApplied::default());
+}
+
+#[test]
+fn test_synthesis_status() {
+    let settings = Settings {
+        max_cp: 500,
+        max_durability: 20,
+        max_progress: 200,
+        max_quality: 100,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::all(),
+        adversarial: false,
+    };
+    let status = |actions: &[Action]| {
+        SimulationState::from_macro(&settings, actions)
+            .unwrap()
+            .status(&settings)
+    };
+    assert_eq!(status(&[]), SynthesisStatus::InProgress);
+    // Quality is maxed out, but Progress can still be completed
+    assert_eq!(status(&[Action::BasicTouch]), SynthesisStatus::InProgress);
+    assert_eq!(
+        status(&[Action::BasicTouch, Action::BasicTouch]),
+        SynthesisStatus::Failed
+    );
+    assert_eq!(
+        status(&[Action::BasicSynthesis, Action::BasicSynthesis]),
+        SynthesisStatus::Completed
+    );
+    // completing Progress with the last durability still completes the synthesis
+    let settings = Settings {
+        max_progress: 100,
+        ..settings
+    };
+    let state =
+        SimulationState::from_macro(&settings, &[Action::BasicTouch, Action::BasicSynthesis])
+            .unwrap();
+    assert!(state.durability <= 0);
+    assert_eq!(state.status(&settings), SynthesisStatus::Completed);
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let settings = Settings {
+        max_cp: 250,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 40000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::from_level(100).remove(Action::TrainedEye),
+        adversarial: true,
+    };
+    let state = SimulationState::from_macro(
+        &settings,
+        &[
+            Action::MuscleMemory,
+            Action::Manipulation,
+            Action::BasicTouch,
+            Action::HeartAndSoul,
+        ],
+    )
+    .unwrap();
+    let serialized = serde_json::to_string(&(settings, state)).unwrap();
+    // changing the format breaks debug states that were copied from older versions
+    assert_eq!(
+        serialized,
+        concat!(
+            r#"[{"max_cp":250,"max_durability":60,"max_progress":2000,"max_quality":40000,"base_progress":100,"base_quality":100,"job_level":100,"allowed_actions":{"mask":17045651455},"adversarial":true},"#,
+            r#"{"cp":130,"durability":45,"progress":300,"unreliable_quality":[100,50],"effects":784334889,"combo":"None"}]"#
+        )
+    );
+    let deserialized: (Settings, SimulationState) = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, (settings, state));
+}
@@ -0,0 +1,38 @@
+use simulator::{Action, ActionMask, Settings, SimulationState};
+use solvers::SolverConfig;
+use tokio_stream::StreamExt;
+
+#[tokio::main]
+async fn main() {
+    // Ra'Kaznar Lapidary Hammer
+    // 4462 Craftsmanship, 4391 Control
+    let settings = Settings {
+        max_cp: 569,
+        max_durability: 80,
+        max_progress: 6600,
+        max_quality: 10000,
+        base_progress: 237,
+        base_quality: 245,
+        job_level: 100,
+        allowed_actions: ActionMask::from_level(100)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+
+    let (solution, mut progress) = solvers::solve_async(settings, SolverConfig::default());
+    // the solver keeps running while the progress is being reported
+    let report = tokio::spawn(async move {
+        while let Some(progress) = progress.next().await {
+            eprintln!("progress: {:.2}%", progress * 100.0);
+        }
+    });
+    let result = solution.await.unwrap();
+    report.await.unwrap();
+
+    let quality = SimulationState::from_macro(&settings, &result.actions)
+        .unwrap()
+        .get_quality();
+    dbg!(quality, result.actions.len(), result.cp_used);
+}
@@ -1,5 +1,5 @@
 use simulator::{Action, ActionMask, Settings, SimulationState};
-use solvers::MacroSolver;
+use solvers::{MacroSolver, SolverConfig};
 
 fn main() {
     dbg!(std::mem::size_of::<SimulationState>());
@@ -23,9 +23,12 @@ fn main() {
     };
 
     let state = SimulationState::new(&settings);
-    let actions = MacroSolver::new(settings, Box::new(|_| {}), Box::new(|_| {}))
-        .solve(state, false, true)
-        .unwrap();
+    let config = SolverConfig {
+        minimize_steps: true,
+        ..Default::default()
+    };
+    let mut solver = MacroSolver::new(settings, config, Box::new(|_| {}), Box::new(|_| {}));
+    let actions = solver.solve(state).unwrap();
 
     let quality = SimulationState::from_macro(&settings, &actions)
         .unwrap()
@@ -33,4 +36,5 @@ fn main() {
     let steps = actions.len();
     let duration: i16 = actions.iter().map(|action| action.time_cost()).sum();
     dbg!(quality, steps, duration);
+    dbg!(solver.stats());
 }
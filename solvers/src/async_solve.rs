@@ -0,0 +1,47 @@
+use std::future::Future;
+
+use simulator::{Settings, SimulationState};
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+
+use crate::{MacroSolver, SolveResult, SolverConfig};
+
+/// Runs `MacroSolver::solve` from the initial state on Tokio's blocking thread pool.
+/// Returns the solution and the CP it uses as a future, together with a stream of the solver's progress updates,
+/// which ends once the solve is done. Must be called from within a Tokio runtime.
+pub fn solve_async(
+    settings: Settings,
+    config: SolverConfig,
+) -> (
+    impl Future<Output = Option<SolveResult>>,
+    impl Stream<Item = f32>,
+) {
+    let (progress_tx, progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    let handle = tokio::task::spawn_blocking(move || {
+        let progress_callback = move |progress: f32| {
+            // the caller may not be interested in the progress and drop the stream
+            let _ = progress_tx.send(progress);
+        };
+        let initial_state = SimulationState::new(&settings);
+        let actions = MacroSolver::new(
+            settings,
+            config,
+            Box::new(|_| {}),
+            Box::new(progress_callback),
+        )
+        .solve(initial_state)?;
+        let final_state = SimulationState::from_macro(&settings, &actions).unwrap();
+        Some(SolveResult {
+            actions,
+            cp_used: initial_state.cp - final_state.cp,
+        })
+    });
+    let solution = async move {
+        match handle.await {
+            Ok(solution) => solution,
+            Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+            // the runtime is shutting down
+            Err(_) => None,
+        }
+    };
+    (solution, UnboundedReceiverStream::new(progress_rx))
+}
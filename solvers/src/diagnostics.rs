@@ -0,0 +1,252 @@
+use simulator::{Action, Settings, SimulationState};
+
+use crate::{FinishSolver, QualityUpperBoundSolver};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// CP left over that couldn't be spent on Quality because durability ran out
+    WastedCp(i16),
+    /// CP and durability are left over, so Quality is held back by having to max out Progress
+    QualityCappedByProgress,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::WastedCp(cp) => write!(f, "{cp} CP left unused because durability ran out"),
+            Diagnostic::QualityCappedByProgress => write!(
+                f,
+                "CP and durability left unused, Quality is limited by the Progress requirement"
+            ),
+        }
+    }
+}
+
+/// Explain why the Quality of a completed synthesis doesn't reach `settings.max_quality`.
+/// Returns no diagnostics if the synthesis isn't completed or if Quality is maxed out.
+pub fn diagnose(settings: &Settings, state: &SimulationState) -> Vec<Diagnostic> {
+    if state.progress < settings.max_progress || state.get_quality() >= settings.max_quality {
+        return Vec::new();
+    }
+    // cheapest action that can increase Quality outside of special conditions
+    let quality_action = Action::BasicTouch;
+    if state.cp < quality_action.cp_cost() {
+        return Vec::new();
+    }
+    if state.durability >= quality_action.base_durability_cost() {
+        vec![Diagnostic::QualityCappedByProgress]
+    } else {
+        vec![Diagnostic::WastedCp(state.cp)]
+    }
+}
+
+/// Whether Progress can be maxed out from the initial state, regardless of Quality.
+/// Much cheaper than a full solve, which makes it suitable for testing many candidate settings.
+pub fn can_complete(settings: &Settings) -> bool {
+    FinishSolver::new(*settings).can_finish(&SimulationState::new(settings))
+}
+
+/// Upper-bound on the Quality that could be reached if durability weren't a constraint, clamped to 2 times `settings.max_quality`.
+/// Comparing it to the solved Quality shows how much Quality is lost to managing durability.
+/// Like `QualityUpperBoundSolver::quality_upper_bound`, there is no guarantee that the bound is tight.
+pub fn unconstrained_durability_quality(settings: &Settings) -> u16 {
+    // with unlimited durability, Progress can always be maxed out for free by repeating Basic Synthesis,
+    // so any Progress is enough and the Quality of the first value of the front is reachable
+    QualityUpperBoundSolver::new(*settings)
+        .with_unlimited_durability()
+        .pareto_front(SimulationState::new(settings))
+        .first()
+        .map_or(0, |value| value.second)
+}
+
+/// Resource that holds back the Quality of a completed synthesis, see `limiting_resource`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitingResource {
+    Cp,
+    Durability,
+    /// CP and durability are left over, but the steps had to be spent on maxing out Progress
+    Progress,
+}
+
+impl std::fmt::Display for LimitingResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitingResource::Cp => write!(f, "CP"),
+            LimitingResource::Durability => write!(f, "durability"),
+            LimitingResource::Progress => write!(f, "the Progress requirement"),
+        }
+    }
+}
+
+/// Classify what ran out first in a completed synthesis that doesn't reach `settings.max_quality`.
+/// Returns `None` if the synthesis isn't completed or if Quality is maxed out.
+pub fn limiting_resource(settings: &Settings, state: &SimulationState) -> Option<LimitingResource> {
+    if state.progress < settings.max_progress || state.get_quality() >= settings.max_quality {
+        return None;
+    }
+    match diagnose(settings, state).first() {
+        Some(Diagnostic::WastedCp(_)) => Some(LimitingResource::Durability),
+        Some(Diagnostic::QualityCappedByProgress) => Some(LimitingResource::Progress),
+        None => Some(LimitingResource::Cp),
+    }
+}
+
+/// Quality upper-bounds of the initial state, with one constraint of the settings relaxed at a time.
+/// Comparing them shows how much Quality a bit more CP or not having to manage durability could gain at most.
+/// All bounds are clamped to `settings.max_quality`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RelaxedQualityBounds {
+    /// Upper-bound without any relaxation
+    pub baseline: u16,
+    /// Upper-bound with `extra_cp` more CP
+    pub extra_cp: u16,
+    /// Upper-bound if durability weren't a constraint, see `unconstrained_durability_quality`
+    pub unlimited_durability: u16,
+}
+
+impl RelaxedQualityBounds {
+    /// Solves the upper-bound once per relaxation, which takes about as long as the setup of a regular solve.
+    pub fn new(settings: &Settings, extra_cp: i16) -> Self {
+        let cp_settings = Settings {
+            max_cp: settings.max_cp.saturating_add(extra_cp),
+            ..*settings
+        };
+        let initial_bound = |settings: &Settings| {
+            QualityUpperBoundSolver::new(*settings)
+                .quality_upper_bound(SimulationState::new(settings))
+        };
+        Self {
+            baseline: std::cmp::min(initial_bound(settings), settings.max_quality),
+            extra_cp: std::cmp::min(initial_bound(&cp_settings), settings.max_quality),
+            unlimited_durability: std::cmp::min(
+                unconstrained_durability_quality(settings),
+                settings.max_quality,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use simulator::ActionMask;
+
+    use super::*;
+
+    const SETTINGS: Settings = Settings {
+        max_cp: 200,
+        max_durability: 30,
+        max_progress: 500,
+        max_quality: 2000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::all(),
+        adversarial: false,
+    };
+
+    #[test]
+    fn test_can_complete() {
+        assert!(can_complete(&SETTINGS));
+        let settings = Settings {
+            max_progress: 5000,
+            max_cp: 0,
+            ..SETTINGS
+        };
+        assert!(!can_complete(&settings));
+    }
+
+    #[test]
+    fn test_wasted_cp() {
+        let state =
+            SimulationState::from_macro(&SETTINGS, &[Action::MuscleMemory, Action::Groundwork])
+                .unwrap();
+        assert_eq!(diagnose(&SETTINGS, &state), [Diagnostic::WastedCp(176)]);
+    }
+
+    #[test]
+    fn test_quality_capped_by_progress() {
+        let state =
+            SimulationState::from_macro(&SETTINGS, &[Action::MuscleMemory, Action::BasicSynthesis])
+                .unwrap();
+        assert_eq!(
+            diagnose(&SETTINGS, &state),
+            [Diagnostic::QualityCappedByProgress]
+        );
+    }
+
+    #[test]
+    fn test_no_diagnostics() {
+        // synthesis is not completed
+        let state = SimulationState::from_macro(&SETTINGS, &[Action::BasicTouch]).unwrap();
+        assert!(diagnose(&SETTINGS, &state).is_empty());
+        // Quality is maxed out
+        let settings = Settings {
+            max_quality: 100,
+            ..SETTINGS
+        };
+        let state = SimulationState::from_macro(
+            &settings,
+            &[
+                Action::MuscleMemory,
+                Action::BasicTouch,
+                Action::BasicSynthesis,
+            ],
+        )
+        .unwrap();
+        assert!(diagnose(&settings, &state).is_empty());
+    }
+
+    #[test]
+    fn test_limiting_resource() {
+        let state =
+            SimulationState::from_macro(&SETTINGS, &[Action::MuscleMemory, Action::Groundwork])
+                .unwrap();
+        assert_eq!(
+            limiting_resource(&SETTINGS, &state),
+            Some(LimitingResource::Durability)
+        );
+        let state =
+            SimulationState::from_macro(&SETTINGS, &[Action::MuscleMemory, Action::BasicSynthesis])
+                .unwrap();
+        assert_eq!(
+            limiting_resource(&SETTINGS, &state),
+            Some(LimitingResource::Progress)
+        );
+        let settings = Settings {
+            max_cp: 24,
+            ..SETTINGS
+        };
+        let state = SimulationState::from_macro(
+            &settings,
+            &[
+                Action::MuscleMemory,
+                Action::BasicTouch,
+                Action::BasicSynthesis,
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            limiting_resource(&settings, &state),
+            Some(LimitingResource::Cp)
+        );
+        let state = SimulationState::from_macro(&SETTINGS, &[Action::BasicTouch]).unwrap();
+        assert_eq!(limiting_resource(&SETTINGS, &state), None);
+    }
+
+    #[test]
+    fn test_relaxed_quality_bounds() {
+        let bounds = RelaxedQualityBounds::new(&SETTINGS, 50);
+        assert!(bounds.extra_cp >= bounds.baseline);
+        assert!(bounds.unlimited_durability >= bounds.baseline);
+        assert!(bounds.extra_cp <= SETTINGS.max_quality);
+        // a synthesis that easily maxes out Quality doesn't gain anything from the relaxations
+        let settings = Settings {
+            max_quality: 100,
+            ..SETTINGS
+        };
+        let bounds = RelaxedQualityBounds::new(&settings, 50);
+        assert_eq!(bounds.baseline, 100);
+        assert_eq!(bounds.extra_cp, 100);
+        assert_eq!(bounds.unlimited_durability, 100);
+    }
+}
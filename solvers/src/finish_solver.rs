@@ -6,9 +6,11 @@ use rustc_hash::FxHashMap as HashMap;
 
 use super::actions::{DURABILITY_ACTIONS, PROGRESS_ACTIONS};
 
+// Tricks of the Trade is only usable with Heart and Soul, as the search assumes Normal condition
 const SEARCH_ACTIONS: ActionMask = PROGRESS_ACTIONS
     .union(DURABILITY_ACTIONS)
-    .remove(Action::DelicateSynthesis);
+    .remove(Action::DelicateSynthesis)
+    .add(Action::TricksOfTheTrade);
 
 #[bitfield_struct::bitfield(u16)]
 #[derive(PartialEq, Eq, Hash)]
@@ -76,6 +78,7 @@ impl ReducedState {
                 .to_effects()
                 .with_trained_perfection(self.trained_perfection),
             combo: self.combo,
+            careful_observation_uses: 0,
         }
     }
 }
@@ -88,14 +91,17 @@ pub struct FinishSolver {
 
 impl FinishSolver {
     pub fn new(settings: Settings) -> FinishSolver {
-        dbg!(std::mem::size_of::<ReducedState>());
-        dbg!(std::mem::align_of::<ReducedState>());
         FinishSolver {
             settings,
             max_progress: HashMap::default(),
         }
     }
 
+    /// Number of memoized states.
+    pub fn num_states(&self) -> usize {
+        self.max_progress.len()
+    }
+
     pub fn can_finish(&mut self, state: &SimulationState) -> bool {
         let max_progress = self.solve_max_progress(ReducedState::from_state(state));
         state.progress + max_progress >= self.settings.max_progress
@@ -137,9 +143,3 @@ impl FinishSolver {
         }
     }
 }
-
-impl Drop for FinishSolver {
-    fn drop(&mut self) {
-        dbg!(self.max_progress.len());
-    }
-}
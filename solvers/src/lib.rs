@@ -1,50 +1,63 @@
 mod actions;
 mod utils;
+pub use utils::ParetoValue;
 
 mod finish_solver;
 use finish_solver::FinishSolver;
 
 mod quality_upper_bound_solver;
-use quality_upper_bound_solver::QualityUpperBoundSolver;
+pub use quality_upper_bound_solver::QualityUpperBoundSolver;
 
 mod step_lower_bound_solver;
 use step_lower_bound_solver::StepLowerBoundSolver;
 
 mod macro_solver;
-pub use macro_solver::MacroSolver;
+pub use macro_solver::{
+    MacroSolver, QualityOverflow, SolutionTrace, SolveResult, SolveStats, SolverCache, SolverConfig,
+};
+
+mod diagnostics;
+pub use diagnostics::{
+    can_complete, diagnose, limiting_resource, unconstrained_durability_quality, Diagnostic,
+    LimitingResource, RelaxedQualityBounds,
+};
+
+#[cfg(feature = "async")]
+mod async_solve;
+#[cfg(feature = "async")]
+pub use async_solve::solve_async;
 
 pub mod test_utils {
     use simulator::{Action, Condition, Settings, SimulationState};
 
-    use crate::MacroSolver;
+    use crate::{MacroSolver, SolverConfig};
 
     pub fn solve(
         settings: &Settings,
         backload_progress: bool,
         minimize_steps: bool,
     ) -> Option<Vec<Action>> {
-        MacroSolver::new(settings.clone(), Box::new(|_| {}), Box::new(|_| {})).solve(
-            SimulationState::new(settings),
+        let config = SolverConfig {
             backload_progress,
             minimize_steps,
-        )
+            ..Default::default()
+        };
+        MacroSolver::new(*settings, config, Box::new(|_| {}), Box::new(|_| {}))
+            .solve(SimulationState::new(settings))
     }
 
     pub fn get_score_triple(settings: &Settings, actions: &[Action]) -> (u16, u8, u8) {
         let quality = get_quality(settings, actions);
         let steps = actions.len() as u8;
-        let duration: u8 = actions
-            .into_iter()
-            .map(|action| action.time_cost() as u8)
-            .sum();
+        let duration: u8 = actions.iter().map(|action| action.time_cost() as u8).sum();
         (quality, steps, duration)
     }
 
     fn get_quality(settings: &Settings, actions: &[Action]) -> u16 {
-        let mut state = SimulationState::new(&settings);
+        let mut state = SimulationState::new(settings);
         for action in actions {
             state = state
-                .use_action(action.clone(), Condition::Normal, &settings)
+                .use_action(*action, Condition::Normal, settings)
                 .unwrap();
         }
         assert!(state.progress >= settings.max_progress);
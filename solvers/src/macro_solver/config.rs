@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use simulator::ActionMask;
+
+use super::search_queue::QualityOverflow;
+
+/// Options of a `MacroSolver`. The defaults solve for the best macro without any limits,
+/// so callers only set the fields they care about, e.g. `SolverConfig { minimize_steps: true, ..Default::default() }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SolverConfig {
+    /// Only use Progress-increasing actions at the end of the macro, which is faster to solve but may lose Quality.
+    pub backload_progress: bool,
+    /// Guarantee the shortest macro among the macros with the best Quality.
+    /// Otherwise, the solver may return the first macro it finds that maxes out Quality.
+    pub minimize_steps: bool,
+    /// Only consider macros with at most `max_steps` steps.
+    pub max_steps: Option<u8>,
+    /// Approximation mode: trade precision for speed by allowing the found Quality to be less than optimal.
+    /// Branches that can't improve on the best macro found so far by more than `quality_tolerance` are not searched,
+    /// so the found Quality is at most `quality_tolerance` less than the optimal Quality (capped at `max_quality`).
+    /// Only Quality is guaranteed, the found macro may use more steps than an optimal one.
+    pub quality_tolerance: u16,
+    /// How Quality in excess of `max_quality` is weighted against the number of steps and duration.
+    pub quality_overflow: QualityOverflow,
+    /// Stop the search once `timeout` has elapsed and return the best macro found up to that point.
+    /// The timeout is only checked in the main search loop, so setting up the bounds at the start of the search isn't interrupted.
+    /// On WASM without the Performance API, the timeout is turned into a rough budget of search nodes.
+    pub timeout: Option<Duration>,
+    /// Never use these actions, on top of the actions that `settings.allowed_actions` already excludes.
+    /// E.g. for actions that a macro tool can't parse.
+    pub forbidden_actions: ActionMask,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            backload_progress: false,
+            minimize_steps: false,
+            max_steps: None,
+            quality_tolerance: 0,
+            quality_overflow: QualityOverflow::default(),
+            timeout: None,
+            forbidden_actions: ActionMask::none(),
+        }
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
     finish_solver::FinishSolver,
     macro_solver::pareto_front::QualityParetoFront,
     utils::NamedTimer,
-    QualityUpperBoundSolver,
+    QualityOverflow, QualityUpperBoundSolver,
 };
 
 const SEARCH_ACTIONS: ActionMask = QUALITY_ACTIONS
@@ -52,7 +52,8 @@ pub fn fast_lower_bound(
                     if quality_upper_bound <= quality_lower_bound {
                         continue;
                     }
-                    if !pareto_set.insert(state, settings) {
+                    // the lower-bound is capped at max_quality, so overflow is irrelevant
+                    if !pareto_set.insert(state, settings, QualityOverflow::Ignore) {
                         continue;
                     }
                     search_queue.push(quality_upper_bound, state);
@@ -61,7 +62,6 @@ pub fn fast_lower_bound(
         }
     }
 
-    dbg!(quality_lower_bound);
     std::cmp::min(settings.max_quality, quality_lower_bound)
 }
 
@@ -1,7 +1,10 @@
+mod config;
 mod fast_lower_bound;
 mod pareto_front;
 mod quick_search;
 mod search_queue;
 mod solver;
 
-pub use solver::MacroSolver;
+pub use config::SolverConfig;
+pub use search_queue::QualityOverflow;
+pub use solver::{MacroSolver, SolutionTrace, SolveResult, SolveStats, SolverCache};
@@ -82,6 +82,11 @@ pub struct EffectParetoFront {
 }
 
 impl EffectParetoFront {
+    /// Number of values stored across all buckets of the front.
+    pub fn num_entries(&self) -> usize {
+        self.buckets.values().map(|value| value.len()).sum()
+    }
+
     pub fn insert(&mut self, state: SimulationState, _settings: &Settings) -> bool {
         self.buckets
             .entry(Key::new(state))
@@ -89,10 +94,3 @@ impl EffectParetoFront {
             .insert(Value::from(state))
     }
 }
-
-impl Drop for EffectParetoFront {
-    fn drop(&mut self) {
-        let pareto_entries: usize = self.buckets.values().map(|value| value.len()).sum();
-        dbg!(self.buckets.len(), pareto_entries);
-    }
-}
@@ -1,6 +1,8 @@
 use rustc_hash::FxHashMap;
 use simulator::{Combo, Effects, Settings, SimulationState};
 
+use crate::QualityOverflow;
+
 use super::{Dominate, ParetoFront};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -38,18 +40,20 @@ struct Key {
 }
 
 impl Key {
-    pub fn new(state: SimulationState, settings: &Settings) -> Self {
-        let effects = if state.get_quality() >= settings.max_quality {
-            state
-                .effects
-                .with_inner_quiet(0)
-                .with_innovation(0)
-                .with_great_strides(0)
-                .with_guard(0)
-                .with_quick_innovation_used(true)
-        } else {
-            state.effects.with_inner_quiet(0) // iq is included in the pareto value
-        };
+    pub fn new(state: SimulationState, settings: &Settings, overflow: QualityOverflow) -> Self {
+        // effects that only affect Quality are worthless once Quality is maxed out, unless overflow is preferred
+        let effects =
+            if state.get_quality() >= settings.max_quality && overflow != QualityOverflow::Prefer {
+                state
+                    .effects
+                    .with_inner_quiet(0)
+                    .with_innovation(0)
+                    .with_great_strides(0)
+                    .with_guard(0)
+                    .with_quick_innovation_used(true)
+            } else {
+                state.effects.with_inner_quiet(0) // iq is included in the pareto value
+            };
         Self {
             durability: state.durability,
             progress: state.progress,
@@ -65,17 +69,20 @@ pub struct QualityParetoFront {
 }
 
 impl QualityParetoFront {
-    pub fn insert(&mut self, state: SimulationState, settings: &Settings) -> bool {
+    /// Number of values stored across all buckets of the front.
+    pub fn num_entries(&self) -> usize {
+        self.buckets.values().map(|value| value.len()).sum()
+    }
+
+    pub fn insert(
+        &mut self,
+        state: SimulationState,
+        settings: &Settings,
+        overflow: QualityOverflow,
+    ) -> bool {
         self.buckets
-            .entry(Key::new(state, settings))
+            .entry(Key::new(state, settings, overflow))
             .or_default()
             .insert(Value::new(state))
     }
 }
-
-impl Drop for QualityParetoFront {
-    fn drop(&mut self) {
-        let pareto_entries: usize = self.buckets.values().map(|value| value.len()).sum();
-        dbg!(self.buckets.len(), pareto_entries);
-    }
-}
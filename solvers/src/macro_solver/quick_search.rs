@@ -8,7 +8,7 @@ use crate::{
     QualityUpperBoundSolver,
 };
 
-use super::search_queue::SearchScore;
+use super::search_queue::{QualityOverflow, SearchScore};
 
 const PROGRESS_SEARCH_ACTIONS: ActionMask = PROGRESS_ACTIONS
     .union(DURABILITY_ACTIONS)
@@ -44,12 +44,14 @@ pub fn quick_search(
         0,
         0,
         settings,
+        QualityOverflow::Tiebreak,
     );
     let minimum_score = SearchScore {
         quality: settings.max_quality,
         duration: u8::MAX,
         steps: u8::MAX,
         quality_overflow: 0,
+        overflow_weight: QualityOverflow::Tiebreak,
     };
     let mut search_queue = SearchQueue::new(initial_state, initial_score, minimum_score, *settings);
 
@@ -85,6 +87,7 @@ pub fn quick_search(
                             score.duration + action.time_cost() as u8,
                             score.steps + 1,
                             settings,
+                            QualityOverflow::Tiebreak,
                         ),
                         action,
                         backtrack_id,
@@ -97,6 +100,7 @@ pub fn quick_search(
                         score.duration,
                         score.steps,
                         settings,
+                        QualityOverflow::Tiebreak,
                     ));
                     if solution.is_none() || solution.unwrap().quality < state.get_quality() {
                         solution = Some(Solution {
@@ -115,7 +119,6 @@ pub fn quick_search(
             .backtrack(solution.backtrack_id)
             .chain(std::iter::once(solution.action))
             .collect();
-        dbg!(&actions);
         Some(actions)
     } else {
         None
@@ -6,21 +6,42 @@ use crate::utils::Backtracking;
 
 use super::pareto_front::{EffectParetoFront, QualityParetoFront};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How Quality in excess of `max_quality` is weighted against the other objectives.
+/// Quality up to `max_quality` always takes precedence over everything else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum QualityOverflow {
+    /// Overflow is worthless, e.g. for collectables or gear that is already guaranteed to be HQ.
+    Ignore,
+    /// Prefer more overflow only between macros with the same number of steps and duration.
+    #[default]
+    Tiebreak,
+    /// Prefer more overflow over fewer steps and a shorter duration, as a margin for error.
+    Prefer,
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct SearchScore {
     pub quality: u16,
     pub duration: u8,
     pub steps: u8,
     pub quality_overflow: u16,
+    pub overflow_weight: QualityOverflow,
 }
 
 impl SearchScore {
-    pub fn new(quality: u16, duration: u8, steps: u8, settings: &Settings) -> Self {
+    pub fn new(
+        quality: u16,
+        duration: u8,
+        steps: u8,
+        settings: &Settings,
+        overflow_weight: QualityOverflow,
+    ) -> Self {
         Self {
             quality: std::cmp::min(settings.max_quality, quality),
             duration,
             steps,
             quality_overflow: quality.saturating_sub(settings.max_quality),
+            overflow_weight,
         }
     }
 
@@ -33,6 +54,15 @@ impl SearchScore {
     }
 }
 
+// equality must agree with the ordering because scores are used as keys of the search buckets
+impl std::cmp::PartialEq for SearchScore {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl std::cmp::Eq for SearchScore {}
+
 impl std::cmp::PartialOrd for SearchScore {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(std::cmp::Ord::cmp(self, other))
@@ -40,12 +70,22 @@ impl std::cmp::PartialOrd for SearchScore {
 }
 
 impl std::cmp::Ord for SearchScore {
+    /// Both scores must use the same `overflow_weight`.
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.quality
-            .cmp(&other.quality)
+        debug_assert_eq!(self.overflow_weight, other.overflow_weight);
+        let overflow = self.quality_overflow.cmp(&other.quality_overflow);
+        let ordering = self.quality.cmp(&other.quality);
+        let ordering = match self.overflow_weight {
+            QualityOverflow::Prefer => ordering.then(overflow),
+            _ => ordering,
+        };
+        let ordering = ordering
             .then(other.steps.cmp(&self.steps))
-            .then(other.duration.cmp(&self.duration))
-            .then(self.quality_overflow.cmp(&other.quality_overflow))
+            .then(other.duration.cmp(&self.duration));
+        match self.overflow_weight {
+            QualityOverflow::Tiebreak => ordering.then(overflow),
+            _ => ordering,
+        }
     }
 }
 
@@ -88,6 +128,11 @@ impl SearchQueue {
         }
     }
 
+    /// Number of values stored in the Pareto fronts that are used to prune dominated states.
+    pub fn pareto_front_entries(&self) -> usize {
+        self.quality_pareto_front.num_entries() + self.effect_pareto_front.num_entries()
+    }
+
     pub fn progress_estimate(&self) -> f32 {
         1.0 - self.current_score.difference(&self.minimum_score) / self.initial_score_difference
     }
@@ -134,8 +179,11 @@ impl SearchQueue {
                 self.current_nodes = bucket
                     .into_iter()
                     .filter(|node| {
-                        self.quality_pareto_front.insert(node.state, &self.settings)
-                            && self.effect_pareto_front.insert(node.state, &self.settings)
+                        self.quality_pareto_front.insert(
+                            node.state,
+                            &self.settings,
+                            self.current_score.overflow_weight,
+                        ) && self.effect_pareto_front.insert(node.state, &self.settings)
                     })
                     .map(|node| {
                         let backtrack_id = self.backtracking.push(node.action, node.parent_id);
@@ -167,3 +215,39 @@ fn pareto_weight(state: &SimulationState) -> u32 {
         + state.effects.into_bits()
         + state.combo.into_bits() as u32
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(quality: u16, steps: u8, overflow_weight: QualityOverflow) -> SearchScore {
+        let settings = Settings {
+            max_cp: 0,
+            max_durability: 0,
+            max_progress: 0,
+            max_quality: 1000,
+            base_progress: 0,
+            base_quality: 0,
+            job_level: 100,
+            allowed_actions: simulator::ActionMask::none(),
+            adversarial: false,
+        };
+        SearchScore::new(quality, 0, steps, &settings, overflow_weight)
+    }
+
+    #[test]
+    fn test_quality_overflow_ordering() {
+        let ignore = |quality, steps| score(quality, steps, QualityOverflow::Ignore);
+        assert!(ignore(1500, 10) == ignore(1200, 10));
+        assert!(ignore(1500, 10) < ignore(1200, 9));
+        assert!(ignore(900, 9) < ignore(1000, 10));
+
+        let tiebreak = |quality, steps| score(quality, steps, QualityOverflow::Tiebreak);
+        assert!(tiebreak(1500, 10) > tiebreak(1200, 10));
+        assert!(tiebreak(1500, 10) < tiebreak(1200, 9));
+
+        let prefer = |quality, steps| score(quality, steps, QualityOverflow::Prefer);
+        assert!(prefer(1500, 10) > prefer(1200, 9));
+        assert!(prefer(900, 9) < prefer(1000, 10));
+    }
+}
@@ -1,73 +1,440 @@
 use simulator::{Action, ActionMask, Condition, Settings, SimulationState};
 
+use super::config::SolverConfig;
 use super::quick_search::quick_search;
-use super::search_queue::SearchScore;
+use super::search_queue::{QualityOverflow, SearchScore};
 use crate::actions::{DURABILITY_ACTIONS, PROGRESS_ACTIONS, QUALITY_ACTIONS};
 use crate::macro_solver::fast_lower_bound::fast_lower_bound;
 use crate::macro_solver::search_queue::SearchQueue;
-use crate::utils::NamedTimer;
+use crate::utils::{Deadline, NamedTimer, Stopwatch};
 use crate::{FinishSolver, QualityUpperBoundSolver, StepLowerBoundSolver};
 
+#[cfg(feature = "solve-log")]
+use std::io::Write;
+use std::time::Duration;
 use std::vec::Vec;
 
+// Tricks of the Trade restores CP and is only usable with Heart and Soul, as the search assumes Normal condition
 const FULL_SEARCH_ACTIONS: ActionMask = PROGRESS_ACTIONS
     .union(QUALITY_ACTIONS)
-    .union(DURABILITY_ACTIONS);
+    .union(DURABILITY_ACTIONS)
+    .add(Action::TricksOfTheTrade);
 
 const PROGRESS_SEARCH_ACTIONS: ActionMask = PROGRESS_ACTIONS
     .union(DURABILITY_ACTIONS)
     .remove(Action::DelicateSynthesis);
 
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 struct Solution {
     score: SearchScore,
-    actions: Vec<Action>,
+    // the solution's actions are the backtracked actions followed by `action`
+    backtrack_id: usize,
+    action: Action,
+}
+
+/// Handle to a solution found by the solver.
+/// Reconstructing the actions allocates, so it is only done when the actions are requested.
+pub struct SolutionTrace<'a> {
+    prefix: &'a [Action],
+    source: TraceSource<'a>,
+    quality: u16,
+}
+
+enum TraceSource<'a> {
+    Search {
+        search_queue: &'a SearchQueue,
+        backtrack_id: usize,
+        action: Action,
+    },
+    Actions(&'a [Action]),
+}
+
+impl SolutionTrace<'_> {
+    pub fn len(&self) -> usize {
+        self.prefix.len()
+            + match self.source {
+                TraceSource::Search {
+                    search_queue,
+                    backtrack_id,
+                    ..
+                } => search_queue.steps(backtrack_id) as usize + 1,
+                TraceSource::Actions(actions) => actions.len(),
+            }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Quality of the final state of the solution, which is cheap to get without reconstructing the actions.
+    pub fn quality(&self) -> u16 {
+        self.quality
+    }
+
+    pub fn to_vec(&self) -> Vec<Action> {
+        let mut actions = self.prefix.to_vec();
+        match self.source {
+            TraceSource::Search {
+                search_queue,
+                backtrack_id,
+                action,
+            } => {
+                actions.extend(search_queue.backtrack(backtrack_id));
+                actions.push(action);
+            }
+            TraceSource::Actions(source_actions) => actions.extend_from_slice(source_actions),
+        }
+        actions
+    }
+}
+
+/// Statistics of the most recent `MacroSolver::solve` call, meant for diagnosing performance issues.
+/// The memoized states are never evicted during a solve, so their counts are also the peak memo sizes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SolveStats {
+    pub finish_states: usize,
+    pub quality_upper_bound_states: usize,
+    pub step_lower_bound_states: usize,
+    /// Nodes popped from the search queue, not counting the quick search.
+    pub search_nodes: usize,
+    /// Values in the search queue's Pareto fronts.
+    pub pareto_front_entries: usize,
+    /// Not available on WASM if the Performance API is missing.
+    pub solve_time: Option<Duration>,
+}
+
+/// Macro found by `MacroSolver::solve_min_cp` or `solve_async`, together with the CP it uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveResult {
+    pub actions: Vec<Action>,
+    /// CP used by the macro, starting from the solved state.
+    pub cp_used: i16,
+}
+
+/// Memoized bounds of a `MacroSolver`, kept between solves of the same recipe so that they aren't recomputed.
+/// See `MacroSolver::into_cache` and `MacroSolver::with_cache`.
+pub struct SolverCache {
+    settings: Settings,
+    finish_solver: FinishSolver,
+    quality_upper_bound_solver: QualityUpperBoundSolver,
+    step_lower_bound_solver: StepLowerBoundSolver,
+}
+
+impl SolverCache {
+    /// Discard the memoized bounds that are not valid for `settings`.
+    /// Only the step lower-bounds depend on `max_quality`, the other bounds are kept when nothing but `max_quality` changes.
+    /// The quality upper-bounds are also kept when `adversarial` changes.
+    pub fn reset_for_settings(&mut self, settings: Settings) {
+        let same_recipe = Settings {
+            max_quality: settings.max_quality,
+            ..self.settings
+        } == settings;
+        let same_upper_bound_settings = Settings {
+            max_quality: settings.max_quality,
+            adversarial: settings.adversarial,
+            ..self.settings
+        } == settings;
+        if !same_recipe {
+            self.finish_solver = FinishSolver::new(settings);
+        }
+        if !same_upper_bound_settings {
+            self.quality_upper_bound_solver = QualityUpperBoundSolver::new(settings);
+        } else {
+            self.quality_upper_bound_solver
+                .set_max_quality(settings.max_quality);
+            self.quality_upper_bound_solver
+                .set_adversarial(settings.adversarial);
+        }
+        if settings != self.settings {
+            self.step_lower_bound_solver = StepLowerBoundSolver::new(settings);
+        }
+        self.settings = settings;
+    }
+
+    /// Number of memoized states, summed over all bounds.
+    pub fn num_states(&self) -> usize {
+        self.finish_solver.num_states()
+            + self.quality_upper_bound_solver.num_states()
+            + self.step_lower_bound_solver.num_states()
+    }
+}
+
+/// Line of the solve log, see `MacroSolver::with_log`.
+#[cfg(feature = "solve-log")]
+#[derive(serde::Serialize)]
+struct SolveLogRecord {
+    settings: Settings,
+    stats: SolveStats,
+    score: Option<SolveLogScore>,
 }
 
-type SolutionCallback<'a> = dyn Fn(&[Action]) + 'a;
+#[cfg(feature = "solve-log")]
+#[derive(serde::Serialize)]
+struct SolveLogScore {
+    quality: u16,
+    steps: usize,
+    duration: i16,
+}
+
+type SolutionCallback<'a> = dyn Fn(&SolutionTrace<'_>) + 'a;
 type ProgressCallback<'a> = dyn Fn(f32) + 'a;
+type CancellationCallback<'a> = dyn Fn() -> bool + 'a;
 
+/// Options of the search are collected in `SolverConfig`, so that adding an option doesn't change the signature for existing callers.
+/// The builder methods attach what isn't a plain option, i.e. a cancellation callback, a cache of memoized bounds and a solve log.
 pub struct MacroSolver<'a> {
     settings: Settings,
+    config: SolverConfig,
     finish_solver: FinishSolver,
     quality_upper_bound_solver: QualityUpperBoundSolver,
     step_lower_bound_solver: StepLowerBoundSolver,
     solution_callback: Box<SolutionCallback<'a>>,
     progress_callback: Box<ProgressCallback<'a>>,
+    is_cancelled: Option<Box<CancellationCallback<'a>>>,
+    // applied at the start of the next solve
+    cache: Option<SolverCache>,
+    stats: SolveStats,
+    #[cfg(feature = "solve-log")]
+    log: Option<Box<dyn Write + 'a>>,
 }
 
 impl<'a> MacroSolver<'a> {
     pub fn new(
         settings: Settings,
+        config: SolverConfig,
         solution_callback: Box<SolutionCallback<'a>>,
         progress_callback: Box<ProgressCallback<'a>>,
     ) -> MacroSolver<'a> {
+        let settings = Settings {
+            allowed_actions: settings.allowed_actions.minus(config.forbidden_actions),
+            ..settings
+        };
         MacroSolver {
             settings,
+            config,
             finish_solver: FinishSolver::new(settings),
             quality_upper_bound_solver: QualityUpperBoundSolver::new(settings),
             step_lower_bound_solver: StepLowerBoundSolver::new(settings),
             solution_callback,
             progress_callback,
+            is_cancelled: None,
+            cache: None,
+            stats: SolveStats::default(),
+            #[cfg(feature = "solve-log")]
+            log: None,
+        }
+    }
+
+    /// Abandon the search once `is_cancelled` returns true, e.g. because the user is no longer interested in the result.
+    /// Unlike with a timeout, a cancelled solve returns `None` instead of the best macro found so far.
+    /// Same as the timeout, it is only checked in the main search loop.
+    pub fn with_cancellation(mut self, is_cancelled: Box<CancellationCallback<'a>>) -> Self {
+        self.is_cancelled = Some(is_cancelled);
+        self
+    }
+
+    /// Reuse the bounds memoized by a previous solver, as far as they are valid for this solver's settings.
+    /// The cache is only applied when solving, so the order of the builder methods doesn't matter.
+    pub fn with_cache(mut self, cache: SolverCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    fn apply_cache(&mut self) {
+        if let Some(mut cache) = self.cache.take() {
+            cache.reset_for_settings(self.settings);
+            self.finish_solver = cache.finish_solver;
+            self.quality_upper_bound_solver = cache.quality_upper_bound_solver;
+            self.step_lower_bound_solver = cache.step_lower_bound_solver;
         }
     }
 
+    /// Keep the memoized bounds for a later solver, see `with_cache`.
+    pub fn into_cache(mut self) -> SolverCache {
+        self.apply_cache();
+        SolverCache {
+            settings: self.settings,
+            finish_solver: self.finish_solver,
+            quality_upper_bound_solver: self.quality_upper_bound_solver,
+            step_lower_bound_solver: self.step_lower_bound_solver,
+        }
+    }
+
+    /// Write a line of JSON with the settings, statistics and resulting score of every solve to `log`,
+    /// so that solves can be compared across versions. The log is best-effort, write errors are ignored.
+    #[cfg(feature = "solve-log")]
+    pub fn with_log(mut self, log: Box<dyn Write + 'a>) -> Self {
+        self.log = Some(log);
+        self
+    }
+
     /// Returns a list of Actions that maximizes Quality of the completed state.
-    /// Returns `None` if the state cannot be completed (i.e. cannot max out Progress).
+    /// Returns `None` if the state cannot be completed (i.e. cannot max out Progress) within the step budget.
     /// The solver makes an effort to produce a short solution, but it is not (yet) guaranteed to be the shortest solution.
-    pub fn solve(
-        &mut self,
-        state: SimulationState,
-        backload_progress: bool,
-        minimize_steps: bool,
-    ) -> Option<Vec<Action>> {
+    /// If the timeout elapses, the returned macro completes the synthesis but may not be optimal.
+    pub fn solve(&mut self, state: SimulationState) -> Option<Vec<Action>> {
+        self.stats = SolveStats::default();
+        self.apply_cache();
+        if self.config.quality_overflow == QualityOverflow::Prefer {
+            // all overflow counts, so the upper-bound must not be clamped to 2 times max_quality
+            self.quality_upper_bound_solver
+                .set_max_quality(u16::MAX / 2);
+        }
+        let stopwatch = Stopwatch::new();
+        let actions = self.find_solution(state);
+        self.stats.solve_time = stopwatch.elapsed();
+        self.stats.finish_states = self.finish_solver.num_states();
+        self.stats.quality_upper_bound_states = self.quality_upper_bound_solver.num_states();
+        self.stats.step_lower_bound_states = self.step_lower_bound_solver.num_states();
+        #[cfg(feature = "solve-log")]
+        if self.log.is_some() {
+            self.write_log(state, actions.as_deref());
+        }
+        actions
+    }
+
+    #[cfg(feature = "solve-log")]
+    fn write_log(&mut self, state: SimulationState, actions: Option<&[Action]>) {
+        let record = SolveLogRecord {
+            settings: self.settings,
+            stats: self.stats,
+            score: actions.map(|actions| SolveLogScore {
+                quality: self.final_state(state, actions).get_quality(),
+                steps: actions.len(),
+                duration: actions.iter().map(|action| action.time_cost()).sum(),
+            }),
+        };
+        let log = self.log.as_mut().unwrap();
+        if let Ok(line) = serde_json::to_string(&record) {
+            let _ = writeln!(log, "{line}");
+        }
+    }
+
+    /// Returns up to `k` macros that are Pareto-distinct in (Quality, steps, CP), best macro first.
+    /// Each subsequent macro is the best macro that uses less CP than all previous ones, so no macro is a reordering of another.
+    /// Macros that are dominated by a later, cheaper macro are dropped, and at most `4 * k` solves are run,
+    /// so fewer than `k` macros may be returned.
+    /// Only the first solve reports intermediate solutions and progress through the callbacks.
+    /// The timeout applies to each solve separately and `stats` only covers the last one.
+    pub fn solve_top_k(&mut self, state: SimulationState, k: usize) -> Vec<Vec<Action>> {
+        let mut solutions: Vec<(Vec<Action>, u16)> = Vec::new();
+        let mut budget_state = state;
+        let mut callbacks = None;
+        for _ in 0..k.saturating_mul(4) {
+            if solutions.len() >= k {
+                break;
+            }
+            let Some(actions) = self.solve(budget_state) else {
+                break;
+            };
+            if callbacks.is_none() {
+                // the solutions of later solves are worse than the first macro, so they must not be reported as progress
+                callbacks = Some((
+                    std::mem::replace(&mut self.solution_callback, Box::new(|_| {})),
+                    std::mem::replace(&mut self.progress_callback, Box::new(|_| {})),
+                ));
+            }
+            let final_state = self.final_state(state, &actions);
+            let quality = std::cmp::min(final_state.get_quality(), self.settings.max_quality);
+            let cp_used = state.cp - final_state.cp;
+            // the new macro uses less CP, so it dominates every macro that isn't better in Quality or steps
+            solutions.retain(|(other_actions, other_quality)| {
+                *other_quality > quality || other_actions.len() < actions.len()
+            });
+            solutions.push((actions, quality));
+            if cp_used <= 0 {
+                break;
+            }
+            budget_state.cp = cp_used - 1;
+        }
+        if let Some((solution_callback, progress_callback)) = callbacks {
+            self.solution_callback = solution_callback;
+            self.progress_callback = progress_callback;
+        }
+        solutions.into_iter().map(|(actions, _)| actions).collect()
+    }
+
+    /// Returns the macro that uses the least CP among the macros that reach the best achievable Quality, together with the CP it uses.
+    /// Useful to check whether a cheaper meal or potion still gets the same result.
+    ///
+    /// The CP objective isn't part of the search score. Instead, this runs a binary search over the available CP,
+    /// i.e. one solve for the best Quality followed by up to `ceil(log2(state.cp + 1))` solves with less CP,
+    /// about 10 solves for a typical CP budget. The memoized bounds are shared between the solves,
+    /// but the timeout applies to each solve separately and `stats` only covers the last one.
+    pub fn solve_min_cp(&mut self, state: SimulationState) -> Option<SolveResult> {
+        let mut best_actions = self.solve(state)?;
+        let quality_target = self.capped_quality(state, &best_actions);
+        // the best macro is known to be reachable with `max_budget` CP, and not with less than `min_budget` CP
+        let mut min_budget = 0;
+        let mut max_budget = state.cp;
+        while min_budget < max_budget {
+            let budget = min_budget + (max_budget - min_budget) / 2;
+            let budget_state = SimulationState {
+                cp: budget,
+                ..state
+            };
+            if self.is_cancelled() {
+                return None;
+            }
+            match self.solve(budget_state) {
+                Some(actions) if self.capped_quality(state, &actions) >= quality_target => {
+                    best_actions = actions;
+                    max_budget = budget;
+                }
+                _ => min_budget = budget + 1,
+            }
+        }
+        let cp_used = state.cp - self.final_state(state, &best_actions).cp;
+        Some(SolveResult {
+            actions: best_actions,
+            cp_used,
+        })
+    }
+
+    fn final_state(&self, state: SimulationState, actions: &[Action]) -> SimulationState {
+        actions.iter().fold(state, |state, action| {
+            state
+                .use_action(*action, Condition::Normal, &self.settings)
+                .unwrap()
+        })
+    }
+
+    fn capped_quality(&self, state: SimulationState, actions: &[Action]) -> u16 {
+        std::cmp::min(
+            self.final_state(state, actions).get_quality(),
+            self.settings.max_quality,
+        )
+    }
+
+    /// Statistics of the most recent `solve` call.
+    pub fn stats(&self) -> SolveStats {
+        self.stats
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.is_cancelled
+            .as_ref()
+            .is_some_and(|is_cancelled| is_cancelled())
+    }
+
+    fn find_solution(&mut self, state: SimulationState) -> Option<Vec<Action>> {
+        let deadline = Deadline::new(self.config.timeout);
+
         let timer = NamedTimer::new("Finish solver");
         if !self.finish_solver.can_finish(&state) {
             return None;
         }
         drop(timer);
 
-        if !minimize_steps {
+        // quick search doesn't respect the step budget,
+        // and both shortcuts stop at the first macro that maxes out Quality, regardless of overflow
+        if !self.config.minimize_steps
+            && self.config.max_steps.is_none()
+            && self.config.quality_overflow != QualityOverflow::Prefer
+        {
+            if let Some(actions) = self.solve_with_trained_eye(state, &deadline) {
+                return Some(actions);
+            }
             if let Some(actions) = quick_search(
                 state,
                 &self.settings,
@@ -79,69 +446,177 @@ impl<'a> MacroSolver<'a> {
         }
 
         let _timer = NamedTimer::new("Full search");
-        self.do_solve(state, backload_progress)
+        self.do_solve(state, self.config.backload_progress, &deadline, &[])
+    }
+
+    /// Upper-bound on the Quality of any macro that continues from `state`.
+    /// Quality actions are no longer searched once Quality is maxed out, unless overflow is preferred.
+    fn quality_upper_bound(&mut self, state: SimulationState) -> u16 {
+        if state.get_quality() >= self.settings.max_quality
+            && self.config.quality_overflow != QualityOverflow::Prefer
+        {
+            state.get_quality()
+        } else {
+            self.quality_upper_bound_solver.quality_upper_bound(state)
+        }
     }
 
-    fn do_solve(&mut self, state: SimulationState, backload_progress: bool) -> Option<Vec<Action>> {
+    /// Trained Eye maxes out Quality in a single step, so only Progress is left to be solved.
+    /// Returns `None` if Trained Eye isn't available or doesn't max out Quality.
+    fn solve_with_trained_eye(
+        &mut self,
+        state: SimulationState,
+        deadline: &Deadline,
+    ) -> Option<Vec<Action>> {
+        if !self.settings.allowed_actions.has(Action::TrainedEye) {
+            return None;
+        }
+        let state = state
+            .use_action(Action::TrainedEye, Condition::Normal, &self.settings)
+            .ok()?;
+        if state.get_quality() < self.settings.max_quality || !self.finish_solver.can_finish(&state)
+        {
+            return None;
+        }
+        let _timer = NamedTimer::new("Trained Eye search");
+        self.do_solve(state, false, deadline, &[Action::TrainedEye])
+    }
+
+    /// `prefix` contains the actions that lead up to `state` and is prepended to every solution.
+    fn do_solve(
+        &mut self,
+        state: SimulationState,
+        backload_progress: bool,
+        deadline: &Deadline,
+        prefix: &[Action],
+    ) -> Option<Vec<Action>> {
+        if self.is_cancelled() {
+            return None;
+        }
+        let initial_state = state;
         let mut search_queue = {
-            let quality_upper_bound = self.quality_upper_bound_solver.quality_upper_bound(state);
+            let quality_upper_bound = self.quality_upper_bound(state);
             let step_lower_bound = if quality_upper_bound >= self.settings.max_quality {
                 self.step_lower_bound_solver.step_lower_bound(state, true)
             } else {
                 1 // quality dominates the search score, so no need to query the step solver
             };
-            let initial_score =
-                SearchScore::new(quality_upper_bound, 0, step_lower_bound, &self.settings);
-            let quality_lower_bound = fast_lower_bound(
-                state,
+            let initial_score = SearchScore::new(
+                quality_upper_bound,
+                0,
+                step_lower_bound,
                 &self.settings,
-                &mut self.finish_solver,
-                &mut self.quality_upper_bound_solver,
+                self.config.quality_overflow,
+            );
+            let quality_lower_bound = match self.config.max_steps {
+                // the lower-bound may not be reachable within the step budget
+                Some(_) => 0,
+                None if state.get_quality() >= self.settings.max_quality => {
+                    self.settings.max_quality
+                }
+                None => fast_lower_bound(
+                    state,
+                    &self.settings,
+                    &mut self.finish_solver,
+                    &mut self.quality_upper_bound_solver,
+                ),
+            };
+            let minimum_score = SearchScore::new(
+                quality_lower_bound,
+                u8::MAX,
+                u8::MAX,
+                &self.settings,
+                self.config.quality_overflow,
             );
-            let minimum_score =
-                SearchScore::new(quality_lower_bound, u8::MAX, u8::MAX, &self.settings);
             SearchQueue::new(state, initial_score, minimum_score, self.settings)
         };
 
         let mut solution: Option<Solution> = None;
 
         let mut popped = 0;
+        let mut cancelled = false;
         while let Some((state, score, backtrack_id)) = search_queue.pop() {
             popped += 1;
+            // the best macro may have improved since the node was pushed
+            if self.is_within_tolerance(solution.as_ref(), &score) {
+                continue;
+            }
             if popped % (1 << 16) == 0 {
                 (self.progress_callback)(search_queue.progress_estimate());
             }
+            if popped % (1 << 10) == 0 {
+                if self.is_cancelled() {
+                    cancelled = true;
+                    break;
+                }
+                if deadline.is_exceeded(self.stats.search_nodes + popped) {
+                    break;
+                }
+            }
             let mut search_actions = match backload_progress && state.progress != 0 {
                 true => PROGRESS_SEARCH_ACTIONS.intersection(self.settings.allowed_actions),
                 false => FULL_SEARCH_ACTIONS.intersection(self.settings.allowed_actions),
             };
-            if state.get_quality() >= self.settings.max_quality {
+            if state.get_quality() >= self.settings.max_quality
+                && self.config.quality_overflow != QualityOverflow::Prefer
+            {
                 search_actions = search_actions.minus(QUALITY_ACTIONS);
             }
 
             let current_steps = search_queue.steps(backtrack_id);
+            if self
+                .config
+                .max_steps
+                .is_some_and(|max_steps| current_steps >= max_steps)
+            {
+                continue;
+            }
+            // a non-final child needs at least one more step to finish
+            let can_continue = self
+                .config
+                .max_steps
+                .is_none_or(|max_steps| current_steps as u16 + 2 <= max_steps as u16);
 
             for action in search_actions.actions_iter() {
                 if let Ok(state) = state.use_action(action, Condition::Normal, &self.settings) {
                     if !state.is_final(&self.settings) {
-                        if !self.finish_solver.can_finish(&state) {
+                        if !can_continue || !self.finish_solver.can_finish(&state) {
                             // skip this state if it is impossible to max out Progress
                             continue;
                         }
 
-                        search_queue.update_min_score(SearchScore::new(
-                            state.get_quality(),
-                            u8::MAX,
-                            u8::MAX,
-                            &self.settings,
-                        ));
+                        if self.config.max_steps.is_none() {
+                            // the state can be finished without losing Quality, but maybe not within the step budget
+                            search_queue.update_min_score(SearchScore::new(
+                                state.get_quality(),
+                                u8::MAX,
+                                u8::MAX,
+                                &self.settings,
+                                self.config.quality_overflow,
+                            ));
+                        }
 
-                        let quality_upper_bound =
-                            if state.get_quality() >= self.settings.max_quality {
-                                state.get_quality()
-                            } else {
-                                self.quality_upper_bound_solver.quality_upper_bound(state)
-                            };
+                        let quality_upper_bound = self.quality_upper_bound(state);
+                        // the upper-bound of a child must not exceed that of its parent,
+                        // which isn't guaranteed when the added Quality of an ancestor was clamped to the cap but the child's isn't
+                        let parent_upper_bound = match self.config.quality_overflow {
+                            // equal scores share a bucket, so the overflow of an ignored score is that of any node in the bucket
+                            QualityOverflow::Ignore
+                                if score.quality >= self.settings.max_quality =>
+                            {
+                                u16::MAX
+                            }
+                            _ => score.quality + score.quality_overflow,
+                        };
+                        debug_assert!(
+                            parent_upper_bound >= self.quality_upper_bound_solver.quality_cap()
+                                || quality_upper_bound <= parent_upper_bound,
+                            "refunded CP lets the upper-bound of a child exceed that of its parent"
+                        );
+                        let quality_upper_bound = std::cmp::min(
+                            quality_upper_bound,
+                            score.quality + score.quality_overflow,
+                        );
 
                         let step_lower_bound = if quality_upper_bound >= self.settings.max_quality {
                             let fast_mode = popped < 200_000;
@@ -154,34 +629,55 @@ impl<'a> MacroSolver<'a> {
                             current_steps + 1
                         };
 
-                        search_queue.push(
-                            state,
-                            SearchScore::new(
-                                quality_upper_bound,
-                                score.duration + action.time_cost() as u8,
-                                step_lower_bound,
-                                &self.settings,
-                            ),
-                            action,
-                            backtrack_id,
+                        let child_score = SearchScore::new(
+                            quality_upper_bound,
+                            score.duration + action.time_cost() as u8,
+                            step_lower_bound,
+                            &self.settings,
+                            self.config.quality_overflow,
                         );
+                        if self.is_within_tolerance(solution.as_ref(), &child_score) {
+                            continue;
+                        }
+                        search_queue.push(state, child_score, action, backtrack_id);
                     } else if state.progress >= self.settings.max_progress {
                         let solution_score = SearchScore::new(
                             state.get_quality(),
                             score.duration,
                             current_steps + 1,
                             &self.settings,
+                            self.config.quality_overflow,
                         );
                         search_queue.update_min_score(solution_score);
-                        if solution.is_none() || solution.as_ref().unwrap().score < solution_score {
+                        let is_better = match solution {
+                            None => true,
+                            Some(solution) if solution.score == solution_score => {
+                                // break ties lexicographically, so that equivalent macros are always resolved the same way
+                                let candidate = search_queue
+                                    .backtrack(backtrack_id)
+                                    .chain(std::iter::once(action));
+                                let incumbent = search_queue
+                                    .backtrack(solution.backtrack_id)
+                                    .chain(std::iter::once(solution.action));
+                                candidate.lt(incumbent)
+                            }
+                            Some(solution) => solution.score < solution_score,
+                        };
+                        if is_better {
                             solution = Some(Solution {
                                 score: solution_score,
-                                actions: search_queue
-                                    .backtrack(backtrack_id)
-                                    .chain(std::iter::once(action))
-                                    .collect(),
+                                backtrack_id,
+                                action,
+                            });
+                            (self.solution_callback)(&SolutionTrace {
+                                prefix,
+                                source: TraceSource::Search {
+                                    search_queue: &search_queue,
+                                    backtrack_id,
+                                    action,
+                                },
+                                quality: state.get_quality(),
                             });
-                            (self.solution_callback)(&solution.as_ref().unwrap().actions);
                             (self.progress_callback)(search_queue.progress_estimate());
                         }
                     }
@@ -189,11 +685,119 @@ impl<'a> MacroSolver<'a> {
             }
         }
 
+        self.stats.search_nodes += popped;
+        self.stats.pareto_front_entries += search_queue.pareto_front_entries();
+
+        if cancelled {
+            return None;
+        }
         if let Some(solution) = solution {
-            dbg!(&solution.actions);
-            Some(solution.actions)
+            let trace = SolutionTrace {
+                prefix,
+                source: TraceSource::Search {
+                    search_queue: &search_queue,
+                    backtrack_id: solution.backtrack_id,
+                    action: solution.action,
+                },
+                quality: solution.score.quality + solution.score.quality_overflow,
+            };
+            Some(trace.to_vec())
         } else {
-            None
+            // Either the search was stopped by the deadline, or no macro fits within max_steps.
+            // Finishing greedily only helps in the first case, the step budget is checked again below.
+            let (greedy_actions, final_state) = self.finish_greedily(initial_state)?;
+            let actions: Vec<Action> = prefix.iter().copied().chain(greedy_actions).collect();
+            if self
+                .config
+                .max_steps
+                .is_some_and(|max_steps| actions.len() > max_steps as usize)
+            {
+                return None;
+            }
+            (self.solution_callback)(&SolutionTrace {
+                prefix: &[],
+                source: TraceSource::Actions(&actions),
+                quality: final_state.get_quality(),
+            });
+            Some(actions)
         }
     }
+
+    /// Approximation mode: a node whose upper-bound exceeds the Quality of the best macro by at most `quality_tolerance`
+    /// can't improve on it by more than the tolerance, so it isn't searched.
+    /// Nodes whose upper-bound doesn't exceed it at all are left to the exact score comparison of the search queue,
+    /// which also accounts for steps and duration.
+    fn is_within_tolerance(&self, solution: Option<&Solution>, score: &SearchScore) -> bool {
+        solution.is_some_and(|solution| {
+            score.quality > solution.score.quality
+                && score.quality - solution.score.quality <= self.config.quality_tolerance
+        })
+    }
+
+    /// Completes the synthesis without regard for Quality, using the first action (in `actions_iter` order) that keeps the state finishable.
+    /// Returns the actions together with the final state.
+    fn finish_greedily(
+        &mut self,
+        mut state: SimulationState,
+    ) -> Option<(Vec<Action>, SimulationState)> {
+        let search_actions = PROGRESS_SEARCH_ACTIONS.intersection(self.settings.allowed_actions);
+        let mut actions = Vec::new();
+        while state.progress < self.settings.max_progress {
+            let (action, next_state) = search_actions.actions_iter().find_map(|action| {
+                let next_state = state
+                    .use_action(action, Condition::Normal, &self.settings)
+                    .ok()?;
+                let finishable = match next_state.is_final(&self.settings) {
+                    true => next_state.progress >= self.settings.max_progress,
+                    false => self.finish_solver.can_finish(&next_state),
+                };
+                finishable.then_some((action, next_state))
+            })?;
+            actions.push(action);
+            state = next_state;
+        }
+        Some((actions, state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_budget_stops_search() {
+        let settings = Settings {
+            max_cp: 714,
+            max_durability: 70,
+            max_progress: 5720,
+            max_quality: 12900,
+            base_progress: 239,
+            base_quality: 271,
+            job_level: 90,
+            allowed_actions: ActionMask::from_level(90)
+                .remove(Action::TrainedEye)
+                .remove(Action::HeartAndSoul)
+                .remove(Action::QuickInnovation),
+            adversarial: false,
+        };
+        let max_nodes = 10_000;
+        let mut solver = MacroSolver::new(
+            settings,
+            SolverConfig::default(),
+            Box::new(|_| {}),
+            Box::new(|_| {}),
+        );
+        let actions = solver
+            .do_solve(
+                SimulationState::new(&settings),
+                false,
+                &Deadline::from_node_budget(max_nodes),
+                &[],
+            )
+            .unwrap();
+        // the budget is checked together with cancellation, every 1024 nodes
+        assert!(solver.stats().search_nodes < max_nodes + 1024);
+        let state = SimulationState::from_macro(&settings, &actions).unwrap();
+        assert!(state.progress >= settings.max_progress);
+    }
 }
@@ -2,80 +2,125 @@ use crate::{
     actions::{PROGRESS_ACTIONS, QUALITY_ACTIONS},
     utils::{ParetoFrontBuilder, ParetoValue},
 };
-use simulator::{Action, ActionMask, Condition, Settings, SimulationState, SingleUse};
+use simulator::{
+    durability_cost, Action, ActionMask, Condition, Settings, SimulationState, SingleUse,
+};
 
 use rustc_hash::FxHashMap as HashMap;
 
 use super::state::ReducedState;
 
+// Opener actions (Muscle Memory, Reflect, Trained Eye) are kept because the bound is also queried for the initial state.
+// Past the first step, `use_action` rejects them, so they never produce a child there.
 const SEARCH_ACTIONS: ActionMask = PROGRESS_ACTIONS
     .union(QUALITY_ACTIONS)
     .add(Action::TrainedPerfection);
 
+/// CP restored by Tricks of the Trade
+const TRICKS_OF_THE_TRADE_CP: i16 = 20;
+
 pub struct QualityUpperBoundSolver {
     settings: Settings,
+    // visited in `ActionMask::actions_iter` order, which only affects how soon `solve_state` can stop early
+    search_actions: Box<[Action]>,
     base_durability_cost: i16,
     waste_not_cost: i16,
+    unlimited_durability: bool,
+    quality_cap: u16,
+    // Quality cap that the memoized Pareto fronts were clamped with
+    solved_quality_cap: u16,
     solved_states: HashMap<ReducedState, Box<[ParetoValue<u16, u16>]>>,
     pareto_front_builder: ParetoFrontBuilder<u16, u16>,
 }
 
 impl QualityUpperBoundSolver {
+    /// Infallible on purpose: `game_data::get_game_settings` rejects recipes with less than 5 durability,
+    /// and for any settings that get past it anyway the bound stays well-defined (see `test_minimum_durability`),
+    /// because `durability_cost` neither divides by zero nor returns 0. Returning a `Result` here would make `MacroSolver::new`
+    /// fallible for every caller without any settings for which it could actually fail.
     pub fn new(settings: Settings) -> Self {
-        dbg!(std::mem::size_of::<ReducedState>());
-        dbg!(std::mem::align_of::<ReducedState>());
-        let mut durability_cost = Action::MasterMend.cp_cost() / 6;
-        if settings.allowed_actions.has(Action::Manipulation) {
-            durability_cost = std::cmp::min(durability_cost, Action::Manipulation.cp_cost() / 8);
-        }
-        if settings.allowed_actions.has(Action::ImmaculateMend) {
-            durability_cost = std::cmp::min(
-                durability_cost,
-                Action::ImmaculateMend.cp_cost() / (settings.max_durability as i16 / 5 - 1),
-            );
-        }
+        let quality_cap = quality_cap(&settings);
         Self {
             settings,
-            base_durability_cost: durability_cost,
+            search_actions: SEARCH_ACTIONS
+                .intersection(settings.allowed_actions)
+                .actions_iter()
+                .collect(),
+            base_durability_cost: durability_cost(&settings),
             waste_not_cost: if settings.allowed_actions.has(Action::WasteNot2) {
                 Action::WasteNot2.cp_cost() / 8
             } else {
                 Action::WasteNot.cp_cost() / 4
             },
+            unlimited_durability: false,
+            quality_cap,
+            solved_quality_cap: quality_cap,
             solved_states: HashMap::default(),
-            pareto_front_builder: ParetoFrontBuilder::new(
-                settings.max_progress,
-                settings.max_quality.saturating_mul(2),
-            ),
+            pareto_front_builder: ParetoFrontBuilder::new(settings.max_progress, quality_cap),
         }
     }
 
+    /// Treat durability as unlimited, so that durability never has to be paid for with CP.
+    /// The resulting bounds are still upper-bounds, but much looser ones.
+    /// Repeating an action that leaves the reduced state unchanged is not searched, so Progress from such repetitions is missing from the Pareto fronts.
+    pub(crate) fn with_unlimited_durability(mut self) -> Self {
+        self.base_durability_cost = 0;
+        self.waste_not_cost = 0;
+        // the theoretical ceiling charges for durability, so only the 2 times max_quality clamp is left
+        self.unlimited_durability = true;
+        self.update_quality_cap();
+        self.solved_quality_cap = self.quality_cap;
+        self.pareto_front_builder.set_max_second(self.quality_cap);
+        self
+    }
+
+    /// Changes `settings.max_quality` while keeping as much of the memoized work as possible.
+    /// The Pareto fronts only depend on max_quality through clamping, so they are kept as long as the new Quality cap
+    /// doesn't exceed the one they were solved with. Otherwise they are discarded.
+    /// Apart from `adversarial` (see `set_adversarial`), all other Settings fields affect the fronts themselves,
+    /// so changing any of them requires a new solver.
+    pub fn set_max_quality(&mut self, max_quality: u16) {
+        self.settings.max_quality = max_quality;
+        self.update_quality_cap();
+        if self.quality_cap > self.solved_quality_cap {
+            self.solved_states.clear();
+            self.pareto_front_builder.set_max_second(self.quality_cap);
+            self.solved_quality_cap = self.quality_cap;
+        }
+    }
+
+    /// Changes `settings.adversarial` without discarding any of the memoized work.
+    /// Every reduced state is searched as if it were guarded (see `ReducedState`), so the simulator credits the
+    /// Normal-condition Quality for each action in both modes and the Pareto fronts are identical.
+    /// The flag only affects the bound through the current Quality of the queried state, which is already the
+    /// pessimistic value for adversarial states. The adversarial bounds are therefore still upper-bounds,
+    /// but never tighter than the non-adversarial ones.
+    pub fn set_adversarial(&mut self, adversarial: bool) {
+        self.settings.adversarial = adversarial;
+    }
+
+    /// Additional Quality at which the Pareto fronts are clamped (see `quality_cap`).
+    pub fn quality_cap(&self) -> u16 {
+        self.quality_cap
+    }
+
+    /// Number of memoized states.
+    pub fn num_states(&self) -> usize {
+        self.solved_states.len()
+    }
+
     /// Returns an upper-bound on the maximum Quality achievable from this state while also maxing out Progress.
     /// The returned upper-bound is clamped to 2 times settings.max_quality.
     /// There is no guarantee on the tightness of the upper-bound.
-    pub fn quality_upper_bound(&mut self, mut state: SimulationState) -> u16 {
+    pub fn quality_upper_bound(&mut self, state: SimulationState) -> u16 {
         let current_quality = state.get_quality();
         let missing_progress = self.settings.max_progress.saturating_sub(state.progress);
-
-        // refund effects and durability
-        state.cp += state.effects.manipulation() as i16 * (Action::Manipulation.cp_cost() / 8);
-        state.cp += state.effects.waste_not() as i16 * self.waste_not_cost;
-        state.cp += state.durability as i16 / 5 * self.base_durability_cost;
-        if state.effects.trained_perfection() != SingleUse::Unavailable
-            && self.settings.allowed_actions.has(Action::TrainedPerfection)
-        {
-            state.effects.set_trained_perfection(SingleUse::Unavailable);
-            state.cp += 4 * self.base_durability_cost;
+        if missing_progress == 0 {
+            // the synthesis is already complete, so no actions (and no Quality) can follow
+            return std::cmp::min(self.settings.max_quality.saturating_mul(2), current_quality);
         }
-        state.durability = i8::MAX;
-
-        let reduced_state =
-            ReducedState::from_state(state, self.base_durability_cost, self.waste_not_cost);
 
-        if !self.solved_states.contains_key(&reduced_state) {
-            self.solve_state(reduced_state);
-            self.pareto_front_builder.clear();
-        }
+        let reduced_state = self.solve_reduced_state(state);
         let pareto_front = self.solved_states.get(&reduced_state).unwrap();
 
         match pareto_front.last() {
@@ -98,17 +143,79 @@ impl QualityUpperBoundSolver {
         )
     }
 
+    /// Pareto front of the additional (Progress, Quality) that is reachable from this state, sorted by increasing Progress.
+    /// Progress and Quality are clamped to settings.max_progress and the Quality cap (see `quality_cap`).
+    /// For the initial state, this is the tradeoff curve between Progress and Quality for the given CP and durability.
+    pub fn pareto_front(&mut self, state: SimulationState) -> Box<[ParetoValue<u16, u16>]> {
+        let reduced_state = self.solve_reduced_state(state);
+        let max_quality = self.quality_cap;
+        let pareto_front = self.solved_states.get(&reduced_state).unwrap();
+        if self.solved_quality_cap == max_quality {
+            return pareto_front.clone();
+        }
+        // the front was solved for a higher Quality cap, so its values need to be clamped again
+        let mut clamped_front: Vec<ParetoValue<u16, u16>> = Vec::new();
+        for value in pareto_front.iter() {
+            let value = ParetoValue::new(value.first, std::cmp::min(value.second, max_quality));
+            // clamping can make values with less Progress redundant
+            while clamped_front
+                .last()
+                .is_some_and(|last| last.second <= value.second)
+            {
+                clamped_front.pop();
+            }
+            clamped_front.push(value);
+        }
+        clamped_front.into_boxed_slice()
+    }
+
+    /// Recompute the Quality cap (see `quality_cap`) for the current settings.
+    fn update_quality_cap(&mut self) {
+        self.quality_cap = match self.unlimited_durability {
+            true => self.settings.max_quality.saturating_mul(2),
+            false => quality_cap(&self.settings),
+        };
+    }
+
+    /// Reduce the state and make sure its Pareto front is solved
+    fn solve_reduced_state(&mut self, mut state: SimulationState) -> ReducedState {
+        // refund effects and durability
+        // Waste Not is turned into CP, which lets Prudent actions be combined with Waste Not in the relaxed state.
+        // That is illegal in-game (see SimulationState::use_action), but it only loosens the upper-bound.
+        state.cp += state.effects.manipulation() as i16 * (Action::Manipulation.cp_cost() / 8);
+        state.cp += state.effects.waste_not() as i16 * self.waste_not_cost;
+        state.cp += state.durability as i16 / 5 * self.base_durability_cost;
+        if state.effects.trained_perfection() != SingleUse::Unavailable
+            && self.settings.allowed_actions.has(Action::TrainedPerfection)
+        {
+            state.effects.set_trained_perfection(SingleUse::Unavailable);
+            state.cp += 4 * self.base_durability_cost;
+        }
+        // Tricks of the Trade can only be used with Heart and Soul under Normal condition, so it is refunded instead of searched
+        // Refunding it while keeping Heart and Soul, and without capping CP at max_cp, only loosens the upper-bound.
+        if state.effects.heart_and_soul() != SingleUse::Unavailable
+            && self.settings.allowed_actions.has(Action::HeartAndSoul)
+            && self.settings.allowed_actions.has(Action::TricksOfTheTrade)
+        {
+            state.cp += TRICKS_OF_THE_TRADE_CP;
+        }
+        state.durability = i8::MAX;
+        let reduced_state =
+            ReducedState::from_state(state, self.base_durability_cost, self.waste_not_cost);
+        if !self.solved_states.contains_key(&reduced_state) {
+            self.solve_state(reduced_state);
+            self.pareto_front_builder.clear();
+        }
+        reduced_state
+    }
+
     fn solve_state(&mut self, state: ReducedState) {
         self.pareto_front_builder.push_empty();
-        for action in SEARCH_ACTIONS
-            .intersection(self.settings.allowed_actions)
-            .actions_iter()
-        {
-            self.build_child_front(state, action);
+        for index in 0..self.search_actions.len() {
+            self.build_child_front(state, self.search_actions[index]);
             if self.pareto_front_builder.is_max() {
                 // stop early if both Progress and Quality are maxed out
-                // this optimization would work even better with better action ordering
-                // (i.e. if better actions are visited first)
+                // (actions are visited roughly best-first, so this usually happens early)
                 break;
             }
         }
@@ -117,6 +224,8 @@ impl QualityUpperBoundSolver {
     }
 
     fn build_child_front(&mut self, state: ReducedState, action: Action) {
+        // The child is computed by the simulator itself, so Great Strides, Innovation and Inner Quiet
+        // stack exactly as in `Action::quality_increase` and can't be mis-estimated by the bound.
         if let Ok(new_state) =
             SimulationState::from(state).use_action(action, Condition::Normal, &self.settings)
         {
@@ -124,7 +233,8 @@ impl QualityUpperBoundSolver {
             let action_quality = new_state.get_quality();
             let new_state =
                 ReducedState::from_state(new_state, self.base_durability_cost, self.waste_not_cost);
-            if new_state.cp > 0 {
+            // with unlimited durability, actions that cost no CP can leave the state unchanged, which would recurse forever
+            if new_state.cp > 0 && new_state != state {
                 match self.solved_states.get(&new_state) {
                     Some(pareto_front) => self.pareto_front_builder.push(pareto_front),
                     None => self.solve_state(new_state),
@@ -135,9 +245,13 @@ impl QualityUpperBoundSolver {
                 });
                 self.pareto_front_builder.merge();
             }
-            if new_state.cp + self.base_durability_cost >= 0 && action_progress != 0 {
-                // "durability" must not go lower than -5
-                // last action must be a progress increase
+            // Durability is paid for with CP (see ReducedState::from_state), so running out of durability shows up as negative CP.
+            // The last action may overrun durability by at most 5 (i.e. one base_durability_cost worth of CP).
+            let within_durability_overrun = new_state.cp + self.base_durability_cost >= 0;
+            // The last action must be a Progress increase. The synthesis fails as soon as durability runs out,
+            // so a Quality-only action that overruns durability can never be the last action of a successful craft.
+            let is_progress_action = action_progress != 0;
+            if within_durability_overrun && is_progress_action {
                 self.pareto_front_builder
                     .push(&[ParetoValue::new(action_progress, action_quality)]);
                 self.pareto_front_builder.merge();
@@ -146,8 +260,19 @@ impl QualityUpperBoundSolver {
     }
 }
 
+/// The Pareto fronts, i.e. the additional Quality that is reachable from a state, are clamped to this value.
+/// Quality beyond 2 times max_quality isn't worth telling apart, and no sequence of actions can add more than the theoretical ceiling.
+/// The ceiling doesn't cover the initial Quality from HQ materials, so it must not be applied to the upper-bound itself.
+fn quality_cap(settings: &Settings) -> u16 {
+    std::cmp::min(
+        settings.max_quality.saturating_mul(2),
+        simulator::max_theoretical_quality(settings),
+    )
+}
+
 #[cfg(test)]
 mod tests {
+    use fuzz_rng::{fuzz_rng, FuzzRng};
     use rand::Rng;
     use simulator::{Combo, Effects, SimulationState};
 
@@ -155,9 +280,9 @@ mod tests {
 
     fn solve(settings: Settings, actions: &[Action]) -> u16 {
         let state = SimulationState::from_macro(&settings, actions).unwrap();
-        let result =
-            QualityUpperBoundSolver::new(settings).quality_upper_bound(state.try_into().unwrap());
+        let result = QualityUpperBoundSolver::new(settings).quality_upper_bound(state);
         dbg!(result);
+        assert!(result <= simulator::max_theoretical_quality(&settings));
         result
     }
 
@@ -600,42 +725,406 @@ mod tests {
         assert_eq!(result, 24260);
     }
 
-    fn random_effects(adversarial: bool) -> Effects {
+    #[test]
+    fn test_quality_above_ceiling() {
+        // the ceiling only bounds the Quality that actions can add, not the Quality the state already has
+        let settings = Settings {
+            max_cp: 200,
+            max_durability: 40,
+            max_progress: 1600,
+            max_quality: 20000,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: 100,
+            allowed_actions: ActionMask::from_level(100)
+                .remove(Action::HeartAndSoul)
+                .remove(Action::QuickInnovation),
+            adversarial: false,
+        };
+        let initial_state = SimulationState::new(&settings);
+        let ceiling = simulator::max_theoretical_quality(&settings);
+        let state = SimulationState {
+            unreliable_quality: [ceiling; 2],
+            ..initial_state
+        };
+        let mut solver = QualityUpperBoundSolver::new(settings);
+        let initial_bound = solver.quality_upper_bound(initial_state);
+        assert_eq!(solver.quality_upper_bound(state), ceiling + initial_bound);
+    }
+
+    #[test]
+    fn test_quality_action_durability_overrun() {
+        // Delicate Synthesis finishes the craft. There is enough CP left to pay for Byregot's Blessing
+        // with a durability overrun, but a Quality action that runs out of durability can't end a successful craft.
+        let settings = Settings {
+            max_cp: 44,
+            max_durability: 20,
+            max_progress: 150,
+            max_quality: 1000,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: 100,
+            allowed_actions: ActionMask::from_actions(&[
+                Action::DelicateSynthesis,
+                Action::ByregotsBlessing,
+            ]),
+            adversarial: false,
+        };
+        let result = solve(settings, &[]);
+        assert_eq!(result, 100);
+    }
+
+    #[test]
+    fn test_completed_state() {
+        let settings = Settings {
+            max_cp: 100,
+            max_durability: 40,
+            max_progress: 100,
+            max_quality: 1000,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: 90,
+            allowed_actions: ActionMask::from_actions(&[
+                Action::BasicTouch,
+                Action::CarefulSynthesis,
+            ]),
+            adversarial: false,
+        };
+        // CP and durability are left over, but the craft is finished
+        let result = solve(settings, &[Action::BasicTouch, Action::CarefulSynthesis]);
+        assert_eq!(result, 100);
+        // no CP and durability are left over, so no Progress action could follow in the relaxed state either
+        let settings = Settings {
+            max_cp: 25,
+            max_durability: 20,
+            ..settings
+        };
+        let result = solve(settings, &[Action::BasicTouch, Action::CarefulSynthesis]);
+        assert_eq!(result, 100);
+    }
+
+    #[test]
+    fn test_pareto_front() {
+        let settings = Settings {
+            max_cp: 300,
+            max_durability: 70,
+            max_progress: 2400,
+            max_quality: 20000,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: 90,
+            allowed_actions: ActionMask::from_level(90)
+                .remove(Action::TrainedEye)
+                .remove(Action::HeartAndSoul)
+                .remove(Action::QuickInnovation),
+            adversarial: false,
+        };
+        let state = SimulationState::new(&settings);
+        let mut solver = QualityUpperBoundSolver::new(settings);
+        let pareto_front = solver.pareto_front(state);
+        assert_eq!(pareto_front.len(), 30);
+        // more Progress always comes at the cost of Quality
+        for window in pareto_front.windows(2) {
+            assert!(window[0].first < window[1].first);
+            assert!(window[0].second > window[1].second);
+        }
+        // the upper-bound is the Quality of the first value that maxes out Progress
+        let last = pareto_front.last().unwrap();
+        assert_eq!(last.first, settings.max_progress);
+        assert_eq!(solver.quality_upper_bound(state), last.second);
+    }
+
+    #[test]
+    fn test_search_order_independent() {
+        let settings = Settings {
+            max_cp: 300,
+            max_durability: 70,
+            max_progress: 2400,
+            max_quality: 20000,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: 90,
+            allowed_actions: ActionMask::from_level(90)
+                .remove(Action::TrainedEye)
+                .remove(Action::HeartAndSoul)
+                .remove(Action::QuickInnovation),
+            adversarial: false,
+        };
+        let mut solver = QualityUpperBoundSolver::new(settings);
+        let mut reversed_solver = QualityUpperBoundSolver::new(settings);
+        reversed_solver.search_actions.reverse();
+        assert_ne!(solver.search_actions, reversed_solver.search_actions);
+        for actions in [
+            &[][..],
+            &[Action::MuscleMemory],
+            &[Action::Reflect, Action::BasicTouch],
+            &[Action::BasicTouch, Action::ComboStandardTouch],
+            &[Action::Manipulation, Action::Veneration, Action::Innovation],
+        ] {
+            let state = SimulationState::from_macro(&settings, actions).unwrap();
+            assert_eq!(
+                solver.pareto_front(state),
+                reversed_solver.pareto_front(state)
+            );
+        }
+    }
+
+    #[test]
+    fn test_set_max_quality() {
+        let settings = Settings {
+            max_cp: 300,
+            max_durability: 70,
+            max_progress: 2400,
+            max_quality: 20000,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: 90,
+            allowed_actions: ActionMask::from_level(90)
+                .remove(Action::TrainedEye)
+                .remove(Action::HeartAndSoul)
+                .remove(Action::QuickInnovation),
+            adversarial: false,
+        };
+        let state = SimulationState::new(&settings);
+        let mut solver = QualityUpperBoundSolver::new(settings);
+        solver.quality_upper_bound(state);
+        let solved_states = solver.solved_states.len();
+        for max_quality in [1000, 3000, 20000] {
+            let settings = Settings {
+                max_quality,
+                ..settings
+            };
+            let mut fresh_solver = QualityUpperBoundSolver::new(settings);
+            // lowering max_quality reuses the memoized fronts
+            solver.set_max_quality(max_quality);
+            assert_eq!(
+                solver.quality_upper_bound(state),
+                fresh_solver.quality_upper_bound(state)
+            );
+            assert!(solver
+                .pareto_front(state)
+                .iter()
+                .all(|value| value.second <= 2 * max_quality));
+            assert_eq!(solver.solved_states.len(), solved_states);
+        }
+        // the theoretical ceiling already caps the fronts, so raising max_quality further keeps them
+        assert!(quality_cap(&settings) < 2 * settings.max_quality);
+        solver.set_max_quality(40000);
+        assert_eq!(solver.solved_states.len(), solved_states);
+        // raising the Quality cap above the solved value discards them
+        let mut solver = QualityUpperBoundSolver::new(Settings {
+            max_quality: 1000,
+            ..settings
+        });
+        solver.quality_upper_bound(state);
+        solver.set_max_quality(40000);
+        assert!(solver.solved_states.is_empty());
+        let settings = Settings {
+            max_quality: 40000,
+            ..settings
+        };
+        assert_eq!(
+            solver.quality_upper_bound(state),
+            QualityUpperBoundSolver::new(settings).quality_upper_bound(state)
+        );
+    }
+
+    #[test]
+    fn test_toggle_adversarial() {
+        let settings = Settings {
+            max_cp: 400,
+            max_durability: 70,
+            max_progress: 2400,
+            max_quality: 20000,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: 90,
+            allowed_actions: ActionMask::from_level(90)
+                .remove(Action::TrainedEye)
+                .remove(Action::HeartAndSoul)
+                .remove(Action::QuickInnovation),
+            adversarial: false,
+        };
+        let adversarial_settings = Settings {
+            adversarial: true,
+            ..settings
+        };
+        let actions = [
+            Action::MuscleMemory,
+            Action::Veneration,
+            Action::Groundwork,
+            Action::BasicTouch,
+            Action::StandardTouch,
+        ];
+        let state = SimulationState::from_macro(&settings, &actions).unwrap();
+        let adversarial_state =
+            SimulationState::from_macro(&adversarial_settings, &actions).unwrap();
+        assert!(adversarial_state.get_quality() < state.get_quality());
+
+        let mut solver = QualityUpperBoundSolver::new(settings);
+        let bound = solver.quality_upper_bound(state);
+        let num_states = solver.num_states();
+        solver.set_adversarial(true);
+        let adversarial_bound = solver.quality_upper_bound(adversarial_state);
+        // nothing had to be recomputed for the adversarial state
+        assert_eq!(solver.num_states(), num_states);
+        assert_eq!(
+            adversarial_bound,
+            QualityUpperBoundSolver::new(adversarial_settings)
+                .quality_upper_bound(adversarial_state)
+        );
+        assert!(adversarial_bound <= bound);
+        assert!(adversarial_bound >= adversarial_state.get_quality());
+        // toggling back gives the original bound
+        solver.set_adversarial(false);
+        assert_eq!(solver.quality_upper_bound(state), bound);
+    }
+
+    #[test]
+    fn test_adversarial_guard() {
+        let settings = Settings {
+            max_cp: 200,
+            max_durability: 70,
+            max_progress: 2400,
+            max_quality: 20000,
+            base_progress: 100,
+            base_quality: 100,
+            job_level: 90,
+            allowed_actions: ActionMask::from_level(90)
+                .remove(Action::TrainedEye)
+                .remove(Action::HeartAndSoul)
+                .remove(Action::QuickInnovation),
+            adversarial: true,
+        };
+        let state = SimulationState::from_macro(&settings, &[Action::BasicSynthesis]).unwrap();
+        let guarded = SimulationState {
+            effects: state.effects.with_guard(1),
+            ..state
+        };
+        let unguarded = SimulationState {
+            effects: state.effects.with_guard(0),
+            ..state
+        };
+        // without guard, the simulator only credits the Quality of a Poor condition
+        let guarded_child = guarded
+            .use_action(Action::BasicTouch, Condition::Normal, &settings)
+            .unwrap();
+        let unguarded_child = unguarded
+            .use_action(Action::BasicTouch, Condition::Normal, &settings)
+            .unwrap();
+        assert_eq!(guarded_child.get_quality(), 100);
+        assert_eq!(unguarded_child.get_quality(), 50);
+        // the bound treats every state as guarded, so it is the same with and without guard
+        // and never below what either state can actually reach
+        let mut solver = QualityUpperBoundSolver::new(settings);
+        let guarded_bound = solver.quality_upper_bound(guarded);
+        let unguarded_bound = solver.quality_upper_bound(unguarded);
+        assert_eq!(guarded_bound, unguarded_bound);
+        assert!(guarded_bound >= solver.quality_upper_bound(guarded_child));
+        assert!(unguarded_bound >= solver.quality_upper_bound(unguarded_child));
+    }
+
+    #[test]
+    fn test_minimum_durability() {
+        // get_game_settings rejects durability below 5, but the solver must not panic on it either
+        for max_durability in [0, 5, 7, 10] {
+            let settings = Settings {
+                max_cp: 100,
+                max_durability,
+                max_progress: 1000,
+                max_quality: 1000,
+                base_progress: 100,
+                base_quality: 100,
+                job_level: 100,
+                allowed_actions: ActionMask::from_level(100),
+                adversarial: false,
+            };
+            assert!(durability_cost(&settings) > 0);
+            let state = SimulationState::new(&settings);
+            QualityUpperBoundSolver::new(settings).quality_upper_bound(state);
+        }
+    }
+
+    #[test]
+    fn test_durability_cost() {
+        for (max_durability, with_manipulation, without_manipulation) in [
+            (35, 12, 14),
+            (40, 12, 14),
+            (60, 10, 10),
+            (70, 8, 8),
+            (80, 7, 7),
+        ] {
+            let mut settings = Settings {
+                max_cp: 500,
+                max_durability,
+                max_progress: 2000,
+                max_quality: 2000,
+                base_progress: 100,
+                base_quality: 100,
+                job_level: 100,
+                allowed_actions: ActionMask::from_level(100),
+                adversarial: false,
+            };
+            let cost = durability_cost(&settings);
+            assert_eq!(cost, with_manipulation);
+            // no action can restore 5 durability for less than the estimated cost
+            let max_restored = max_durability as i16 / 5 - 1;
+            assert!(cost * std::cmp::min(6, max_restored) <= Action::MasterMend.cp_cost());
+            assert!(cost * 8 <= Action::Manipulation.cp_cost());
+            assert!(cost * max_restored <= Action::ImmaculateMend.cp_cost());
+
+            settings.allowed_actions = settings.allowed_actions.remove(Action::Manipulation);
+            assert_eq!(durability_cost(&settings), without_manipulation);
+        }
+    }
+
+    fn random_effects(adversarial: bool, rng: &mut FuzzRng) -> Effects {
         Effects::default()
-            .with_inner_quiet(rand::thread_rng().gen_range(0..=10))
-            .with_great_strides(rand::thread_rng().gen_range(0..=3))
-            .with_innovation(rand::thread_rng().gen_range(0..=4))
-            .with_veneration(rand::thread_rng().gen_range(0..=4))
-            .with_waste_not(rand::thread_rng().gen_range(0..=8))
-            .with_manipulation(rand::thread_rng().gen_range(0..=8))
-            .with_quick_innovation_used(rand::random())
-            .with_guard(if adversarial {
-                rand::thread_rng().gen_range(0..=1)
-            } else {
-                0
-            })
+            .with_inner_quiet(rng.gen_range(0..=10))
+            .with_great_strides(rng.gen_range(0..=3))
+            .with_innovation(rng.gen_range(0..=4))
+            .with_veneration(rng.gen_range(0..=4))
+            .with_waste_not(rng.gen_range(0..=8))
+            .with_manipulation(rng.gen_range(0..=8))
+            .with_quick_innovation_used(rng.gen())
+            .with_guard(if adversarial { rng.gen_range(0..=1) } else { 0 })
     }
 
-    fn random_state(settings: &Settings) -> SimulationState {
+    fn random_state(settings: &Settings, rng: &mut FuzzRng) -> SimulationState {
+        // Combo::SynthesisBegin is left out, so the opener actions are never used mid-craft
         const COMBOS: [Combo; 3] = [Combo::None, Combo::BasicTouch, Combo::StandardTouch];
         SimulationState {
-            cp: rand::thread_rng().gen_range(0..=settings.max_cp),
-            durability: rand::thread_rng().gen_range(1..=(settings.max_durability / 5)) * 5,
-            progress: rand::thread_rng().gen_range(0..settings.max_progress),
+            cp: rng.gen_range(0..=settings.max_cp),
+            durability: rng.gen_range(1..=(settings.max_durability / 5)) * 5,
+            progress: rng.gen_range(0..settings.max_progress),
             unreliable_quality: [settings.max_quality; 2],
-            effects: random_effects(settings.adversarial),
-            combo: COMBOS[rand::thread_rng().gen_range(0..3)],
+            effects: random_effects(settings.adversarial, rng),
+            combo: COMBOS[rng.gen_range(0..3)],
+            careful_observation_uses: 0,
+        }
+    }
+
+    /// State with maxed-out Waste Not and Manipulation and little durability left,
+    /// i.e. a state where most of the CP of the upper-bound comes from refunds
+    fn random_refund_state(settings: &Settings, rng: &mut FuzzRng) -> SimulationState {
+        let state = random_state(settings, rng);
+        SimulationState {
+            durability: rng.gen_range(1..=2) * 5,
+            effects: state.effects.with_waste_not(8).with_manipulation(8),
+            ..state
         }
-        .try_into()
-        .unwrap()
     }
 
     /// Test that the upper-bound solver is monotonic,
     /// i.e. the quality UB of a state is never less than the quality UB of any of its children.
-    fn monotonic_fuzz_check(settings: Settings) {
+    fn monotonic_fuzz_check(
+        settings: Settings,
+        random_state: fn(&Settings, &mut FuzzRng) -> SimulationState,
+    ) {
+        let mut rng = fuzz_rng();
         let mut solver = QualityUpperBoundSolver::new(settings);
         for _ in 0..10000 {
-            let state = random_state(&settings);
+            let state = random_state(&settings, &mut rng);
             let state_upper_bound = solver.quality_upper_bound(state);
             for action in settings.allowed_actions.actions_iter() {
                 let child_upper_bound = match state.use_action(action, Condition::Normal, &settings)
@@ -652,6 +1141,23 @@ mod tests {
                     panic!("Parent's upper bound is less than child's upper bound");
                 }
             }
+            // Great Strides followed by Byregot's Blessing is the biggest single quality increase
+            let finisher = state
+                .use_action(Action::GreatStrides, Condition::Normal, &settings)
+                .and_then(|state| {
+                    state.use_action(Action::ByregotsBlessing, Condition::Normal, &settings)
+                });
+            if let Ok(child) = finisher {
+                let child_upper_bound = match child.is_final(&settings) {
+                    false => solver.quality_upper_bound(child),
+                    true if child.progress >= settings.max_progress => child.get_quality(),
+                    true => 0,
+                };
+                if state_upper_bound < child_upper_bound {
+                    dbg!(state, state_upper_bound, child_upper_bound);
+                    panic!("Upper bound underestimates Great Strides + Byregot's Blessing");
+                }
+            }
         }
     }
 
@@ -668,7 +1174,7 @@ mod tests {
             allowed_actions: ActionMask::all(),
             adversarial: false,
         };
-        monotonic_fuzz_check(settings);
+        monotonic_fuzz_check(settings, random_state);
     }
 
     #[test]
@@ -684,6 +1190,24 @@ mod tests {
             allowed_actions: ActionMask::all(),
             adversarial: true,
         };
-        monotonic_fuzz_check(settings);
+        monotonic_fuzz_check(settings, random_state);
+    }
+
+    #[test]
+    fn test_monotonic_refunds() {
+        for adversarial in [false, true] {
+            let settings = Settings {
+                max_cp: 360,
+                max_durability: 70,
+                max_progress: 1000,
+                max_quality: 20000,
+                base_progress: 100,
+                base_quality: 100,
+                job_level: 100,
+                allowed_actions: ActionMask::all(),
+                adversarial,
+            };
+            monotonic_fuzz_check(settings, random_refund_state);
+        }
     }
 }
@@ -63,8 +63,7 @@ impl std::convert::From<ReducedState> for SimulationState {
                 .with_quick_innovation_used(state.effects.quick_innovation_used)
                 .with_guard(1),
             combo: state.combo,
+            careful_observation_uses: 0,
         }
-        .try_into()
-        .unwrap()
     }
 }
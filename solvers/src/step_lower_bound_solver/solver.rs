@@ -20,6 +20,11 @@ impl StepLowerBoundSolver {
         }
     }
 
+    /// Number of memoized states, summed over the fast and the slow mode.
+    pub fn num_states(&self) -> usize {
+        self.fast_solver.solved_states.len() + self.slow_solver.solved_states.len()
+    }
+
     /// Returns a lower-bound on the additional steps required to max out both Progress and Quality from this state.
     pub fn step_lower_bound(&mut self, state: SimulationState, fast_mode: bool) -> u8 {
         let mut lo = 0;
@@ -55,8 +60,6 @@ struct StepLowerBoundSolverImpl<S: ReducedState> {
 
 impl<S: ReducedState> StepLowerBoundSolverImpl<S> {
     pub fn new(settings: Settings) -> Self {
-        dbg!(std::mem::size_of::<S>());
-        dbg!(std::mem::align_of::<S>());
         Self {
             settings: Settings {
                 allowed_actions: S::optimize_action_mask(settings.allowed_actions),
@@ -145,6 +148,7 @@ impl<S: ReducedState> StepLowerBoundSolverImpl<S> {
 
 #[cfg(test)]
 mod tests {
+    use fuzz_rng::{fuzz_rng, FuzzRng};
     use rand::Rng;
     use simulator::{Action, ActionMask, Combo, Effects, SimulationState};
 
@@ -596,43 +600,39 @@ mod tests {
         assert_eq!(result, 5);
     }
 
-    fn random_effects(adversarial: bool) -> Effects {
+    fn random_effects(adversarial: bool, rng: &mut FuzzRng) -> Effects {
         Effects::default()
-            .with_inner_quiet(rand::thread_rng().gen_range(0..=10))
-            .with_great_strides(rand::thread_rng().gen_range(0..=3))
-            .with_innovation(rand::thread_rng().gen_range(0..=4))
-            .with_veneration(rand::thread_rng().gen_range(0..=4))
-            .with_waste_not(rand::thread_rng().gen_range(0..=8))
-            .with_manipulation(rand::thread_rng().gen_range(0..=8))
-            .with_quick_innovation_used(rand::random())
-            .with_guard(if adversarial {
-                rand::thread_rng().gen_range(0..=1)
-            } else {
-                0
-            })
+            .with_inner_quiet(rng.gen_range(0..=10))
+            .with_great_strides(rng.gen_range(0..=3))
+            .with_innovation(rng.gen_range(0..=4))
+            .with_veneration(rng.gen_range(0..=4))
+            .with_waste_not(rng.gen_range(0..=8))
+            .with_manipulation(rng.gen_range(0..=8))
+            .with_quick_innovation_used(rng.gen())
+            .with_guard(if adversarial { rng.gen_range(0..=1) } else { 0 })
     }
 
-    fn random_state(settings: &Settings) -> SimulationState {
+    fn random_state(settings: &Settings, rng: &mut FuzzRng) -> SimulationState {
         const COMBOS: [Combo; 3] = [Combo::None, Combo::BasicTouch, Combo::StandardTouch];
         SimulationState {
-            cp: rand::thread_rng().gen_range(0..=settings.max_cp),
-            durability: rand::thread_rng().gen_range(1..=(settings.max_durability / 5)) * 5,
-            progress: rand::thread_rng().gen_range(0..settings.max_progress),
+            cp: rng.gen_range(0..=settings.max_cp),
+            durability: rng.gen_range(1..=(settings.max_durability / 5)) * 5,
+            progress: rng.gen_range(0..settings.max_progress),
             unreliable_quality: [settings.max_quality; 2],
-            effects: random_effects(settings.adversarial),
-            combo: COMBOS[rand::thread_rng().gen_range(0..3)],
+            effects: random_effects(settings.adversarial, rng),
+            combo: COMBOS[rng.gen_range(0..3)],
+            careful_observation_uses: 0,
         }
-        .try_into()
-        .unwrap()
     }
 
     /// Test that the upper-bound solver is monotonic,
     /// i.e. the quality UB of a state is never less than the quality UB of any of its children.
     fn monotonic_fuzz_check(settings: Settings) {
+        let mut rng = fuzz_rng();
         let mut solver = StepLowerBoundSolver::new(settings);
         for _ in 0..10000 {
-            let fast_mode: bool = rand::random();
-            let state = random_state(&settings);
+            let fast_mode: bool = rng.gen();
+            let state = random_state(&settings, &mut rng);
             let state_lower_bound = solver.step_lower_bound(state, fast_mode);
             for action in settings.allowed_actions.actions_iter() {
                 let child_lower_bound = match state.use_action(action, Condition::Normal, &settings)
@@ -655,7 +655,7 @@ mod tests {
             }
         }
         for _ in 0..10000 {
-            let state = random_state(&settings);
+            let state = random_state(&settings, &mut rng);
             let fast_mode_lower_bound = solver.step_lower_bound(state, true);
             let slow_mode_lower_bound = solver.step_lower_bound(state, false);
             if fast_mode_lower_bound > slow_mode_lower_bound {
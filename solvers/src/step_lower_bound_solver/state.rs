@@ -111,6 +111,7 @@ impl ReducedState for ReducedStateWithDurability {
                 .with_quick_innovation_used(self.quick_innovation_used)
                 .with_guard(1),
             combo: self.combo,
+            careful_observation_uses: 0,
         }
     }
 }
@@ -176,6 +177,7 @@ impl ReducedState for ReducedStateWithoutDurability {
                 .with_quick_innovation_used(self.quick_innovation_used)
                 .with_guard(1),
             combo: self.combo,
+            careful_observation_uses: 0,
         }
     }
 }
@@ -1,32 +1,148 @@
 mod pareto_front_builder;
 pub use pareto_front_builder::{ParetoFrontBuilder, ParetoValue};
 
-pub struct NamedTimer {
-    name: &'static str,
+/// Measures elapsed time without printing anything, see `NamedTimer` for a timer that reports itself.
+pub struct Stopwatch {
     #[cfg(not(target_arch = "wasm32"))]
     timer: std::time::Instant,
+    /// Start time in milliseconds, `None` if the Performance API is not available
+    #[cfg(target_arch = "wasm32")]
+    start_ms: Option<f64>,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            timer: std::time::Instant::now(),
+            #[cfg(target_arch = "wasm32")]
+            start_ms: performance_now(),
+        }
+    }
+
+    /// Time since the stopwatch was started.
+    /// Returns `None` on WASM if the Performance API is not available.
+    pub fn elapsed(&self) -> Option<std::time::Duration> {
+        #[cfg(target_arch = "wasm32")]
+        return self
+            .start_ms
+            .zip(performance_now())
+            .map(|(start_ms, now_ms)| {
+                std::time::Duration::from_secs_f64((now_ms - start_ms).max(0.0) / 1000.0)
+            });
+        #[cfg(not(target_arch = "wasm32"))]
+        return Some(self.timer.elapsed());
+    }
+}
+
+pub struct NamedTimer {
+    name: &'static str,
+    stopwatch: Stopwatch,
 }
 
 impl NamedTimer {
     pub fn new(name: &'static str) -> Self {
         Self {
             name,
-            #[cfg(not(target_arch = "wasm32"))]
-            timer: std::time::Instant::now(),
+            stopwatch: Stopwatch::new(),
         }
     }
 }
 
 impl Drop for NamedTimer {
     fn drop(&mut self) {
-        #[cfg(target_arch = "wasm32")]
-        eprintln!("{}: (timer not available on WASM)", self.name);
-        #[cfg(not(target_arch = "wasm32"))]
-        eprintln!(
-            "{}: {} seconds",
-            self.name,
-            self.timer.elapsed().as_secs_f32()
-        );
+        match self.stopwatch.elapsed() {
+            Some(elapsed) => eprintln!("{}: {} seconds", self.name, elapsed.as_secs_f32()),
+            None => eprintln!("{}: (timer not available on WASM)", self.name),
+        }
+    }
+}
+
+/// `performance.now()` of the window or web worker the solver runs in.
+/// `std::time::Instant` is not available on WASM.
+#[cfg(target_arch = "wasm32")]
+fn performance_now() -> Option<f64> {
+    use wasm_bindgen::JsCast;
+    let global = js_sys::global();
+    let performance = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+        window.performance()
+    } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        worker.performance()
+    } else {
+        None
+    };
+    performance.map(|performance| performance.now())
+}
+
+/// Time budget of a search.
+/// On WASM, the deadline is measured with the Performance API.
+/// If that API is not available, the timeout is converted into a budget of search nodes instead.
+pub struct Deadline {
+    #[cfg(not(target_arch = "wasm32"))]
+    end: Option<std::time::Instant>,
+    /// End time in milliseconds
+    #[cfg(target_arch = "wasm32")]
+    end_ms: Option<f64>,
+    max_nodes: Option<usize>,
+}
+
+impl Deadline {
+    /// Rough number of nodes the search pops per second on WASM, used when no timer is available
+    #[cfg(target_arch = "wasm32")]
+    const NODES_PER_SECOND: f64 = 30_000.0;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(timeout: Option<std::time::Duration>) -> Self {
+        Self {
+            end: timeout.and_then(|timeout| std::time::Instant::now().checked_add(timeout)),
+            max_nodes: None,
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn new(timeout: Option<std::time::Duration>) -> Self {
+        match (timeout, performance_now()) {
+            (Some(timeout), Some(now_ms)) => Self {
+                end_ms: Some(now_ms + timeout.as_secs_f64() * 1000.0),
+                max_nodes: None,
+            },
+            (Some(timeout), None) => {
+                Self::from_node_budget((timeout.as_secs_f64() * Self::NODES_PER_SECOND) as usize)
+            }
+            (None, _) => Self {
+                end_ms: None,
+                max_nodes: None,
+            },
+        }
+    }
+
+    /// Deadline that is exceeded once `max_nodes` search nodes have been popped
+    #[cfg(any(test, target_arch = "wasm32"))]
+    pub fn from_node_budget(max_nodes: usize) -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            end: None,
+            #[cfg(target_arch = "wasm32")]
+            end_ms: None,
+            max_nodes: Some(max_nodes),
+        }
+    }
+
+    /// `popped` is the number of search nodes popped so far
+    pub fn is_exceeded(&self, popped: usize) -> bool {
+        self.max_nodes.is_some_and(|max_nodes| popped >= max_nodes) || self.is_time_exceeded()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn is_time_exceeded(&self) -> bool {
+        self.end.is_some_and(|end| std::time::Instant::now() >= end)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn is_time_exceeded(&self) -> bool {
+        self.end_ms
+            .zip(performance_now())
+            .is_some_and(|(end_ms, now_ms)| now_ms >= end_ms)
     }
 }
 
@@ -49,10 +165,6 @@ impl<T: Copy> Backtracking<T> {
         }
     }
 
-    pub fn len(&self) -> usize {
-        self.entries.len()
-    }
-
     pub fn get_depth(&self, index: usize) -> u8 {
         if index == Self::SENTINEL {
             0
@@ -61,13 +173,22 @@ impl<T: Copy> Backtracking<T> {
         }
     }
 
-    pub fn get_items(&self, mut index: usize) -> impl Iterator<Item = T> {
+    pub fn get_items(&self, index: usize) -> impl Iterator<Item = T> {
         let mut items = Vec::new();
+        self.reconstruct_into(index, &mut items);
+        items.into_iter()
+    }
+
+    /// Write the items on the path to `index` into `out` (replacing its contents), from first to last.
+    /// Reusing `out` avoids allocating a new buffer for every reconstruction.
+    pub fn reconstruct_into(&self, mut index: usize, out: &mut Vec<T>) {
+        out.clear();
+        out.reserve(self.get_depth(index) as usize);
         while index != Self::SENTINEL {
-            items.push(self.entries[index].item);
+            out.push(self.entries[index].item);
             index = self.entries[index].parent_index;
         }
-        items.into_iter().rev()
+        out.reverse();
     }
 
     pub fn push(&mut self, item: T, parent_index: usize) -> usize {
@@ -85,8 +206,25 @@ impl<T: Copy> Backtracking<T> {
     }
 }
 
-impl<T: Copy> Drop for Backtracking<T> {
-    fn drop(&mut self) {
-        dbg!(self.entries.len());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconstruct_into() {
+        let mut backtracking: Backtracking<u8> = Backtracking::new();
+        let first = backtracking.push(1, Backtracking::<u8>::SENTINEL);
+        let second = backtracking.push(2, first);
+        let branch = backtracking.push(3, first);
+        let third = backtracking.push(4, second);
+
+        let mut items = vec![42; 10];
+        backtracking.reconstruct_into(third, &mut items);
+        assert_eq!(items, [1, 2, 4]);
+        backtracking.reconstruct_into(branch, &mut items);
+        assert_eq!(items, [1, 3]);
+        backtracking.reconstruct_into(Backtracking::<u8>::SENTINEL, &mut items);
+        assert!(items.is_empty());
+        assert!(backtracking.get_items(third).eq([1, 2, 4]));
     }
 }
@@ -26,9 +26,6 @@ where
     // cut-off values
     max_first: T,
     max_second: U,
-    // variables used for profiling
-    fronts_generated: usize,
-    values_generated: usize,
 }
 
 impl<T, U> ParetoFrontBuilder<T, U>
@@ -42,11 +39,14 @@ where
             segments: Vec::new(),
             max_first,
             max_second,
-            fronts_generated: 0,
-            values_generated: 0,
         }
     }
 
+    /// Changes the cut-off value of second. Fronts that were already built are not affected.
+    pub fn set_max_second(&mut self, max_second: U) {
+        self.max_second = max_second;
+    }
+
     pub fn clear(&mut self) {
         self.segments.clear();
         self.buffer.clear();
@@ -176,8 +176,6 @@ where
     pub fn peek(&mut self) -> Option<Box<[ParetoValue<T, U>]>> {
         match self.segments.last() {
             Some(segment) => {
-                self.fronts_generated += 1;
-                self.values_generated += segment.length;
                 let slice = &self.buffer[segment.offset..segment.offset + segment.length];
                 Some(slice.into())
             }
@@ -214,23 +212,10 @@ where
     }
 }
 
-impl<T, U> Drop for ParetoFrontBuilder<T, U>
-where
-    T: Copy + std::cmp::Ord + std::default::Default,
-    U: Copy + std::cmp::Ord + std::default::Default,
-{
-    fn drop(&mut self) {
-        dbg!(
-            self.buffer.capacity(),
-            self.fronts_generated,
-            self.values_generated
-        );
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use fuzz_rng::fuzz_rng;
     use rand::Rng;
 
     const SAMPLE_FRONT_1: &[ParetoValue<u16, u16>] = &[
@@ -316,9 +301,29 @@ mod tests {
         builder.check_invariants();
     }
 
+    #[test]
+    fn test_merge_order_independent() {
+        const SAMPLE_FRONT_3: &[ParetoValue<u16, u16>] =
+            &[ParetoValue::new(120, 280), ParetoValue::new(400, 20)];
+        let fronts = [SAMPLE_FRONT_1, SAMPLE_FRONT_2, SAMPLE_FRONT_3];
+        let mut results = Vec::new();
+        for order in [[0, 1, 2], [2, 1, 0], [1, 2, 0]] {
+            let mut builder: ParetoFrontBuilder<u16, u16> = ParetoFrontBuilder::new(1000, 2000);
+            builder.push_empty();
+            for index in order {
+                builder.push(fronts[index]);
+                builder.merge();
+                builder.check_invariants();
+            }
+            results.push(builder.peek().unwrap().to_vec());
+        }
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[0], results[2]);
+    }
+
     #[test]
     fn test_fuzz() {
-        let mut rng = rand::thread_rng();
+        let mut rng = fuzz_rng();
         let mut builder: ParetoFrontBuilder<u16, u16> = ParetoFrontBuilder::new(1000, 2000);
         let mut lut = [0; 5000];
 
@@ -327,8 +332,8 @@ mod tests {
             for _ in 0..cnt {
                 let progress: u16 = rng.gen_range(0..5000);
                 let quality: u16 = rng.gen_range(0..10000);
-                for i in 0..=progress as usize {
-                    lut[i] = std::cmp::max(lut[i], quality);
+                for value in lut.iter_mut().take(progress as usize + 1) {
+                    *value = std::cmp::max(*value, quality);
                 }
                 builder.push(&[ParetoValue::new(progress, quality)]);
                 builder.check_invariants();
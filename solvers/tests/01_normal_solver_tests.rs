@@ -1,5 +1,8 @@
-use simulator::{Action, ActionMask, Settings};
-use solvers::test_utils::*;
+use simulator::{Action, ActionMask, Condition, Settings, SimulationState};
+use solvers::{
+    test_utils::*, unconstrained_durability_quality, MacroSolver, QualityOverflow,
+    QualityUpperBoundSolver, SolutionTrace, SolveResult, SolveStats, SolverCache, SolverConfig,
+};
 
 #[test]
 fn unsolvable() {
@@ -569,3 +572,624 @@ fn black_star_4048_3997() {
     let score = get_score_triple(&settings, &actions);
     assert_eq!(score, (6114, 12, 31));
 }
+
+#[test]
+fn trained_eye_low_level_recipe() {
+    // level 100 crafter, level 80 recipe
+    let settings = Settings {
+        max_cp: 600,
+        max_durability: 80,
+        max_progress: 3900,
+        max_quality: 10920,
+        base_progress: 420,
+        base_quality: 520,
+        job_level: 100,
+        allowed_actions: ActionMask::from_level(100)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let actions = solve(&settings, false, false).unwrap();
+    assert_eq!(actions[0], Action::TrainedEye);
+    let score = get_score_triple(&settings, &actions);
+    assert_eq!(score, (10920, 4, 11));
+}
+
+#[test]
+fn solve_stats() {
+    let settings = Settings {
+        max_cp: 80,
+        max_durability: 60,
+        max_progress: 1920,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let config = SolverConfig {
+        minimize_steps: true,
+        ..Default::default()
+    };
+    let mut solver = MacroSolver::new(settings, config, Box::new(|_| {}), Box::new(|_| {}));
+    assert_eq!(solver.stats(), SolveStats::default());
+    let actions = solver.solve(SimulationState::new(&settings));
+    assert!(actions.is_some());
+    let stats = solver.stats();
+    assert!(stats.search_nodes > 0);
+    assert!(stats.pareto_front_entries > 0);
+    assert!(stats.finish_states > 0);
+    assert!(stats.quality_upper_bound_states > 0);
+    assert!(stats.solve_time.is_some());
+}
+
+#[test]
+fn observe_advanced_touch_combo() {
+    // not enough CP for Basic Touch into Standard Touch, and only enough durability for a single touch action
+    let settings = Settings {
+        max_cp: 25,
+        max_durability: 20,
+        max_progress: 100,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::Reflect)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let actions = solve(&settings, false, false).unwrap();
+    // Observe + Advanced Touch (25 CP, 150% efficiency) beats Basic Touch (18 CP, 100% efficiency)
+    assert_eq!(
+        actions,
+        [
+            Action::Observe,
+            Action::ComboAdvancedTouch,
+            Action::BasicSynthesis
+        ]
+    );
+    let score = get_score_triple(&settings, &actions);
+    assert_eq!(score, (150, 3, 9));
+}
+
+#[test]
+fn forbidden_actions() {
+    // same as observe_advanced_touch_combo, but Observe may not be used
+    let settings = Settings {
+        max_cp: 25,
+        max_durability: 20,
+        max_progress: 100,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::Reflect)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let config = SolverConfig {
+        forbidden_actions: ActionMask::none().add(Action::Observe),
+        ..Default::default()
+    };
+    let actions = MacroSolver::new(settings, config, Box::new(|_| {}), Box::new(|_| {}))
+        .solve(SimulationState::new(&settings))
+        .unwrap();
+    assert!(!actions.contains(&Action::Observe));
+    let score = get_score_triple(&settings, &actions);
+    assert_eq!(score, (100, 2, 6));
+}
+
+#[test]
+fn solution_trace() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let intermediate_solutions = std::cell::RefCell::new(Vec::new());
+    let solution_callback = |trace: &SolutionTrace<'_>| {
+        let actions = trace.to_vec();
+        assert_eq!(actions.len(), trace.len());
+        let state = SimulationState::from_macro(&settings, &actions).unwrap();
+        assert_eq!(state.get_quality(), trace.quality());
+        intermediate_solutions.borrow_mut().push(actions);
+    };
+    let config = SolverConfig {
+        minimize_steps: true,
+        ..Default::default()
+    };
+    let actions = MacroSolver::new(
+        settings,
+        config,
+        Box::new(solution_callback),
+        Box::new(|_| {}),
+    )
+    .solve(SimulationState::new(&settings))
+    .unwrap();
+    // the last reported solution is the final one
+    assert_eq!(intermediate_solutions.borrow().last(), Some(&actions));
+}
+
+#[test]
+fn no_combo_actions() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation)
+            .remove(Action::ComboStandardTouch)
+            .remove(Action::ComboAdvancedTouch)
+            .remove(Action::ComboRefinedTouch),
+        adversarial: false,
+    };
+    let actions = solve(&settings, false, false).unwrap();
+    assert!(!actions.iter().any(|action| matches!(
+        action,
+        Action::ComboStandardTouch | Action::ComboAdvancedTouch | Action::ComboRefinedTouch
+    )));
+    // the macro re-simulates with the same result under the full action mask
+    let full_settings = Settings {
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        ..settings
+    };
+    assert_eq!(
+        get_score_triple(&settings, &actions),
+        get_score_triple(&full_settings, &actions)
+    );
+}
+
+#[test]
+fn quality_overflow_weighting() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let solve_with = |quality_overflow| {
+        let config = SolverConfig {
+            minimize_steps: true,
+            quality_overflow,
+            ..Default::default()
+        };
+        MacroSolver::new(settings, config, Box::new(|_| {}), Box::new(|_| {}))
+            .solve(SimulationState::new(&settings))
+            .unwrap()
+    };
+    // without overflow, any macro that maxes out Quality in the fewest steps is optimal
+    let (quality, steps, duration) =
+        get_score_triple(&settings, &solve_with(QualityOverflow::Ignore));
+    assert!(quality >= 1000);
+    assert_eq!((steps, duration), (11, 28));
+    let score = get_score_triple(&settings, &solve_with(QualityOverflow::Tiebreak));
+    assert_eq!(score, (1100, 11, 28));
+    let score = get_score_triple(&settings, &solve_with(QualityOverflow::Prefer));
+    assert_eq!(score, (2013, 15, 41));
+    // preferring overflow must be the same as maximizing Quality without a cap
+    let uncapped_settings = Settings {
+        max_quality: simulator::max_theoretical_quality(&settings),
+        ..settings
+    };
+    let config = SolverConfig {
+        minimize_steps: true,
+        ..Default::default()
+    };
+    let actions = MacroSolver::new(
+        uncapped_settings,
+        config,
+        Box::new(|_| {}),
+        Box::new(|_| {}),
+    )
+    .solve(SimulationState::new(&uncapped_settings))
+    .unwrap();
+    assert_eq!(score, get_score_triple(&uncapped_settings, &actions));
+}
+
+#[test]
+fn solve_top_k() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let reported = std::cell::Cell::new(0);
+    let mut solver = MacroSolver::new(
+        settings,
+        SolverConfig::default(),
+        Box::new(|_| reported.set(reported.get() + 1)),
+        Box::new(|_| {}),
+    );
+    let solutions = solver.solve_top_k(SimulationState::new(&settings), 3);
+    // only the first solve reports its solutions
+    let first_solve_reported = std::cell::Cell::new(0);
+    MacroSolver::new(
+        settings,
+        SolverConfig::default(),
+        Box::new(|_| first_solve_reported.set(first_solve_reported.get() + 1)),
+        Box::new(|_| {}),
+    )
+    .solve(SimulationState::new(&settings));
+    assert_eq!(reported.get(), first_solve_reported.get());
+    let scores: Vec<(u16, usize, i16)> = solutions
+        .iter()
+        .map(|actions| {
+            let state = SimulationState::from_macro(&settings, actions).unwrap();
+            (
+                std::cmp::min(state.get_quality(), settings.max_quality),
+                actions.len(),
+                settings.max_cp - state.cp,
+            )
+        })
+        .collect();
+    assert_eq!(scores, [(1000, 11, 293), (1000, 12, 275), (965, 12, 272)]);
+    for (index, score) in scores.iter().enumerate() {
+        for other in scores[..index].iter() {
+            // each macro uses less CP than the previous ones and isn't dominated by them
+            assert!(score.2 < other.2);
+            assert!(score.0 < other.0 || score.1 > other.1);
+        }
+    }
+}
+
+#[test]
+fn solver_cache() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let config = SolverConfig {
+        minimize_steps: true,
+        ..Default::default()
+    };
+    let solve_with_cache = |settings: Settings, cache: Option<SolverCache>| {
+        let mut solver = MacroSolver::new(settings, config, Box::new(|_| {}), Box::new(|_| {}));
+        if let Some(cache) = cache {
+            solver = solver.with_cache(cache);
+        }
+        let actions = solver.solve(SimulationState::new(&settings)).unwrap();
+        (get_score_triple(&settings, &actions), solver.into_cache())
+    };
+    let (score, cache) = solve_with_cache(settings, None);
+    let num_states = cache.num_states();
+    // solving the same recipe again reuses all memoized states
+    let (cached_score, cache) = solve_with_cache(settings, Some(cache));
+    assert_eq!(cached_score, score);
+    assert_eq!(cache.num_states(), num_states);
+    // the cache is applied when solving, so it isn't discarded by builder methods that are called after `with_cache`
+    let cache = MacroSolver::new(settings, config, Box::new(|_| {}), Box::new(|_| {}))
+        .with_cache(cache)
+        .with_cancellation(Box::new(|| false))
+        .into_cache();
+    assert_eq!(cache.num_states(), num_states);
+    // changing the recipe discards the memoized states
+    let mut cache = cache;
+    cache.reset_for_settings(Settings {
+        max_cp: 300,
+        ..settings
+    });
+    assert_eq!(cache.num_states(), 0);
+}
+
+#[test]
+fn deterministic_solution() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let config = SolverConfig {
+        minimize_steps: true,
+        ..Default::default()
+    };
+    let mut solver = MacroSolver::new(settings, config, Box::new(|_| {}), Box::new(|_| {}));
+    let actions = solver.solve(SimulationState::new(&settings)).unwrap();
+    // a fresh solver and a solver that reuses memoized bounds pick the same macro among equivalent ones
+    let fresh_actions = solve(&settings, false, true).unwrap();
+    let cached_actions = solver.solve(SimulationState::new(&settings)).unwrap();
+    assert_eq!(fresh_actions, actions);
+    assert_eq!(cached_actions, actions);
+}
+
+#[test]
+fn reduced_initial_durability() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let initial_state = SimulationState::with_initial_durability(&settings, 20);
+    let actions = MacroSolver::new(
+        settings,
+        SolverConfig::default(),
+        Box::new(|_| {}),
+        Box::new(|_| {}),
+    )
+    .solve(initial_state)
+    .unwrap();
+    let mut state = initial_state;
+    for action in actions.iter() {
+        state = state
+            .use_action(*action, Condition::Normal, &settings)
+            .unwrap();
+    }
+    assert!(state.progress >= settings.max_progress);
+    assert_eq!(state.get_quality(), 1000);
+    // the full-durability solution breaks the item when starting from 20 durability
+    let full_durability_actions = solve(&settings, false, false).unwrap();
+    let result = full_durability_actions
+        .iter()
+        .try_fold(initial_state, |state, action| {
+            state.use_action(*action, Condition::Normal, &settings)
+        });
+    assert!(!result.is_ok_and(|state| state.progress >= settings.max_progress));
+}
+
+#[test]
+fn minimize_cp_usage() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let default_actions = solve(&settings, false, false).unwrap();
+    let default_cp_used = settings.max_cp
+        - SimulationState::from_macro(&settings, &default_actions)
+            .unwrap()
+            .cp;
+    let SolveResult { actions, cp_used } = MacroSolver::new(
+        settings,
+        SolverConfig::default(),
+        Box::new(|_| {}),
+        Box::new(|_| {}),
+    )
+    .solve_min_cp(SimulationState::new(&settings))
+    .unwrap();
+    let state = SimulationState::from_macro(&settings, &actions).unwrap();
+    assert_eq!(cp_used, settings.max_cp - state.cp);
+    assert_eq!(state.get_quality(), 1000);
+    assert_eq!(cp_used, 275);
+    assert!(cp_used < default_cp_used);
+    // the macro still works with exactly the reported amount of CP
+    let reduced_settings = Settings {
+        max_cp: cp_used,
+        ..settings
+    };
+    assert!(SimulationState::from_macro(&reduced_settings, &actions).is_ok());
+}
+
+#[test]
+fn unconstrained_durability() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 5000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let actions = solve(&settings, false, false).unwrap();
+    let score = get_score_triple(&settings, &actions);
+    let ceiling = unconstrained_durability_quality(&settings);
+    let upper_bound =
+        QualityUpperBoundSolver::new(settings).quality_upper_bound(SimulationState::new(&settings));
+    assert!(ceiling >= upper_bound);
+    assert!(ceiling >= score.0);
+}
+
+#[test]
+fn finish_with_tricks_of_the_trade() {
+    let settings = Settings {
+        max_cp: 100,
+        max_durability: 20,
+        max_progress: 300,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 100,
+        allowed_actions: ActionMask::from_level(100).remove(Action::TrainedPerfection),
+        adversarial: false,
+    };
+    // without CP, Basic Synthesis can only add 240 Progress before durability runs out
+    let initial_state = SimulationState::with_initial_cp(&settings, 0);
+    let actions = MacroSolver::new(
+        settings,
+        SolverConfig::default(),
+        Box::new(|_| {}),
+        Box::new(|_| {}),
+    )
+    .solve(initial_state)
+    .unwrap();
+    assert!(actions.contains(&Action::TricksOfTheTrade));
+    let state = actions.iter().fold(initial_state, |state, action| {
+        state
+            .use_action(*action, Condition::Normal, &settings)
+            .unwrap()
+    });
+    assert!(state.progress >= settings.max_progress);
+}
+
+#[test]
+fn reduced_initial_cp() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let initial_state = SimulationState::with_initial_cp(&settings, 200);
+    let actions = MacroSolver::new(
+        settings,
+        SolverConfig::default(),
+        Box::new(|_| {}),
+        Box::new(|_| {}),
+    )
+    .solve(initial_state)
+    .unwrap();
+    // every action must be affordable with the reduced budget
+    let state = actions.iter().fold(initial_state, |state, action| {
+        state
+            .use_action(*action, Condition::Normal, &settings)
+            .unwrap()
+    });
+    assert!(state.progress >= settings.max_progress);
+    assert_eq!(state.get_quality(), 570);
+    // the full-CP solution doesn't fit into the reduced budget
+    let full_cp_actions = solve(&settings, false, false).unwrap();
+    assert!(get_score_triple(&settings, &full_cp_actions).0 >= settings.max_quality);
+    let result = full_cp_actions
+        .iter()
+        .try_fold(initial_state, |state, action| {
+            state.use_action(*action, Condition::Normal, &settings)
+        });
+    assert!(result.is_err());
+}
+
+#[test]
+#[cfg(feature = "solve-log")]
+fn solve_log() {
+    let settings = Settings {
+        max_cp: 400,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 1000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let mut log: Vec<u8> = Vec::new();
+    let mut solver = MacroSolver::new(
+        settings,
+        SolverConfig::default(),
+        Box::new(|_| {}),
+        Box::new(|_| {}),
+    )
+    .with_log(Box::new(&mut log));
+    let actions = solver.solve(SimulationState::new(&settings)).unwrap();
+    let unsolvable_state = SimulationState {
+        cp: 0,
+        durability: 5,
+        ..SimulationState::new(&settings)
+    };
+    assert_eq!(solver.solve(unsolvable_state), None);
+    drop(solver);
+
+    let log = String::from_utf8(log).unwrap();
+    let records: Vec<serde_json::Value> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        records[0]["settings"],
+        serde_json::to_value(settings).unwrap()
+    );
+    assert!(records[0]["stats"]["search_nodes"].is_u64());
+    let (quality, steps, duration) = get_score_triple(&settings, &actions);
+    assert_eq!(
+        records[0]["score"],
+        serde_json::json!({"quality": quality, "steps": steps, "duration": duration})
+    );
+    assert!(records[1]["score"].is_null());
+}
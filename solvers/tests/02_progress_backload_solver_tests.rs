@@ -8,7 +8,7 @@ fn is_progress_backloaded(actions: &[Action]) -> bool {
         .unwrap();
     // there musn't be any Quality-increasing actions after the first Progress-increasing action
     !actions
-        .into_iter()
+        .iter()
         .skip(first_progress_action)
         .any(|action| action.quality_efficiency(10) != 0)
 }
@@ -1,24 +1,25 @@
 use simulator::{Action, ActionMask, Condition, Settings, SimulationState};
-use solvers::MacroSolver;
+use solvers::{MacroSolver, SolverConfig};
 
 fn solve(
     settings: &Settings,
     backload_progress: bool,
     minimize_steps: bool,
 ) -> Option<Vec<Action>> {
-    MacroSolver::new(settings.clone(), Box::new(|_| {}), Box::new(|_| {})).solve(
-        SimulationState::new(settings),
+    let config = SolverConfig {
         backload_progress,
         minimize_steps,
-    )
+        ..Default::default()
+    };
+    MacroSolver::new(*settings, config, Box::new(|_| {}), Box::new(|_| {}))
+        .solve(SimulationState::new(settings))
 }
 
 fn get_quality(settings: &Settings, actions: &[Action]) -> u16 {
-    let mut state = SimulationState::new(&settings);
+    let mut state = SimulationState::new(settings);
     for action in actions {
-        state = SimulationState::try_from(state)
-            .unwrap()
-            .use_action(action.clone(), Condition::Normal, &settings)
+        state = state
+            .use_action(*action, Condition::Normal, settings)
             .unwrap();
     }
     assert!(state.progress >= settings.max_progress);
@@ -26,7 +27,7 @@ fn get_quality(settings: &Settings, actions: &[Action]) -> u16 {
 }
 
 fn get_duration(actions: &[Action]) -> i16 {
-    actions.into_iter().map(|action| action.time_cost()).sum()
+    actions.iter().map(|action| action.time_cost()).sum()
 }
 
 fn is_progress_backloaded(actions: &[Action]) -> bool {
@@ -36,7 +37,7 @@ fn is_progress_backloaded(actions: &[Action]) -> bool {
         .unwrap();
     // there musn't be any Quality-increasing actions after the first Progress-increasing action
     !actions
-        .into_iter()
+        .iter()
         .skip(first_progress_action)
         .any(|action| action.quality_efficiency(10) != 0)
 }
@@ -70,6 +71,38 @@ fn random_0f93c79f() {
     assert_eq!(actions.len(), 19);
 }
 
+#[test]
+fn guaranteed_quality_holds() {
+    let settings = Settings {
+        max_cp: 370,
+        max_durability: 60,
+        max_progress: 2000,
+        ..SETTINGS
+    };
+    let actions = solve(&settings, false, false).unwrap();
+    let guaranteed_quality = get_quality(&settings, &actions);
+    let normal_settings = Settings {
+        adversarial: false,
+        ..settings
+    };
+    assert!(guaranteed_quality <= get_quality(&normal_settings, &actions));
+    // An Excellent proc forces the next step to be Poor, which hurts the most if the next step is a Quality action.
+    // Try every placement of the proc (the first step is always Normal).
+    let worst_quality = (1..actions.len() - 1)
+        .map(|excellent_index| {
+            let mut conditions = vec![Condition::Normal; actions.len()];
+            conditions[excellent_index] = Condition::Excellent;
+            conditions[excellent_index + 1] = Condition::Poor;
+            SimulationState::new(&normal_settings)
+                .use_actions_with_conditions(&actions, &conditions, &normal_settings)
+                .unwrap()
+                .get_quality()
+        })
+        .min()
+        .unwrap();
+    assert!(worst_quality >= guaranteed_quality);
+}
+
 #[test]
 fn random_1e281667() {
     let settings = Settings {
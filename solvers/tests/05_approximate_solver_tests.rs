@@ -0,0 +1,134 @@
+use simulator::{Action, ActionMask, Settings, SimulationState};
+use solvers::{test_utils::*, MacroSolver, SolverConfig};
+
+fn solve_approximate(settings: &Settings, quality_tolerance: u16) -> Option<Vec<Action>> {
+    let config = SolverConfig {
+        quality_tolerance,
+        ..Default::default()
+    };
+    MacroSolver::new(*settings, config, Box::new(|_| {}), Box::new(|_| {}))
+        .solve(SimulationState::new(settings))
+}
+
+/// Check that the approximate solution is within tolerance of the exact solution
+fn check_within_tolerance(settings: &Settings, exact_quality: u16) {
+    let quality_tolerance = exact_quality / 100;
+    let actions = solve_approximate(settings, quality_tolerance).unwrap();
+    let (quality, _, _) = get_score_triple(settings, &actions);
+    dbg!(quality, exact_quality);
+    assert!(quality <= exact_quality);
+    assert!(quality + quality_tolerance >= exact_quality);
+}
+
+#[test]
+fn random_0f93c79f() {
+    let settings = Settings {
+        max_cp: 370,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 40000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    check_within_tolerance(&settings, 1802);
+}
+
+#[test]
+fn random_1e281667() {
+    let settings = Settings {
+        max_cp: 553,
+        max_durability: 70,
+        max_progress: 2400,
+        max_quality: 20000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    check_within_tolerance(&settings, 3366);
+}
+
+#[test]
+fn random_e413e05d() {
+    let settings = Settings {
+        max_cp: 450,
+        max_durability: 80,
+        max_progress: 2800,
+        max_quality: 40000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    check_within_tolerance(&settings, 2018);
+}
+
+#[test]
+fn random_6799bb1d() {
+    let settings = Settings {
+        max_cp: 501,
+        max_durability: 70,
+        max_progress: 1950,
+        max_quality: 20000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    check_within_tolerance(&settings, 3321);
+}
+
+/// The tolerance is a guarantee on the Quality of the found macro, so it must hold for any tolerance and also when Quality is capped
+#[test]
+fn tolerance_sweep() {
+    let settings = Settings {
+        max_cp: 370,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 40000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    for max_quality in [40000, 1700] {
+        let settings = Settings {
+            max_quality,
+            ..settings
+        };
+        let capped_quality =
+            |actions: &[Action]| std::cmp::min(get_score_triple(&settings, actions).0, max_quality);
+        let exact_quality = capped_quality(&solve_approximate(&settings, 0).unwrap());
+        for quality_tolerance in [1, 50, 300, 1000] {
+            let actions = solve_approximate(&settings, quality_tolerance).unwrap();
+            let quality = capped_quality(&actions);
+            assert!(quality <= exact_quality);
+            assert!(
+                quality + quality_tolerance >= exact_quality,
+                "{quality} + {quality_tolerance} < {exact_quality}"
+            );
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use simulator::{Action, ActionMask, Settings, SimulationState};
+use solvers::{test_utils::*, MacroSolver, SolverConfig};
+
+fn solve_with_max_steps(settings: &Settings, max_steps: u8) -> Option<Vec<Action>> {
+    let config = SolverConfig {
+        max_steps: Some(max_steps),
+        ..Default::default()
+    };
+    MacroSolver::new(*settings, config, Box::new(|_| {}), Box::new(|_| {}))
+        .solve(SimulationState::new(settings))
+}
+
+fn settings() -> Settings {
+    Settings {
+        max_cp: 370,
+        max_durability: 60,
+        max_progress: 2000,
+        max_quality: 40000,
+        base_progress: 100,
+        base_quality: 100,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    }
+}
+
+#[test]
+fn test_max_steps() {
+    let settings = settings();
+    let actions = solve_with_max_steps(&settings, 10).unwrap();
+    let state = SimulationState::from_macro(&settings, &actions).unwrap();
+    assert!(actions.len() <= 10);
+    assert!(state.progress >= settings.max_progress);
+    let (quality, _, _) = get_score_triple(&settings, &actions);
+    let (unlimited_quality, unlimited_steps, _) =
+        get_score_triple(&settings, &solve(&settings, false, false).unwrap());
+    // the step budget is binding, so the budgeted solution cannot be better
+    assert!(unlimited_steps > 10);
+    assert!(quality < unlimited_quality);
+}
+
+#[test]
+fn test_max_steps_unreachable_progress() {
+    assert_eq!(solve_with_max_steps(&settings(), 3), None);
+}
@@ -0,0 +1,137 @@
+use std::{
+    cell::{Cell, RefCell},
+    time::{Duration, Instant},
+};
+
+use simulator::{Action, ActionMask, Settings, SimulationState};
+use solvers::{test_utils::get_score_triple, MacroSolver, SolverConfig};
+
+fn solve_with_timeout(
+    settings: &Settings,
+    minimize_steps: bool,
+    timeout: Duration,
+) -> Option<Vec<Action>> {
+    let config = SolverConfig {
+        minimize_steps,
+        timeout: Some(timeout),
+        ..Default::default()
+    };
+    MacroSolver::new(*settings, config, Box::new(|_| {}), Box::new(|_| {}))
+        .solve(SimulationState::new(settings))
+}
+
+#[test]
+fn test_timeout_returns_valid_macro() {
+    let settings = Settings {
+        max_cp: 714,
+        max_durability: 70,
+        max_progress: 5720,
+        max_quality: 12900,
+        base_progress: 239,
+        base_quality: 271,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let actions = solve_with_timeout(&settings, true, Duration::ZERO).unwrap();
+    // get_score_triple checks that the macro maxes out Progress
+    let (quality, _, _) = get_score_triple(&settings, &actions);
+    // without a timeout, the solver finds a macro with 13046 Quality
+    assert!(quality <= 13046);
+}
+
+#[test]
+fn test_timeout_stops_search() {
+    let settings = Settings {
+        max_cp: 714,
+        max_durability: 70,
+        max_progress: 5720,
+        max_quality: 12900,
+        base_progress: 239,
+        base_quality: 271,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    let timeout = Duration::from_millis(100);
+    // the search polls for cancellation right before it checks the deadline
+    let polls = RefCell::new(Vec::new());
+    let start = Instant::now();
+    let config = SolverConfig {
+        minimize_steps: true,
+        timeout: Some(timeout),
+        ..Default::default()
+    };
+    let actions = MacroSolver::new(settings, config, Box::new(|_| {}), Box::new(|_| {}))
+        .with_cancellation(Box::new(|| {
+            polls.borrow_mut().push(start.elapsed());
+            false
+        }))
+        .solve(SimulationState::new(&settings))
+        .unwrap();
+    let elapsed = start.elapsed();
+    get_score_triple(&settings, &actions);
+    // the search was stopped by the first poll past the deadline
+    let polls = polls.into_inner();
+    let last_poll = *polls.last().unwrap();
+    assert!(last_poll >= timeout);
+    assert_eq!(polls.iter().filter(|poll| **poll >= timeout).count(), 1);
+    // only finishing the macro greedily is left after the search is stopped
+    assert!(elapsed - last_poll < Duration::from_secs(1));
+}
+
+#[test]
+fn test_timeout_unreachable_progress() {
+    let settings = Settings {
+        max_cp: 100,
+        max_durability: 20,
+        max_progress: 5720,
+        max_quality: 12900,
+        base_progress: 239,
+        base_quality: 271,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90),
+        adversarial: false,
+    };
+    assert_eq!(solve_with_timeout(&settings, true, Duration::ZERO), None);
+}
+
+#[test]
+fn test_cancellation_returns_none() {
+    let settings = Settings {
+        max_cp: 714,
+        max_durability: 70,
+        max_progress: 5720,
+        max_quality: 12900,
+        base_progress: 239,
+        base_quality: 271,
+        job_level: 90,
+        allowed_actions: ActionMask::from_level(90)
+            .remove(Action::TrainedEye)
+            .remove(Action::HeartAndSoul)
+            .remove(Action::QuickInnovation),
+        adversarial: false,
+    };
+    // cancel as soon as the search has found its first macro
+    let cancelled = Cell::new(false);
+    let config = SolverConfig {
+        minimize_steps: true,
+        ..Default::default()
+    };
+    let actions = MacroSolver::new(
+        settings,
+        config,
+        Box::new(|_| cancelled.set(true)),
+        Box::new(|_| {}),
+    )
+    .with_cancellation(Box::new(|| cancelled.get()))
+    .solve(SimulationState::new(&settings));
+    assert!(cancelled.get());
+    assert_eq!(actions, None);
+}
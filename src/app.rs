@@ -11,14 +11,27 @@ use web_time::Instant;
 
 use egui::{Align, CursorIcon, FontData, FontDefinitions, FontFamily, Id, Layout, TextStyle};
 use game_data::{
-    action_name, get_initial_quality, get_item_name, get_job_name, Consumable, Locale,
+    action_name, cp_used, get_initial_quality, get_item_name, get_job_name, Consumable,
+    CrafterStats, Locale,
 };
 
-use simulator::Action;
+use simulator::{Action, Settings, SimulationState};
+use solvers::{QualityOverflow, RelaxedQualityBounds, SolveStats};
 
-use crate::config::{CrafterConfig, QualitySource, QualityTarget, RecipeConfiguration};
+use crate::config::{
+    BaseValueOverride, CrafterConfig, QualitySource, QualityTarget, RecipeConfiguration,
+};
+use crate::utils::ActionHistory;
 use crate::widgets::*;
-use crate::worker::BridgeType;
+use crate::worker::{BridgeType, CancelFlag, WorkerInput};
+
+fn quality_overflow_name(quality_overflow: QualityOverflow) -> &'static str {
+    match quality_overflow {
+        QualityOverflow::Ignore => "Ignore",
+        QualityOverflow::Tiebreak => "Tiebreak",
+        QualityOverflow::Prefer => "Prefer",
+    }
+}
 
 fn load<T: DeserializeOwned>(cc: &eframe::CreationContext<'_>, key: &'static str, default: T) -> T {
     match cc.storage {
@@ -29,17 +42,60 @@ fn load<T: DeserializeOwned>(cc: &eframe::CreationContext<'_>, key: &'static str
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum SolverEvent {
-    Progress(f32),
+    /// Estimated fraction of the search that is done, and the Quality of the best solution found so far.
+    Progress(f32, Option<u16>),
     IntermediateSolution(Vec<Action>),
-    FinalSolution(Vec<Action>),
+    /// All solutions found by the solver, best solution first.
+    FinalSolution(Vec<Vec<Action>>, SolveStats),
+    RelaxedQualityBounds(Settings, RelaxedQualityBounds),
+    /// Food and medicine that would allow finishing the craft, after a solve that found no solution.
+    ConsumableSuggestion(Vec<Consumable>),
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SolverConfig {
     pub quality_target: QualityTarget,
     pub backload_progress: bool,
     pub adversarial: bool,
     pub minimize_steps: bool,
+    #[serde(default)]
+    pub max_steps: Option<u8>,
+    /// Time limit in seconds
+    #[serde(default = "default_time_limit")]
+    pub time_limit: Option<u16>,
+    #[serde(default)]
+    pub avoid_combo_actions: bool,
+    #[serde(default)]
+    pub quality_overflow: QualityOverflow,
+    #[serde(default = "default_solution_count")]
+    pub solution_count: u8,
+    #[serde(default)]
+    pub minimize_cp: bool,
+}
+
+fn default_time_limit() -> Option<u16> {
+    Some(30)
+}
+
+fn default_solution_count() -> u8 {
+    1
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            quality_target: QualityTarget::default(),
+            backload_progress: false,
+            adversarial: false,
+            minimize_steps: false,
+            max_steps: None,
+            time_limit: default_time_limit(),
+            avoid_combo_actions: false,
+            quality_overflow: QualityOverflow::default(),
+            solution_count: default_solution_count(),
+            minimize_cp: false,
+        }
+    }
 }
 
 pub struct MacroSolverApp {
@@ -50,24 +106,36 @@ pub struct MacroSolverApp {
     crafter_config: CrafterConfig,
     solver_config: SolverConfig,
     macro_view_config: MacroViewConfig,
+    base_value_override: BaseValueOverride,
+    developer_mode: bool,
 
     stats_edit_window_open: bool,
     actions: Vec<Action>,
+    action_history: ActionHistory,
+    solver_actions: Vec<Action>,
+    solutions: Vec<Vec<Action>>,
     solver_pending: bool,
     solver_progress: f32,
+    solver_best_quality: Option<u16>,
     start_time: Option<Instant>,
     duration: Option<Duration>,
+    solve_stats: Option<SolveStats>,
+    /// Consumables that would let the craft finish, suggested when the last solve found no solution
+    consumable_suggestion: Vec<Consumable>,
+    /// Set while the worker computes `consumable_suggestion`
+    suggestion_pending: bool,
     data_update: Rc<Cell<Option<SolverEvent>>>,
     bridge: BridgeType,
+    cancel_flag: CancelFlag,
 }
 
 impl MacroSolverApp {
     #[cfg(target_arch = "wasm32")]
     fn initialize_bridge(
-        cc: &eframe::CreationContext<'_>,
+        ctx: &egui::Context,
         data_update: &Rc<Cell<Option<SolverEvent>>>,
     ) -> BridgeType {
-        let ctx = cc.egui_ctx.clone();
+        let ctx = ctx.clone();
         let sender = data_update.clone();
 
         <crate::worker::Worker as gloo_worker::Spawnable>::spawner()
@@ -75,12 +143,13 @@ impl MacroSolverApp {
                 sender.set(Some(response));
                 ctx.request_repaint();
             })
+            .encoding::<crate::worker::Codec>()
             .spawn(concat!("./webworker", env!("RANDOM_SUFFIX"), ".js"))
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     fn initialize_bridge(
-        _cc: &eframe::CreationContext<'_>,
+        _ctx: &egui::Context,
         _data_cell: &Rc<Cell<Option<SolverEvent>>>,
     ) -> BridgeType {
         BridgeType::new()
@@ -89,7 +158,7 @@ impl MacroSolverApp {
     /// Called once before the first frame.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         let data_update = Rc::new(Cell::new(None));
-        let bridge = Self::initialize_bridge(cc, &data_update);
+        let bridge = Self::initialize_bridge(&cc.egui_ctx, &data_update);
 
         cc.egui_ctx.set_pixels_per_point(1.2);
         cc.egui_ctx.style_mut(|style| {
@@ -100,10 +169,8 @@ impl MacroSolverApp {
 
         Self::load_fonts(&cc.egui_ctx);
 
-        let default_recipe_config = RecipeConfiguration {
-            recipe: *game_data::RECIPES.last().unwrap(),
-            quality_source: QualitySource::HqMaterialList([0; 6]),
-        };
+        let default_recipe_config =
+            RecipeConfiguration::default_recipe().expect("Game data contains no recipes");
 
         Self {
             locale: load(cc, "LOCALE", Locale::EN),
@@ -113,15 +180,25 @@ impl MacroSolverApp {
             crafter_config: load(cc, "CRAFTER_CONFIG", Default::default()),
             solver_config: load(cc, "SOLVER_CONFIG", Default::default()),
             macro_view_config: load(cc, "MACRO_VIEW_CONFIG", Default::default()),
+            base_value_override: load(cc, "BASE_VALUE_OVERRIDE", Default::default()),
+            developer_mode: load(cc, "DEVELOPER_MODE", false),
 
             stats_edit_window_open: false,
             actions: Vec::new(),
+            action_history: ActionHistory::new(&[]),
+            solver_actions: Vec::new(),
+            solutions: Vec::new(),
             solver_pending: false,
             solver_progress: 0.0,
+            solver_best_quality: None,
             start_time: None,
             duration: None,
+            solve_stats: None,
+            consumable_suggestion: Vec::new(),
+            suggestion_pending: false,
             data_update,
             bridge,
+            cancel_flag: CancelFlag::default(),
         }
     }
 }
@@ -129,7 +206,34 @@ impl MacroSolverApp {
 impl eframe::App for MacroSolverApp {
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.solver_update();
+        self.solver_update(ctx);
+
+        // keyboard shortcuts are ignored while typing in a text field
+        if !ctx.wants_keyboard_input()
+            && !self.solver_pending
+            && ctx.input_mut(|input| input.consume_key(egui::Modifiers::COMMAND, egui::Key::Enter))
+        {
+            self.start_solver(ctx);
+        }
+        if !ctx.wants_keyboard_input() && !self.solver_pending {
+            // check redo first, Ctrl+Z would also match Ctrl+Shift+Z
+            let redo = ctx.input_mut(|input| {
+                input.consume_key(
+                    egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                    egui::Key::Z,
+                ) || input.consume_key(egui::Modifiers::COMMAND, egui::Key::Y)
+            });
+            let undo = !redo
+                && ctx.input_mut(|input| input.consume_key(egui::Modifiers::COMMAND, egui::Key::Z));
+            let restored_actions = match (undo, redo) {
+                (true, _) => self.action_history.undo(),
+                (_, true) => self.action_history.redo(),
+                _ => None,
+            };
+            if let Some(actions) = restored_actions {
+                self.actions = actions;
+            }
+        }
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -188,20 +292,15 @@ impl eframe::App for MacroSolverApp {
                     )
                     .open_in_new_tab(true),
                 );
-                ui.with_layout(
-                    Layout::right_to_left(Align::Center),
-                    egui::warn_if_debug_build,
-                );
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    egui::warn_if_debug_build(ui);
+                    ui.checkbox(&mut self.developer_mode, "Developer")
+                        .on_hover_text("Show statistics of the most recent solve");
+                });
             });
         });
 
-        let game_settings = game_data::get_game_settings(
-            self.recipe_config.recipe,
-            *self.crafter_config.active_stats(),
-            self.selected_food,
-            self.selected_potion,
-            self.solver_config.adversarial,
-        );
+        let game_settings = self.game_settings();
         let initial_quality = match self.recipe_config.quality_source {
             QualitySource::HqMaterialList(hq_materials) => {
                 game_data::get_initial_quality(self.recipe_config.recipe, hq_materials)
@@ -214,17 +313,43 @@ impl eframe::App for MacroSolverApp {
                 ui.horizontal(|ui| {
                     ui.with_layout(Layout::top_down_justified(Align::TOP), |ui| {
                         ui.set_max_width(885.0);
-                        ui.add(Simulator::new(
-                            &game_settings,
-                            initial_quality,
-                            self.solver_config,
-                            &self.crafter_config,
-                            &self.actions,
-                            game_data::ITEMS
-                                .get(&self.recipe_config.recipe.item_id)
-                                .unwrap(),
-                            self.locale,
-                        ));
+                        match game_settings {
+                            Ok(game_settings) => {
+                                if !self.solver_pending && self.solutions.len() > 1 {
+                                    self.draw_solution_tabs(ui, &game_settings, initial_quality);
+                                    ui.add_space(5.5);
+                                }
+                                ui.add(Simulator::new(
+                                    &game_settings,
+                                    initial_quality,
+                                    self.solver_config,
+                                    &self.crafter_config,
+                                    &mut self.actions,
+                                    &self.recipe_config.recipe,
+                                    self.locale,
+                                ));
+                                if !self.solver_pending
+                                    && !self.solver_actions.is_empty()
+                                    && self.actions != self.solver_actions
+                                {
+                                    ui.add_space(5.5);
+                                    ui.add(SolverDiff::new(
+                                        &game_settings,
+                                        &mut self.actions,
+                                        &self.solver_actions,
+                                    ));
+                                }
+                            }
+                            Err(error) => {
+                                ui.group(|ui| {
+                                    ui.label(
+                                        egui::RichText::new(format!("⚠ Invalid recipe: {error}"))
+                                            .strong()
+                                            .color(ui.visuals().error_fg_color),
+                                    );
+                                });
+                            }
+                        }
                         ui.add_space(5.5);
                         ui.horizontal(|ui| {
                             ui.vertical(|ui| {
@@ -272,17 +397,26 @@ impl eframe::App for MacroSolverApp {
                                     );
                                 });
                             });
-                            ui.add_enabled_ui(!self.solver_pending, |ui| {
-                                ui.group(|ui| {
-                                    ui.set_height(560.0);
-                                    self.draw_configuration_widget(ui)
+                            ui.group(|ui| {
+                                ui.set_height(560.0);
+                                ui.vertical(|ui| {
+                                    ui.add_enabled_ui(!self.solver_pending, |ui| {
+                                        self.draw_configuration_widget(ui)
+                                    });
+                                    self.draw_solve_controls(ui, initial_quality);
                                 });
                             });
                         });
                     });
                     ui.add_sized(
                         [320.0, 730.0],
-                        MacroView::new(&mut self.actions, &mut self.macro_view_config, self.locale),
+                        MacroView::new(
+                            &mut self.actions,
+                            &mut self.macro_view_config,
+                            self.locale,
+                            game_settings.ok(),
+                            initial_quality,
+                        ),
                     );
                     // fill remaining horizontal space
                     ui.with_layout(Layout::right_to_left(Align::Center), |_| {});
@@ -303,6 +437,40 @@ impl eframe::App for MacroSolverApp {
         .show(ctx, |ui| {
             ui.add(StatsEdit::new(self.locale, &mut self.crafter_config));
         });
+
+        // intermediate solutions are not worth undoing to, so only the final solution is recorded
+        if !self.solver_pending {
+            self.action_history.record(&self.actions);
+        }
+
+        egui::Window::new(
+            egui::RichText::new("Solver statistics")
+                .strong()
+                .text_style(TextStyle::Body),
+        )
+        .open(&mut self.developer_mode)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| match self.solve_stats {
+            Some(stats) => Self::draw_solve_stats(ui, stats),
+            None => {
+                ui.label("Statistics are shown once the solver has finished.");
+            }
+        });
+
+        // requested by the simulator widget, and held back until the solver and the consumable suggestion are done because the worker runs one job at a time
+        if !self.solver_pending && !self.suggestion_pending {
+            let bounds_request = ctx.data_mut(|data| {
+                let request_id = Id::new("RELAXED_QUALITY_BOUNDS_REQUEST");
+                let request = data.get_temp::<(Settings, i16)>(request_id);
+                data.remove::<(Settings, i16)>(request_id);
+                request
+            });
+            if let Some((settings, extra_cp)) = bounds_request {
+                self.bridge
+                    .send(WorkerInput::RelaxedQualityBounds(settings, extra_cp));
+            }
+        }
     }
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
@@ -313,6 +481,8 @@ impl eframe::App for MacroSolverApp {
         eframe::set_value(storage, "CRAFTER_CONFIG", &self.crafter_config);
         eframe::set_value(storage, "SOLVER_CONFIG", &self.solver_config);
         eframe::set_value(storage, "MACRO_VIEW_CONFIG", &self.macro_view_config);
+        eframe::set_value(storage, "BASE_VALUE_OVERRIDE", &self.base_value_override);
+        eframe::set_value(storage, "DEVELOPER_MODE", &self.developer_mode);
     }
 
     fn auto_save_interval(&self) -> std::time::Duration {
@@ -321,7 +491,18 @@ impl eframe::App for MacroSolverApp {
 }
 
 impl MacroSolverApp {
-    fn solver_update(&mut self) {
+    fn game_settings(&self) -> Result<Settings, &'static str> {
+        let game_settings = game_data::get_game_settings(
+            self.recipe_config.recipe,
+            *self.crafter_config.active_stats(),
+            self.selected_food,
+            self.selected_potion,
+            self.solver_config.adversarial,
+        )?;
+        Ok(self.base_value_override.apply(game_settings))
+    }
+
+    fn solver_update(&mut self, ctx: &egui::Context) {
         #[cfg(not(target_arch = "wasm32"))]
         if let Some(bridge_rx) = &self.bridge.rx {
             if let Ok(update) = bridge_rx.try_recv() {
@@ -331,21 +512,203 @@ impl MacroSolverApp {
 
         if let Some(update) = self.data_update.take() {
             match update {
-                SolverEvent::Progress(progress) => {
+                SolverEvent::RelaxedQualityBounds(settings, bounds) => {
+                    ctx.data_mut(|data| {
+                        data.insert_temp(
+                            Id::new("RELAXED_QUALITY_BOUNDS"),
+                            (settings, Some(bounds)),
+                        );
+                    });
+                }
+                SolverEvent::ConsumableSuggestion(consumables) => {
+                    if self.suggestion_pending {
+                        self.consumable_suggestion = consumables;
+                        self.suggestion_pending = false;
+                    }
+                }
+                // late event of a cancelled job
+                _ if !self.solver_pending => (),
+                SolverEvent::Progress(progress, best_quality) => {
                     self.solver_progress = progress;
+                    self.solver_best_quality = best_quality;
                 }
                 SolverEvent::IntermediateSolution(actions) => {
-                    self.actions = actions;
+                    self.actions.clone_from(&actions);
+                    self.solver_actions = actions;
                 }
-                SolverEvent::FinalSolution(actions) => {
-                    self.actions = actions;
+                SolverEvent::FinalSolution(solutions, stats) => {
+                    if solutions.is_empty() {
+                        self.suggestion_pending = true;
+                        self.bridge.send(WorkerInput::SuggestConsumables(
+                            self.recipe_config.recipe,
+                            *self.crafter_config.active_stats(),
+                            self.selected_food,
+                            self.selected_potion,
+                            self.base_value_override,
+                        ));
+                    }
+                    let actions = solutions.first().cloned().unwrap_or_default();
+                    self.actions.clone_from(&actions);
+                    self.solver_actions = actions;
+                    self.solutions = solutions;
                     self.duration = Some(Instant::now() - self.start_time.unwrap());
+                    self.solve_stats = Some(stats);
                     self.solver_pending = false;
                 }
             }
         }
     }
 
+    /// Switch between the alternative solutions of the last solve and compare them in a plot
+    fn draw_solution_tabs(
+        &mut self,
+        ui: &mut egui::Ui,
+        game_settings: &Settings,
+        initial_quality: u16,
+    ) {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                for (index, solution) in self.solutions.iter().enumerate() {
+                    let state =
+                        SimulationState::from_macro_continue_on_error(game_settings, solution).0;
+                    let selected = self.solver_actions == *solution;
+                    if ui
+                        .selectable_label(selected, format!("Solution {}", index + 1))
+                        .on_hover_text(format!(
+                            "{} Quality | {} steps | {} CP",
+                            state.get_quality() + initial_quality,
+                            solution.len(),
+                            cp_used(
+                                game_settings,
+                                &SimulationState::new(game_settings),
+                                solution
+                            )
+                            .unwrap_or_default()
+                        ))
+                        .clicked()
+                        && !selected
+                    {
+                        self.actions.clone_from(solution);
+                        self.solver_actions.clone_from(solution);
+                    }
+                }
+            });
+            ui.separator();
+            ui.add(TradeoffPlot::new(
+                game_settings,
+                initial_quality,
+                &self.solutions,
+                &self.solver_actions,
+            ));
+        });
+    }
+
+    fn draw_solve_stats(ui: &mut egui::Ui, stats: SolveStats) {
+        egui::Grid::new("SOLVE_STATS").striped(true).show(ui, |ui| {
+            ui.label("Solve time:");
+            match stats.solve_time {
+                Some(solve_time) => ui.label(format!("{:.2} seconds", solve_time.as_secs_f32())),
+                None => ui.label("(not available on web)"),
+            };
+            ui.end_row();
+            ui.label("Search nodes:");
+            ui.label(stats.search_nodes.to_string());
+            ui.end_row();
+            ui.label("Pareto front entries:");
+            ui.label(stats.pareto_front_entries.to_string());
+            ui.end_row();
+            ui.label("Finish solver states:");
+            ui.label(stats.finish_states.to_string());
+            ui.end_row();
+            ui.label("Quality upper-bound states:");
+            ui.label(stats.quality_upper_bound_states.to_string());
+            ui.end_row();
+            ui.label("Step lower-bound states:");
+            ui.label(stats.step_lower_bound_states.to_string());
+            ui.end_row();
+        });
+        if ui.button("Copy").clicked() {
+            ui.output_mut(|output| output.copied_text = format!("{stats:?}"));
+        }
+    }
+
+    fn draw_gearset_import(&mut self, ui: &mut egui::Ui) {
+        let text_id = Id::new("GEARSET_IMPORT_TEXT");
+        let error_id = Id::new("GEARSET_IMPORT_ERROR");
+        let mut text: String = ui.data(|data| data.get_temp(text_id)).unwrap_or_default();
+        let mut error: Option<&'static str> = ui.data(|data| data.get_temp(error_id)).flatten();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut text)
+                    .hint_text("Craftsmanship: 3858, Control: 4057, CP: 687")
+                    .desired_width(220.0),
+            );
+            if ui.button("Import stats").clicked() {
+                match game_data::parse_gearset(&text) {
+                    Ok(stats) => {
+                        let active_stats = self.crafter_config.active_stats_mut();
+                        active_stats.craftsmanship = stats.craftsmanship;
+                        active_stats.control = stats.control;
+                        active_stats.cp = stats.cp;
+                        text.clear();
+                        error = None;
+                    }
+                    Err(reason) => error = Some(reason),
+                }
+            }
+        });
+        if let Some(reason) = error {
+            ui.label(
+                egui::RichText::new(reason)
+                    .small()
+                    .color(ui.visuals().warn_fg_color),
+            );
+        }
+        ui.data_mut(|data| {
+            data.insert_temp(text_id, text);
+            data.insert_temp(error_id, error);
+        });
+    }
+
+    fn draw_base_value_override(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let was_enabled = self.base_value_override.enabled;
+            ui.checkbox(&mut self.base_value_override.enabled, "Override base values");
+            ui.add(HelpText::new("Enter Progress and Quality per 100% efficiency directly instead of computing them from crafter stats, e.g. to match datamined values."));
+            if self.base_value_override.enabled && !was_enabled {
+                // start from the computed values
+                if let Ok(game_settings) = self.game_settings() {
+                    self.base_value_override.base_progress = game_settings.base_progress;
+                    self.base_value_override.base_quality = game_settings.base_quality;
+                }
+            }
+        });
+        if !self.base_value_override.enabled {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Progress per 100% efficiency:");
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                ui.add(egui::DragValue::new(
+                    &mut self.base_value_override.base_progress,
+                ));
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Quality per 100% efficiency:");
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                ui.add(egui::DragValue::new(
+                    &mut self.base_value_override.base_quality,
+                ));
+            });
+        });
+        ui.label(
+            egui::RichText::new("⚠ Overrides the values computed from crafter stats")
+                .small()
+                .color(ui.visuals().warn_fg_color),
+        );
+    }
+
     fn draw_configuration_widget(&mut self, ui: &mut egui::Ui) {
         ui.vertical(|ui| {
             ui.horizontal(|ui| {
@@ -385,7 +748,7 @@ impl MacroSolverApp {
                     ui.monospace("+");
                     ui.add(
                         egui::DragValue::new(&mut self.crafter_config.active_stats_mut().craftsmanship)
-                            .clamp_range(0..=9999),
+                            .clamp_range(0..=CrafterStats::MAX_CRAFTSMANSHIP),
                     );
                 });
             });
@@ -400,7 +763,7 @@ impl MacroSolverApp {
                         )),
                     );
                     ui.monospace("+");
-                    ui.add(egui::DragValue::new(&mut self.crafter_config.active_stats_mut().control).clamp_range(0..=9999));
+                    ui.add(egui::DragValue::new(&mut self.crafter_config.active_stats_mut().control).clamp_range(0..=CrafterStats::MAX_CONTROL));
                 });
             });
             ui.horizontal(|ui| {
@@ -414,22 +777,24 @@ impl MacroSolverApp {
                         )),
                     );
                     ui.monospace("+");
-                    ui.add(egui::DragValue::new(&mut self.crafter_config.active_stats_mut().cp).clamp_range(0..=9999));
+                    ui.add(egui::DragValue::new(&mut self.crafter_config.active_stats_mut().cp).clamp_range(0..=CrafterStats::MAX_CP));
                 });
             });
             ui.horizontal(|ui| {
                 ui.label("Job Level:");
                 ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                    ui.add(egui::DragValue::new(&mut self.crafter_config.active_stats_mut().level).clamp_range(1..=100));
+                    ui.add(egui::DragValue::new(&mut self.crafter_config.active_stats_mut().level).clamp_range(1..=CrafterStats::MAX_LEVEL));
                 });
             });
+            self.draw_gearset_import(ui);
+            self.draw_base_value_override(ui);
             ui.separator();
 
             ui.label(egui::RichText::new("HQ ingredients").strong());
             let mut has_hq_ingredient = false;
-            let recipe_ingredients = self.recipe_config.recipe.ingredients;
+            let recipe = self.recipe_config.recipe;
             if let QualitySource::HqMaterialList(provided_ingredients) = &mut self.recipe_config.quality_source {
-                for (index, ingredient) in recipe_ingredients.into_iter().enumerate() {
+                for (index, ingredient) in recipe.ingredients_iter() {
                     if let Some(item) = game_data::ITEMS.get(&ingredient.item_id) {
                         if item.can_be_hq {
                             has_hq_ingredient = true;
@@ -453,15 +818,26 @@ impl MacroSolverApp {
             }
             if !has_hq_ingredient {
                 ui.label("None");
+            } else if let QualitySource::HqMaterialList(provided_ingredients) = self.recipe_config.quality_source {
+                ui.horizontal(|ui| {
+                    ui.label("Initial Quality:");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let mut initial_quality = get_initial_quality(recipe, provided_ingredients);
+                        ui.add_enabled(false, egui::DragValue::new(&mut initial_quality));
+                    });
+                });
             }
             ui.separator();
 
             ui.label(egui::RichText::new("Actions").strong());
             if self.crafter_config.active_stats().level >= Action::Manipulation.level_requirement() {
-                ui.add(egui::Checkbox::new(
-                    &mut self.crafter_config.active_stats_mut().manipulation,
-                    format!("Enable {}", action_name(Action::Manipulation, self.locale)),
-                ));
+                ui.horizontal(|ui| {
+                    ui.add(egui::Checkbox::new(
+                        &mut self.crafter_config.active_stats_mut().manipulation,
+                        format!("Enable {}", action_name(Action::Manipulation, self.locale)),
+                    ));
+                    ui.add(HelpText::new("Manipulation is unlocked by a job quest, not by reaching its level.\nUntick this if the crafter hasn't learned it (yet) and the solver won't use it."));
+                });
             } else {
                 ui.add_enabled(
                     false,
@@ -484,6 +860,21 @@ impl MacroSolverApp {
                     egui::Checkbox::new(&mut false, format!("Enable {}", action_name(Action::QuickInnovation, self.locale))),
                 );
             }
+            if self.crafter_config.active_stats().level >= Action::CarefulObservation.level_requirement() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Checkbox::new(&mut self.crafter_config.active_stats_mut().careful_observation, format!("Enable {}", action_name(Action::CarefulObservation, self.locale))));
+                    ui.add(HelpText::new("Specialist action. The solver doesn't use it because it assumes Normal condition on every step."));
+                });
+            } else {
+                ui.add_enabled(
+                    false,
+                    egui::Checkbox::new(&mut false, format!("Enable {}", action_name(Action::CarefulObservation, self.locale))),
+                );
+            }
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.crafter_config.splendorous, "Splendorous tool");
+                ui.add(HelpText::new("Increases the Quality bonus of the Good condition from 50% to 100%.\nHas no effect on the solver, which assumes Normal condition on every step."));
+            });
             ui.separator();
 
             ui.label(egui::RichText::new("Solver settings").strong());
@@ -491,22 +882,22 @@ impl MacroSolverApp {
                 ui.label("Target quality");
                 ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                     ui.style_mut().spacing.item_spacing = [4.0, 4.0].into();
-                    let game_settings = game_data::get_game_settings(
-                        self.recipe_config.recipe,
-                        self.crafter_config.crafter_stats
-                            [self.crafter_config.selected_job as usize],
-                        self.selected_food,
-                        self.selected_potion,
-                        self.solver_config.adversarial,
-                    );
-                    let mut current_value = self
-                        .solver_config
-                        .quality_target
-                        .get_target(game_settings.max_quality);
+                    let max_quality = self
+                        .game_settings()
+                        .map_or(0, |game_settings| game_settings.max_quality);
+                    let mut current_value = self.solver_config.quality_target.get_target(max_quality);
                     match &mut self.solver_config.quality_target {
                         QualityTarget::Custom(value) => {
                             ui.add(egui::DragValue::new(value));
                         }
+                        QualityTarget::HqPercentage(percentage) => {
+                            ui.add(
+                                egui::DragValue::new(percentage)
+                                    .clamp_range(0..=100)
+                                    .suffix("%"),
+                            );
+                            ui.add_enabled(false, egui::DragValue::new(&mut current_value));
+                        }
                         _ => {
                             ui.add_enabled(false, egui::DragValue::new(&mut current_value));
                         }
@@ -543,6 +934,15 @@ impl MacroSolverApp {
                                 &mut self.solver_config.quality_target,
                                 QualityTarget::Custom(current_value),
                                 format!("{}", QualityTarget::Custom(0)),
+                            );
+                            let current_percentage = match max_quality {
+                                0 => 100,
+                                max_quality => game_data::hq_percentage(current_value, max_quality),
+                            };
+                            ui.selectable_value(
+                                &mut self.solver_config.quality_target,
+                                QualityTarget::HqPercentage(current_percentage),
+                                format!("{}", QualityTarget::HqPercentage(0)),
                             )
                         });
                 });
@@ -563,7 +963,8 @@ impl MacroSolverApp {
                 ui.add_enabled(!self.recipe_config.recipe.is_expert, egui::Checkbox::new(
                     &mut self.solver_config.adversarial,
                     "Ensure 100% reliability",
-                ));
+                ))
+                .on_disabled_hover_text("Not available for expert recipes, whose conditions are not modeled by the solver");
                 ui.add(HelpText::new("Find a rotation that can reach the target quality no matter how unlucky the random conditions are.\n  ⊟ May decrease achievable Quality.\n  ⊟ May increase macro duration.\n  ⊟ Much longer solve-time."));
             });
             if self.solver_config.adversarial {
@@ -586,52 +987,221 @@ impl MacroSolverApp {
                 );
             }
 
-            ui.add_space(5.5);
             ui.horizontal(|ui| {
-                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                    if ui.button("Solve").clicked() {
-                        self.actions = Vec::new();
-                        self.solver_pending = true;
-                        self.solver_progress = 0.0;
-                        self.start_time = Some(Instant::now());
-                        let mut game_settings = game_data::get_game_settings(
-                            self.recipe_config.recipe,
-                            self.crafter_config.crafter_stats
-                                [self.crafter_config.selected_job as usize],
-                            self.selected_food,
-                            self.selected_potion,
-                            self.solver_config.adversarial,
-                        );
-                        let target_quality = self
-                            .solver_config
-                            .quality_target
-                            .get_target(game_settings.max_quality);
-                        let initial_quality = match self.recipe_config.quality_source {
-                            QualitySource::HqMaterialList(hq_materials) => get_initial_quality(self.recipe_config.recipe, hq_materials),
-                            QualitySource::Value(quality) => quality,
-                        };
-
-                        ui.ctx().data_mut(|data| {
-                            data.insert_temp(Id::new("LAST_SOLVE_PARAMS"), (game_settings, initial_quality, self.solver_config));
-                        });
+                ui.checkbox(&mut self.solver_config.avoid_combo_actions, "Avoid combo actions");
+                ui.add(HelpText::new("Find a rotation that doesn't depend on combo bonuses (e.g. Basic Touch into Standard Touch), for macro runners that can't keep track of combos.\n  ⊟ May decrease achievable Quality.\n  ⊟ Refined Touch is never used."));
+            });
 
-                        game_settings.max_quality = target_quality.saturating_sub(initial_quality);
+            ui.horizontal(|ui| {
+                ui.label("Excess Quality:");
+                egui::ComboBox::from_id_source("QUALITY_OVERFLOW")
+                    .selected_text(quality_overflow_name(self.solver_config.quality_overflow))
+                    .show_ui(ui, |ui| {
+                        for quality_overflow in [
+                            QualityOverflow::Ignore,
+                            QualityOverflow::Tiebreak,
+                            QualityOverflow::Prefer,
+                        ] {
+                            ui.selectable_value(
+                                &mut self.solver_config.quality_overflow,
+                                quality_overflow,
+                                quality_overflow_name(quality_overflow),
+                            );
+                        }
+                    });
+                ui.add(HelpText::new("How Quality above the target is valued once the target is reached.\n  Ignore: excess Quality is worthless, e.g. for collectables.\n  Tiebreak: only decides between macros of the same length.\n  Prefer: leave a margin for error, even if it takes more steps.\n  ⊟ Prefer may increase macro duration and solve-time."));
+            });
 
-                        self.bridge.send((game_settings, self.solver_config));
+            ui.horizontal(|ui| {
+                let mut limit_steps = self.solver_config.max_steps.is_some();
+                ui.checkbox(&mut limit_steps, "Limit steps");
+                let mut max_steps = self.solver_config.max_steps.unwrap_or(30);
+                ui.add_enabled(
+                    limit_steps,
+                    egui::DragValue::new(&mut max_steps).clamp_range(1..=99),
+                );
+                self.solver_config.max_steps = limit_steps.then_some(max_steps);
+                ui.add(HelpText::new("Only consider macros with at most this many steps.\n  ⊟ May decrease achievable Quality.\n  ⊟ Longer solve-time."));
+            });
 
-                        log::debug!("{game_settings:?}");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.solver_config.minimize_cp, "Minimize CP usage");
+                ui.add(HelpText::new("Among the macros that reach the best Quality, find the one that uses the least CP, e.g. to check whether a cheaper meal or potion is enough.\n  ⊟ Solves several times, multiplying the solve-time."));
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Solutions:");
+                ui.add_enabled(
+                    !self.solver_config.minimize_cp,
+                    egui::DragValue::new(&mut self.solver_config.solution_count)
+                        .clamp_range(1..=5),
+                );
+                ui.add(HelpText::new("Also find alternative macros that use less CP, at the cost of Quality or steps.\n  ⊟ Solves once per solution, multiplying the solve-time."));
+            });
+
+            ui.horizontal(|ui| {
+                let mut limit_time = self.solver_config.time_limit.is_some();
+                ui.checkbox(&mut limit_time, "Time limit");
+                let mut time_limit = self.solver_config.time_limit.unwrap_or(30);
+                ui.add_enabled(
+                    limit_time,
+                    egui::DragValue::new(&mut time_limit).clamp_range(1..=3600).suffix("s"),
+                );
+                self.solver_config.time_limit = limit_time.then_some(time_limit);
+                ui.add(HelpText::new("Stop the solver after this many seconds and use the best macro found so far.\n  ⊟ The macro may not be optimal if the time limit is reached."));
+            });
+        });
+    }
+
+    /// Solve button, progress of the running solve and the result summary.
+    /// Kept separate from the configuration widget, which is disabled while the solver is running.
+    fn draw_solve_controls(&mut self, ui: &mut egui::Ui, initial_quality: u16) {
+        ui.add_space(5.5);
+        ui.horizontal(|ui| {
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                let solve_text = match self.solver_pending {
+                    true => "Solving…",
+                    false => "Solve",
+                };
+                if ui
+                    .add_enabled(!self.solver_pending, egui::Button::new(solve_text))
+                    .on_hover_text("Ctrl+Enter")
+                    .clicked()
+                {
+                    self.start_solver(ui.ctx());
+                }
+                if self.solver_pending {
+                    if ui.button("Cancel").clicked() {
+                        self.cancel_solver(ui.ctx());
                     }
-                    if self.solver_pending {
-                        ui.spinner();
-                        if self.solver_progress != 0.0 {
-                            ui.label(format!("{:.2}%", self.solver_progress * 100.0));
-                        }
-                    } else if let Some(duration) = self.duration {
-                        ui.label(format!("Time: {:.3}s", duration.as_secs_f64()));
+                    ui.spinner();
+                    if self.solver_progress != 0.0 {
+                        ui.label(format!("{:.2}%", self.solver_progress * 100.0));
                     }
-                });
+                    if let Some(best_quality) = self.solver_best_quality {
+                        ui.label(format!(
+                            "Best so far: {} quality",
+                            best_quality + initial_quality
+                        ));
+                    }
+                } else if let Some(duration) = self.duration {
+                    ui.label(format!("Time: {:.3}s", duration.as_secs_f64()));
+                }
             });
         });
+        if self.solver_config.minimize_cp && !self.solver_pending && !self.solver_actions.is_empty()
+        {
+            if let Ok(game_settings) = self.game_settings() {
+                ui.label(format!(
+                    "This macro needs only {} CP, you have {}.",
+                    cp_used(
+                        &game_settings,
+                        &SimulationState::new(&game_settings),
+                        &self.solver_actions
+                    )
+                    .unwrap_or_default(),
+                    game_settings.max_cp
+                ));
+            }
+        }
+        if !self.solver_pending && !self.consumable_suggestion.is_empty() {
+            let names: Vec<String> = self
+                .consumable_suggestion
+                .iter()
+                .map(|consumable| {
+                    let kind = match game_data::MEALS
+                        .iter()
+                        .any(|meal| meal.item_id == consumable.item_id)
+                    {
+                        true => "food",
+                        false => "medicine",
+                    };
+                    format!(
+                        "{} ({kind})",
+                        get_item_name(consumable.item_id, consumable.hq, self.locale)
+                    )
+                })
+                .collect();
+            ui.label(format!("Try {} to finish this.", names.join(" and ")));
+        }
+    }
+
+    /// Stop the running solve and go back to editing, keeping the best intermediate solution.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(unused_variables))]
+    fn cancel_solver(&mut self, ctx: &egui::Context) {
+        self.cancel_flag.cancel();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.bridge.cancel();
+        #[cfg(target_arch = "wasm32")]
+        if !self.cancel_flag.is_shared() {
+            // without shared memory the web worker can't be interrupted mid-solve, so it is replaced
+            // by a fresh one and the events of the old one go to a cell that is no longer read
+            self.data_update = Rc::new(Cell::new(None));
+            self.bridge = Self::initialize_bridge(ctx, &self.data_update);
+        }
+        self.solver_pending = false;
+        self.duration = None;
+    }
+
+    fn start_solver(&mut self, ctx: &egui::Context) {
+        let Ok(mut game_settings) = self.game_settings() else {
+            return;
+        };
+        self.actions = Vec::new();
+        self.solver_actions = Vec::new();
+        self.solutions = Vec::new();
+        self.solver_pending = true;
+        self.solver_progress = 0.0;
+        self.solver_best_quality = None;
+        self.consumable_suggestion.clear();
+        self.suggestion_pending = false;
+        self.start_time = Some(Instant::now());
+        let target_quality = self
+            .solver_config
+            .quality_target
+            .get_target(game_settings.max_quality);
+        let initial_quality = match self.recipe_config.quality_source {
+            QualitySource::HqMaterialList(hq_materials) => {
+                get_initial_quality(self.recipe_config.recipe, hq_materials)
+            }
+            QualitySource::Value(quality) => quality,
+        };
+
+        ctx.data_mut(|data| {
+            data.insert_temp(
+                Id::new("LAST_SOLVE_PARAMS"),
+                (game_settings, initial_quality, self.solver_config),
+            );
+        });
+
+        game_settings.max_quality = target_quality.saturating_sub(initial_quality);
+        if self.solver_config.avoid_combo_actions {
+            game_settings.allowed_actions = game_settings
+                .allowed_actions
+                .remove(Action::ComboStandardTouch)
+                .remove(Action::ComboAdvancedTouch)
+                .remove(Action::ComboRefinedTouch);
+        }
+
+        // the result of a pending bounds job may be dropped together with the events of the previous job
+        ctx.data_mut(|data| {
+            let bounds_id = Id::new("RELAXED_QUALITY_BOUNDS");
+            if let Some((_, None)) =
+                data.get_temp::<(Settings, Option<RelaxedQualityBounds>)>(bounds_id)
+            {
+                data.remove::<(Settings, Option<RelaxedQualityBounds>)>(bounds_id);
+            }
+        });
+
+        self.cancel_flag.cancel();
+        self.cancel_flag = CancelFlag::default();
+        self.bridge.send(WorkerInput::Solve(
+            game_settings,
+            self.solver_config,
+            self.cancel_flag.clone(),
+        ));
+
+        log::debug!("{game_settings:?}");
     }
 
     fn load_fonts(ctx: &egui::Context) {
@@ -1,5 +1,6 @@
 use game_data::{CrafterStats, Recipe};
 use serde::{Deserialize, Serialize};
+use simulator::Settings;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum QualitySource {
@@ -13,10 +14,44 @@ pub struct RecipeConfiguration {
     pub quality_source: QualitySource,
 }
 
+impl RecipeConfiguration {
+    /// Configuration used when no recipe has been saved, see `game_data::default_recipe`.
+    pub fn default_recipe() -> Option<Self> {
+        game_data::default_recipe(game_data::RECIPES).map(|recipe| Self {
+            recipe,
+            quality_source: QualitySource::HqMaterialList([0; 6]),
+        })
+    }
+}
+
+/// Manually entered Progress and Quality per 100% efficiency, replacing the values computed from the crafter's stats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BaseValueOverride {
+    pub enabled: bool,
+    pub base_progress: u16,
+    pub base_quality: u16,
+}
+
+impl BaseValueOverride {
+    pub fn apply(self, settings: Settings) -> Settings {
+        match self.enabled {
+            true => Settings {
+                base_progress: self.base_progress,
+                base_quality: self.base_quality,
+                ..settings
+            },
+            false => settings,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct CrafterConfig {
     pub selected_job: u8,
     pub crafter_stats: [CrafterStats; 8],
+    /// Splendorous tools, passed to the simulator as `Condition::good(splendorous)`
+    #[serde(default)]
+    pub splendorous: bool,
 }
 
 impl CrafterConfig {
@@ -34,18 +69,21 @@ impl Default for CrafterConfig {
         Self {
             selected_job: 1,
             crafter_stats: Default::default(),
+            splendorous: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QualityTarget {
     Zero,
     CollectableT1,
     CollectableT2,
     CollectableT3,
+    #[default]
     Full,
     Custom(u16),
+    HqPercentage(u8),
 }
 
 impl QualityTarget {
@@ -57,16 +95,13 @@ impl QualityTarget {
             Self::CollectableT3 => (max_quality as f64 * 0.95).ceil() as u16,
             Self::Full => max_quality,
             Self::Custom(quality) => quality,
+            Self::HqPercentage(percentage) => {
+                game_data::quality_for_hq_percentage(percentage, max_quality)
+            }
         }
     }
 }
 
-impl Default for QualityTarget {
-    fn default() -> Self {
-        Self::Full
-    }
-}
-
 impl std::fmt::Display for QualityTarget {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -79,6 +114,7 @@ impl std::fmt::Display for QualityTarget {
                 Self::CollectableT3 => "95% quality",
                 Self::Full => "100% quality",
                 Self::Custom(_) => "Custom",
+                Self::HqPercentage(_) => "HQ chance",
             }
         )
     }
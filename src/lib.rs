@@ -2,6 +2,8 @@
 
 mod app;
 pub use app::MacroSolverApp;
+#[cfg(target_arch = "wasm32")]
+pub use worker::Codec as WorkerCodec;
 pub use worker::Worker;
 
 mod config;
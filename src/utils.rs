@@ -1,3 +1,7 @@
+use std::collections::VecDeque;
+
+use simulator::Action;
+
 pub fn contains_noncontiguous(string: &str, pattern: &str) -> bool {
     let mut it = string.split_whitespace();
     for c in pattern.split_whitespace() {
@@ -12,3 +16,50 @@ pub fn contains_noncontiguous(string: &str, pattern: &str) -> bool {
     }
     true
 }
+
+/// Bounded undo/redo history of the action list.
+pub struct ActionHistory {
+    undo_stack: VecDeque<Vec<Action>>,
+    redo_stack: Vec<Vec<Action>>,
+    current: Vec<Action>,
+}
+
+impl ActionHistory {
+    const MAX_ENTRIES: usize = 50;
+
+    pub fn new(actions: &[Action]) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            current: actions.to_vec(),
+        }
+    }
+
+    /// Adds `actions` to the history if they differ from the most recently recorded actions.
+    /// Recording a new entry discards everything that could be redone.
+    pub fn record(&mut self, actions: &[Action]) {
+        if self.current == actions {
+            return;
+        }
+        if self.undo_stack.len() == Self::MAX_ENTRIES {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack
+            .push_back(std::mem::replace(&mut self.current, actions.to_vec()));
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self) -> Option<Vec<Action>> {
+        let actions = self.undo_stack.pop_back()?;
+        self.redo_stack
+            .push(std::mem::replace(&mut self.current, actions.clone()));
+        Some(actions)
+    }
+
+    pub fn redo(&mut self) -> Option<Vec<Action>> {
+        let actions = self.redo_stack.pop()?;
+        self.undo_stack
+            .push_back(std::mem::replace(&mut self.current, actions.clone()));
+        Some(actions)
+    }
+}
@@ -3,7 +3,9 @@ use gloo_worker::Registrable;
 
 #[cfg(target_arch = "wasm32")]
 fn main() {
-    raphael_xiv::Worker::registrar().register();
+    raphael_xiv::Worker::registrar()
+        .encoding::<raphael_xiv::WorkerCodec>()
+        .register();
 }
 
 #[cfg(not(target_arch = "wasm32"))]
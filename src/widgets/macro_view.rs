@@ -1,7 +1,10 @@
 use egui::{Align, Id, Layout, Widget};
-use game_data::{action_name, Locale};
+use game_data::{
+    action_name, estimated_realtime_seconds, reference_rotations, to_teamcraft_rotation,
+    to_trace_csv, Locale, DEFAULT_ACTION_OVERHEAD_MS, TEAMCRAFT_SIMULATOR_URL,
+};
 use serde::{Deserialize, Serialize};
-use simulator::Action;
+use simulator::{Action, Settings, SimulationState};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct MacroViewConfig {
@@ -14,7 +17,15 @@ pub struct MacroViewConfig {
     #[serde(default)]
     notification_sound: u8,
     #[serde(default)]
+    notification_final_only: bool,
+    #[serde(default)]
     macro_lock: bool,
+    #[serde(default = "default_action_overhead_ms")]
+    action_overhead_ms: u16,
+}
+
+fn default_action_overhead_ms() -> u16 {
+    DEFAULT_ACTION_OVERHEAD_MS
 }
 
 impl Default for MacroViewConfig {
@@ -24,7 +35,9 @@ impl Default for MacroViewConfig {
             include_delay: true,
             notification_enabled: false,
             notification_sound: 1,
+            notification_final_only: false,
             macro_lock: false,
+            action_overhead_ms: DEFAULT_ACTION_OVERHEAD_MS,
         }
     }
 }
@@ -57,7 +70,7 @@ impl MacroTextBox {
                 format!("/ac \"{}\"", action_name(*action, locale))
             }
         }));
-        if config.notification_enabled {
+        if config.notification_enabled && (!config.notification_final_only || index == max_index) {
             lines.push(format!(
                 "/echo Macro finished ({}/{}) <se.{}>",
                 index, max_index, config.notification_sound
@@ -72,29 +85,70 @@ impl MacroTextBox {
 impl Widget for MacroTextBox {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
         let id = Id::new(&self.text);
-        ui.group(|ui| {
-            ui.horizontal_top(|ui| {
-                ui.monospace(&self.text);
-                ui.with_layout(Layout::right_to_left(Align::TOP), |ui| {
-                    if ui.ctx().animate_bool_with_time(id, false, 2.0) == 0.0 {
-                        if ui.button("Copy").clicked() {
-                            ui.output_mut(|output| output.copied_text = self.text);
-                            ui.ctx().animate_bool_with_time(id, true, 0.0);
+        let mut copy_clicked = false;
+        let response = ui
+            .group(|ui| {
+                ui.horizontal_top(|ui| {
+                    ui.monospace(&self.text);
+                    ui.with_layout(Layout::right_to_left(Align::TOP), |ui| {
+                        if ui.ctx().animate_bool_with_time(id, false, 2.0) == 0.0 {
+                            copy_clicked = ui.button("Copy").clicked();
+                        } else {
+                            ui.add_enabled(false, egui::Button::new("Copied"));
                         }
-                    } else {
-                        ui.add_enabled(false, egui::Button::new("Copied"));
-                    }
+                    });
                 });
-            });
-        })
-        .response
+            })
+            .response;
+        // Ctrl+C while hovering over the macro also copies it
+        let copy_shortcut = response.contains_pointer()
+            && !ui.ctx().wants_keyboard_input()
+            && ui.input(|input| input.events.contains(&egui::Event::Copy));
+        if copy_clicked || copy_shortcut {
+            ui.output_mut(|output| output.copied_text = self.text);
+            ui.ctx().animate_bool_with_time(id, true, 0.0);
+        }
+        response
+    }
+}
+
+/// Split the actions into in-game macros of at most 15 lines, accounting for the lines taken up by
+/// the macro lock and the end-of-macro notification.
+fn split_macro<'a>(actions: &'a [Action], config: &MacroViewConfig) -> Vec<&'a [Action]> {
+    if !config.split_macro {
+        return actions.chunks(usize::MAX).collect();
+    }
+    let mut chunk_size = 15;
+    if config.macro_lock {
+        chunk_size -= 1;
+    }
+    if !config.notification_enabled {
+        return actions.chunks(chunk_size).collect();
+    }
+    if !config.notification_final_only {
+        return actions.chunks(chunk_size - 1).collect();
+    }
+    let mut chunks: Vec<&[Action]> = actions.chunks(chunk_size).collect();
+    // the last macro must leave room for the notification
+    if let Some(last_chunk) = chunks.pop() {
+        match last_chunk.split_last() {
+            Some((last_action, remaining)) if last_chunk.len() == chunk_size => {
+                chunks.push(remaining);
+                chunks.push(std::slice::from_ref(last_action));
+            }
+            _ => chunks.push(last_chunk),
+        }
     }
+    chunks
 }
 
 pub struct MacroView<'a> {
     actions: &'a mut Vec<Action>,
     config: &'a mut MacroViewConfig,
     locale: Locale,
+    /// Settings the macro is replayed with when exporting its trace, `None` if they are invalid.
+    settings: Option<Settings>,
+    initial_quality: u16,
 }
 
 impl<'a> MacroView<'a> {
@@ -102,11 +156,15 @@ impl<'a> MacroView<'a> {
         actions: &'a mut Vec<Action>,
         config: &'a mut MacroViewConfig,
         locale: Locale,
+        settings: Option<Settings>,
+        initial_quality: u16,
     ) -> Self {
         Self {
             actions,
             config,
             locale,
+            settings,
+            initial_quality,
         }
     }
 }
@@ -124,6 +182,51 @@ impl<'a> Widget for MacroView<'a> {
                         {
                             self.actions.clear();
                         }
+                        ui.menu_button("Load", |ui| {
+                            for rotation in reference_rotations() {
+                                let hover_text = format!(
+                                    "Level {}+, {} durability, {} CP",
+                                    rotation.level, rotation.durability, rotation.cp
+                                );
+                                if ui.button(rotation.name).on_hover_text(hover_text).clicked() {
+                                    *self.actions = rotation.actions.to_vec();
+                                    ui.close_menu();
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Replace the macro with a reference rotation");
+                        ui.hyperlink_to("Open in Teamcraft", TEAMCRAFT_SIMULATOR_URL)
+                            .on_hover_text("Paste the copied rotation into Teamcraft's \"Import rotation\" dialog");
+                        if ui
+                            .add_enabled(
+                                !self.actions.is_empty(),
+                                egui::Button::new("Copy for Teamcraft"),
+                            )
+                            .clicked()
+                        {
+                            ui.output_mut(|output| {
+                                output.copied_text = to_teamcraft_rotation(self.actions)
+                            });
+                        }
+                        if let Some(settings) = self.settings.as_ref() {
+                            if ui
+                                .add_enabled(
+                                    !self.actions.is_empty(),
+                                    egui::Button::new("Export trace CSV"),
+                                )
+                                .on_hover_text("Copy the state after each step as CSV, for spreadsheets")
+                                .clicked()
+                            {
+                                let csv = to_trace_csv(
+                                    settings,
+                                    &SimulationState::new(settings),
+                                    self.initial_quality,
+                                    self.actions,
+                                );
+                                ui.output_mut(|output| output.copied_text = csv);
+                            }
+                        }
                         ui.label(format!(
                             "{} steps | {} seconds",
                             self.actions.len(),
@@ -146,6 +249,7 @@ impl<'a> Widget for MacroView<'a> {
                         "End-of-macro notification",
                     ));
                     ui.add_enabled_ui(self.config.notification_enabled, |ui| {
+                        ui.checkbox(&mut self.config.notification_final_only, "Last macro only");
                         egui::ComboBox::from_id_source("SOUND_EFFECT")
                             .selected_text(format!("<se.{}>", self.config.notification_sound))
                             .show_ui(ui, |ui| {
@@ -160,25 +264,35 @@ impl<'a> Widget for MacroView<'a> {
                     });
                 });
                 ui.separator();
-                let chunk_size = match self.config.split_macro {
-                    true => {
-                        let mut chunk_size = 15;
-                        if self.config.notification_enabled {
-                            chunk_size -= 1;
-                        }
-                        if self.config.macro_lock {
-                            chunk_size -= 1;
-                        }
-                        chunk_size
-                    }
-                    false => usize::MAX,
-                };
-                let count = self.actions.chunks(chunk_size).count();
+                let chunks = split_macro(self.actions, self.config);
+                let count = chunks.len();
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} {}, ~{:.0} seconds in-game",
+                        count,
+                        if count == 1 { "macro" } else { "macros" },
+                        estimated_realtime_seconds(
+                            self.actions,
+                            count,
+                            self.config.action_overhead_ms
+                        )
+                    ));
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.config.action_overhead_ms)
+                                .clamp_range(0..=5000)
+                                .suffix(" ms"),
+                        )
+                        .on_hover_text("Delay the game adds to every action, depending on your latency");
+                        ui.label("Action delay:");
+                    });
+                });
+                ui.separator();
                 let newline = match ui.ctx().os() {
                     egui::os::OperatingSystem::Mac => "\n",
                     _ => "\r\n",
                 };
-                for (index, actions) in self.actions.chunks(chunk_size).enumerate() {
+                for (index, actions) in chunks.into_iter().enumerate() {
                     ui.add(MacroTextBox::new(
                         index + 1,
                         count,
@@ -18,3 +18,9 @@ pub use stats_edit::StatsEdit;
 
 mod help_text;
 pub use help_text::HelpText;
+
+mod solver_diff;
+pub use solver_diff::SolverDiff;
+
+mod tradeoff_plot;
+pub use tradeoff_plot::TradeoffPlot;
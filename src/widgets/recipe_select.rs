@@ -1,10 +1,14 @@
+use std::sync::Arc;
+
 use egui::{
     util::cache::{ComputerMut, FrameCache},
     Align, Id, Layout, Widget,
 };
 use egui_extras::Column;
 use game_data::{
-    get_game_settings, get_item_name, get_job_name, Consumable, Ingredient, Locale, RLVLS,
+    closest_recipe_name, clvl_to_rlvl, expansion_for_rlvl, get_game_settings, get_item_name,
+    get_job_name, get_recipes_for_item, rlvl_to_clvl, Consumable, Expansion, Ingredient, Locale,
+    Recipe, RLVLS,
 };
 
 use crate::{
@@ -12,26 +16,96 @@ use crate::{
     utils::contains_noncontiguous,
 };
 
+/// Native builds load the item icons from the bundled `assets/item-icons` folder (see `fetch-item-icons.sh`).
+/// Items whose icon isn't bundled are shown without one instead of falling back to the network.
+#[cfg(not(target_arch = "wasm32"))]
+fn item_icon_uri(item_id: u32) -> Option<String> {
+    let path = format!("assets/item-icons/{}", game_data::item_icon_path(item_id)?);
+    std::path::Path::new(&path)
+        .exists()
+        .then(|| format!("file://./{path}"))
+}
+
+/// The web version fetches the item icons from XIVAPI.
+#[cfg(target_arch = "wasm32")]
+fn item_icon_uri(item_id: u32) -> Option<String> {
+    game_data::item_icon_url(item_id)
+}
+
+/// Indices into `RECIPES` of the recipes that match a search, also grouped by expansion (latest expansion first).
 #[derive(Default)]
-struct RecipeFinder {}
-
-impl ComputerMut<(&str, Locale), Vec<usize>> for RecipeFinder {
-    fn compute(&mut self, (text, locale): (&str, Locale)) -> Vec<usize> {
-        game_data::RECIPES
-            .iter()
-            .enumerate()
-            .filter_map(|(index, recipe)| {
-                let item_name = get_item_name(recipe.item_id, false, locale);
-                match contains_noncontiguous(&item_name.to_lowercase(), text) {
-                    true => Some(index),
-                    false => None,
-                }
+struct SearchResult {
+    recipes: Vec<usize>,
+    by_expansion: Vec<(Expansion, Vec<usize>)>,
+}
+
+impl SearchResult {
+    fn new(recipes: Vec<usize>) -> Self {
+        let by_expansion = Expansion::ALL
+            .into_iter()
+            .rev()
+            .map(|expansion| {
+                let group: Vec<usize> = recipes
+                    .iter()
+                    .copied()
+                    .filter(|index| {
+                        expansion_for_rlvl(game_data::RECIPES[*index].recipe_level)
+                            == Some(expansion)
+                    })
+                    .collect();
+                (expansion, group)
+            })
+            .filter(|(_, group)| !group.is_empty())
+            .collect();
+        Self {
+            recipes,
+            by_expansion,
+        }
+    }
+}
+
+#[derive(Default)]
+struct RecipeFinder {
+    last_search: Option<(String, Locale, Arc<SearchResult>)>,
+}
+
+impl ComputerMut<(&str, Locale), Arc<SearchResult>> for RecipeFinder {
+    fn compute(&mut self, (text, locale): (&str, Locale)) -> Arc<SearchResult> {
+        // typing only ever narrows down the search, so the previous result can be filtered instead of all recipes
+        // (a subsequence of the longer text is a subsequence of its prefix as well)
+        let candidates: Vec<usize> = match &self.last_search {
+            Some((last_text, last_locale, last_result))
+                if *last_locale == locale && text.starts_with(last_text.as_str()) =>
+            {
+                last_result.recipes.clone()
+            }
+            _ => (0..game_data::RECIPES.len()).collect(),
+        };
+        let recipes = candidates
+            .into_iter()
+            .filter(|index| {
+                let item_name = get_item_name(game_data::RECIPES[*index].item_id, false, locale);
+                contains_noncontiguous(&item_name.to_lowercase(), text)
             })
-            .collect()
+            .collect();
+        let result = Arc::new(SearchResult::new(recipes));
+        self.last_search = Some((text.to_string(), locale, result.clone()));
+        result
     }
 }
 
-type SearchCache<'a> = FrameCache<Vec<usize>, RecipeFinder>;
+type SearchCache<'a> = FrameCache<Arc<SearchResult>, RecipeFinder>;
+
+#[derive(Default)]
+struct RecipeNameSuggester {}
+
+impl ComputerMut<(&str, Locale), Option<&'static str>> for RecipeNameSuggester {
+    fn compute(&mut self, (text, locale): (&str, Locale)) -> Option<&'static str> {
+        closest_recipe_name(text, locale)
+    }
+}
+
+type SuggestionCache<'a> = FrameCache<Option<&'static str>, RecipeNameSuggester>;
 
 pub struct RecipeSelect<'a> {
     crafter_config: &'a mut CrafterConfig,
@@ -58,7 +132,109 @@ impl<'a> RecipeSelect<'a> {
         }
     }
 
-    fn draw_normal_recipe_select(self, ui: &mut egui::Ui) {
+    fn draw_recipe_stats(&self, ui: &mut egui::Ui) {
+        let recipe = self.recipe_config.recipe;
+        let Ok(game_settings) = get_game_settings(
+            recipe,
+            *self.crafter_config.active_stats(),
+            self.selected_food,
+            self.selected_potion,
+            false,
+        ) else {
+            return;
+        };
+        egui::CollapsingHeader::new("Recipe stats")
+            .id_source("RECIPE_STATS")
+            .show(ui, |ui| {
+                egui::Grid::new("RECIPE_STATS_GRID")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.label(egui::RichText::new("Recipe").strong());
+                        ui.label(egui::RichText::new("Simulator").strong());
+                        ui.end_row();
+                        ui.label("Level");
+                        ui.label(recipe.level.to_string());
+                        ui.label(game_settings.job_level.to_string());
+                        ui.end_row();
+                        ui.label("Recipe Level");
+                        match rlvl_to_clvl(recipe.recipe_level) {
+                            Some(clvl) => {
+                                ui.label(format!("{} (Lv. {})", recipe.recipe_level, clvl))
+                            }
+                            None => ui.label(recipe.recipe_level.to_string()),
+                        };
+                        ui.label("");
+                        ui.end_row();
+                        ui.label("Expansion");
+                        ui.label(
+                            expansion_for_rlvl(recipe.recipe_level)
+                                .map_or(String::new(), |expansion| expansion.to_string()),
+                        );
+                        ui.label("");
+                        ui.end_row();
+                        ui.label("Progress");
+                        ui.label(recipe.progress.to_string());
+                        ui.label(game_settings.max_progress.to_string());
+                        ui.end_row();
+                        ui.label("Quality");
+                        ui.label(recipe.quality.to_string());
+                        ui.label(game_settings.max_quality.to_string());
+                        ui.end_row();
+                        ui.label("Durability");
+                        ui.label(recipe.durability.to_string());
+                        ui.label(game_settings.max_durability.to_string());
+                        ui.end_row();
+                        ui.label("Expert recipe");
+                        ui.label(if recipe.is_expert { "Yes" } else { "No" });
+                        ui.label("");
+                        ui.end_row();
+                        ui.label("Progress per 100% efficiency");
+                        ui.label("");
+                        ui.label(game_settings.base_progress.to_string());
+                        ui.end_row();
+                        ui.label("Quality per 100% efficiency");
+                        ui.label("");
+                        ui.label(game_settings.base_quality.to_string());
+                        ui.end_row();
+                    });
+            });
+    }
+
+    /// Switch between the recipes of the selected item if it can be crafted by more than one job
+    fn draw_alternative_recipes(&mut self, ui: &mut egui::Ui) {
+        let selected_recipe = self.recipe_config.recipe;
+        let alternatives: Vec<Recipe> = get_recipes_for_item(selected_recipe.item_id)
+            .copied()
+            .collect();
+        if alternatives.len() < 2 {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Also craftable by:");
+            for recipe in alternatives {
+                let selected = recipe.job_id == selected_recipe.job_id;
+                if ui
+                    .selectable_label(selected, get_job_name(recipe.job_id, self.locale))
+                    .clicked()
+                    && !selected
+                {
+                    self.crafter_config.selected_job = recipe.job_id;
+                    *self.recipe_config = RecipeConfiguration {
+                        recipe,
+                        quality_source: QualitySource::HqMaterialList([0; 6]),
+                    };
+                }
+            }
+        });
+    }
+
+    fn draw_normal_recipe_select(mut self, ui: &mut egui::Ui) {
+        self.draw_recipe_stats(ui);
+        self.draw_alternative_recipes(ui);
+        ui.separator();
+
         let mut search_text = String::new();
         ui.ctx().data_mut(|data| {
             if let Some(text) = data.get_persisted::<String>(Id::new("RECIPE_SEARCH_TEXT")) {
@@ -66,22 +242,74 @@ impl<'a> RecipeSelect<'a> {
             }
         });
 
+        let mut group_by_expansion = true;
+        ui.ctx().data_mut(|data| {
+            if let Some(value) = data.get_persisted::<bool>(Id::new("RECIPE_GROUP_BY_EXPANSION")) {
+                group_by_expansion = value;
+            }
+        });
+
         ui.horizontal(|ui| {
             ui.label("Search:");
-            ui.text_edit_singleline(&mut search_text);
+            let response = ui.text_edit_singleline(&mut search_text);
+            // "/" focuses the search box
+            if !ui.ctx().wants_keyboard_input()
+                && ui.input_mut(|input| input.consume_key(egui::Modifiers::NONE, egui::Key::Slash))
+            {
+                response.request_focus();
+            }
+            ui.checkbox(&mut group_by_expansion, "Group by expansion");
         });
         ui.separator();
 
-        let mut search_result = Vec::new();
+        let mut search_result = Arc::default();
         ui.ctx().memory_mut(|mem| {
             let search_cache = mem.caches.cache::<SearchCache<'_>>();
             search_result = search_cache.get((&search_text.to_lowercase(), self.locale));
         });
 
+        if search_result.recipes.is_empty() && !search_text.trim().is_empty() {
+            let mut suggestion = None;
+            ui.ctx().memory_mut(|mem| {
+                let suggestion_cache = mem.caches.cache::<SuggestionCache<'_>>();
+                suggestion = suggestion_cache.get((&search_text, self.locale));
+            });
+            ui.label(format!("No recipes match \"{}\"", search_text.trim()));
+            if let Some(item_name) = suggestion {
+                if ui
+                    .button(format!("Did you mean \"{}\"?", item_name))
+                    .clicked()
+                {
+                    search_text = item_name.to_string();
+                }
+            }
+        }
+
         ui.ctx().data_mut(|data| {
             data.insert_persisted(Id::new("RECIPE_SEARCH_TEXT"), search_text);
+            data.insert_persisted(Id::new("RECIPE_GROUP_BY_EXPANSION"), group_by_expansion);
         });
 
+        if group_by_expansion {
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    for (index, (expansion, group)) in search_result.by_expansion.iter().enumerate()
+                    {
+                        egui::CollapsingHeader::new(format!("{} ({})", expansion, group.len()))
+                            .id_source(("RECIPE_EXPANSION", expansion))
+                            .default_open(index == 0)
+                            .show(ui, |ui| {
+                                self.draw_recipe_table(ui, group, false);
+                            });
+                    }
+                });
+        } else {
+            self.draw_recipe_table(ui, &search_result.recipes, true);
+        }
+    }
+
+    fn draw_recipe_table(&mut self, ui: &mut egui::Ui, recipes: &[usize], vscroll: bool) {
         let text_height = egui::TextStyle::Body
             .resolve(ui.style())
             .size
@@ -90,14 +318,16 @@ impl<'a> RecipeSelect<'a> {
             .auto_shrink(false)
             .striped(true)
             .resizable(false)
+            .vscroll(vscroll)
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
             .column(Column::auto())
             .column(Column::exact(28.0)) // Column::auto causes jittering when scrolling
+            .column(Column::exact(text_height))
             .column(Column::remainder())
             .min_scrolled_height(0.0);
         table.body(|body| {
-            body.rows(text_height, search_result.len(), |mut row| {
-                let recipe = game_data::RECIPES[search_result[row.index()]];
+            body.rows(text_height, recipes.len(), |mut row| {
+                let recipe = game_data::RECIPES[recipes[row.index()]];
                 row.col(|ui| {
                     if ui.button("Select").clicked() {
                         self.crafter_config.selected_job = recipe.job_id;
@@ -110,6 +340,16 @@ impl<'a> RecipeSelect<'a> {
                 row.col(|ui| {
                     ui.label(get_job_name(recipe.job_id, self.locale));
                 });
+                row.col(|ui| {
+                    // icons are loaded and cached by the image loaders, and only for visible rows
+                    if let Some(icon_url) = item_icon_uri(recipe.item_id) {
+                        ui.add(
+                            egui::Image::new(icon_url)
+                                .fit_to_exact_size(egui::Vec2::splat(text_height))
+                                .rounding(2.0),
+                        );
+                    }
+                });
                 row.col(|ui| {
                     ui.label(get_item_name(recipe.item_id, false, self.locale));
                 });
@@ -125,29 +365,45 @@ impl<'a> RecipeSelect<'a> {
             amount: 0,
         }; 6];
 
-        let game_settings = get_game_settings(
+        let (base_progress, base_quality) = match get_game_settings(
             self.recipe_config.recipe,
             *self.crafter_config.active_stats(),
             self.selected_food,
             self.selected_potion,
             false,
-        );
+        ) {
+            Ok(game_settings) => (
+                game_settings.base_progress.to_string(),
+                game_settings.base_quality.to_string(),
+            ),
+            Err(_) => ("-".to_string(), "-".to_string()),
+        };
 
         ui.horizontal_top(|ui| {
             ui.vertical(|ui| {
                 ui.horizontal(|ui| {
                     ui.label("Level:");
-                    ui.add(
+                    let response = ui.add(
                         egui::DragValue::new(&mut self.recipe_config.recipe.level)
                             .clamp_range(1..=100),
                     );
+                    if response.changed() {
+                        self.recipe_config.recipe.recipe_level =
+                            clvl_to_rlvl(self.recipe_config.recipe.level);
+                    }
                 });
                 ui.horizontal(|ui| {
                     ui.label("Recipe Level:");
-                    ui.add(
+                    let response = ui.add(
                         egui::DragValue::new(&mut self.recipe_config.recipe.recipe_level)
                             .clamp_range(1..=RLVLS.len() - 1),
                     );
+                    // keep the class level in sync so that the level-difference modifiers match the game
+                    if response.changed() {
+                        if let Some(clvl) = rlvl_to_clvl(self.recipe_config.recipe.recipe_level) {
+                            self.recipe_config.recipe.level = clvl;
+                        }
+                    }
                 });
                 ui.horizontal(|ui| {
                     ui.label("Progress:");
@@ -200,11 +456,11 @@ impl<'a> RecipeSelect<'a> {
                 });
                 ui.horizontal(|ui| {
                     ui.label("Progress per 100% efficiency:");
-                    ui.label(egui::RichText::new(game_settings.base_progress.to_string()).strong());
+                    ui.label(egui::RichText::new(base_progress).strong());
                 });
                 ui.horizontal(|ui| {
                     ui.label("Quality per 100% efficiency:");
-                    ui.label(egui::RichText::new(game_settings.base_quality.to_string()).strong());
+                    ui.label(egui::RichText::new(base_quality).strong());
                 });
             });
         });
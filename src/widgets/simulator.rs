@@ -1,6 +1,7 @@
 use egui::{Align, Color32, Id, Layout, Rounding, Widget};
-use game_data::{action_name, get_job_name, Item, Locale};
-use simulator::{Action, Settings, SimulationState};
+use game_data::{action_name, cp_used, get_job_name, Locale, Recipe, ITEMS};
+use serde::Serialize;
+use simulator::{Action, Condition, Settings, SimulationState, StepBreakdown};
 
 use crate::{
     app::SolverConfig,
@@ -14,13 +15,21 @@ const BASE_ASSET_PATH: &str = env!("BASE_URL");
 #[cfg(not(target_arch = "wasm32"))]
 const BASE_ASSET_PATH: &str = "file://./assets";
 
+/// Everything needed to reproduce a simulation, e.g. in a test
+#[derive(Serialize)]
+struct DebugState<'a> {
+    settings: &'a Settings,
+    initial_quality: u16,
+    actions: &'a [Action],
+}
+
 pub struct Simulator<'a> {
     settings: &'a Settings,
     initial_quality: u16,
     solver_config: SolverConfig,
     crafter_config: &'a CrafterConfig,
-    actions: &'a [Action],
-    item: &'a Item,
+    actions: &'a mut Vec<Action>,
+    recipe: &'a Recipe,
     locale: Locale,
 }
 
@@ -30,8 +39,8 @@ impl<'a> Simulator<'a> {
         initial_quality: u16,
         solver_config: SolverConfig,
         crafter_config: &'a CrafterConfig,
-        actions: &'a [Action],
-        item: &'a Item,
+        actions: &'a mut Vec<Action>,
+        recipe: &'a Recipe,
         locale: Locale,
     ) -> Self {
         Self {
@@ -40,7 +49,7 @@ impl<'a> Simulator<'a> {
             solver_config,
             crafter_config,
             actions,
-            item,
+            recipe,
             locale,
         }
     }
@@ -48,8 +57,19 @@ impl<'a> Simulator<'a> {
 
 impl<'a> Widget for Simulator<'a> {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
-        let (game_state, errors) =
+        // when stepping through the macro, only the first `step` actions are simulated
+        let mut step = ui
+            .ctx()
+            .data(|data| data.get_temp::<Option<usize>>(Id::new("SIMULATION_STEP")))
+            .flatten()
+            .map(|step| step.min(self.actions.len()));
+        let (mut game_state, errors) =
             SimulationState::from_macro_continue_on_error(self.settings, self.actions);
+        if let Some(step) = step {
+            game_state =
+                SimulationState::from_macro_continue_on_error(self.settings, &self.actions[..step])
+                    .0;
+        }
 
         let max_progress = self.settings.max_progress;
         let progress = game_state.progress;
@@ -57,6 +77,8 @@ impl<'a> Widget for Simulator<'a> {
         let max_quality = self.settings.max_quality;
         let quality = game_state.get_quality() + self.initial_quality;
 
+        let item = ITEMS.get(&self.recipe.item_id).unwrap();
+
         let prog_qual_dbg_text = format!(
             "Progress per 100% efficiency: {}\nQuality per 100% efficiency: {}",
             self.settings.base_progress, self.settings.base_quality
@@ -64,13 +86,12 @@ impl<'a> Widget for Simulator<'a> {
 
         let mut config_changed_warning = false;
         ui.ctx().data(|data| {
-            match data.get_temp::<(Settings, u16, SolverConfig)>(Id::new("LAST_SOLVE_PARAMS")) {
-                Some((settings, initial_quality, solver_config)) => {
-                    config_changed_warning = settings != *self.settings
-                        || initial_quality != self.initial_quality
-                        || solver_config != self.solver_config;
-                }
-                None => (),
+            if let Some((settings, initial_quality, solver_config)) =
+                data.get_temp::<(Settings, u16, SolverConfig)>(Id::new("LAST_SOLVE_PARAMS"))
+            {
+                config_changed_warning = settings != *self.settings
+                    || initial_quality != self.initial_quality
+                    || solver_config != self.solver_config;
             }
         });
         if self.actions.is_empty() {
@@ -83,6 +104,20 @@ impl<'a> Widget for Simulator<'a> {
                     ui.horizontal(|ui| {
                         ui.label(egui::RichText::new("Simulation").strong());
                         ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            if ui
+                                .small_button("Copy debug state")
+                                .on_hover_text("Copy the settings and actions for a bug report")
+                                .clicked()
+                            {
+                                let debug_state = DebugState {
+                                    settings: self.settings,
+                                    initial_quality: self.initial_quality,
+                                    actions: self.actions,
+                                };
+                                if let Ok(text) = serde_json::to_string_pretty(&debug_state) {
+                                    ui.output_mut(|output| output.copied_text = text);
+                                }
+                            }
                             ui.add_visible(
                                 config_changed_warning,
                                 egui::Label::new(
@@ -102,12 +137,20 @@ impl<'a> Widget for Simulator<'a> {
                         if progress >= max_progress {
                             text.push_str(&format!("  (+{} overflow)", progress - max_progress));
                         }
-                        ui.add(
-                            egui::ProgressBar::new(progress as f32 / max_progress as f32)
-                                .text(text)
-                                .rounding(Rounding::ZERO),
-                        )
-                        .on_hover_text_at_pointer(&prog_qual_dbg_text);
+                        // the text also changes so that the state doesn't rely on color alone
+                        let mut progress_bar =
+                            egui::ProgressBar::new(progress as f32 / max_progress as f32);
+                        if game_state.is_final(self.settings) {
+                            if progress >= max_progress {
+                                text.push_str("  ✔ Completed");
+                                progress_bar = progress_bar.fill(Color32::DARK_GREEN);
+                            } else {
+                                text.push_str("  ✖ Failed");
+                                progress_bar = progress_bar.fill(Color32::DARK_RED);
+                            }
+                        }
+                        ui.add(progress_bar.text(text).rounding(Rounding::ZERO))
+                            .on_hover_text_at_pointer(&prog_qual_dbg_text);
                     });
                     ui.horizontal(|ui| {
                         ui.label("Quality:");
@@ -115,12 +158,20 @@ impl<'a> Widget for Simulator<'a> {
                         if quality >= max_quality {
                             text.push_str(&format!("  (+{} overflow)", quality - max_quality));
                         }
-                        ui.add(
-                            egui::ProgressBar::new(quality as f32 / max_quality as f32)
-                                .text(text)
-                                .rounding(Rounding::ZERO),
-                        )
-                        .on_hover_text_at_pointer(&prog_qual_dbg_text);
+                        let mut quality_bar =
+                            egui::ProgressBar::new(quality as f32 / max_quality as f32);
+                        if item.can_be_hq && !item.is_collectable && max_quality != 0 {
+                            // tint the bar towards gold as the HQ chance rises
+                            let hq = game_data::hq_percentage(quality, max_quality);
+                            text.push_str(&format!("  ({hq}% HQ)"));
+                            quality_bar = quality_bar.fill(lerp_color(
+                                ui.visuals().selection.bg_fill,
+                                Color32::GOLD,
+                                hq as f32 / 100.0,
+                            ));
+                        }
+                        ui.add(quality_bar.text(text).rounding(Rounding::ZERO))
+                            .on_hover_text_at_pointer(&prog_qual_dbg_text);
                     });
                     ui.horizontal(|ui| {
                         ui.label("Durability:");
@@ -141,6 +192,18 @@ impl<'a> Widget for Simulator<'a> {
                                 .rounding(Rounding::ZERO)
                                 .desired_width(120.0),
                         );
+                        // not shown while the macro contains actions that can't be used
+                        if let Ok(cp_used) = cp_used(
+                            self.settings,
+                            &SimulationState::new(self.settings),
+                            &self.actions[..step.unwrap_or(self.actions.len())],
+                        ) {
+                            ui.label(
+                                egui::RichText::new(format!("CP used: {}", cp_used))
+                                    .small()
+                                    .color(ui.visuals().weak_text_color()),
+                            );
+                        }
 
                         ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                             ui.add(HelpText::new(if self.settings.adversarial {
@@ -150,10 +213,8 @@ impl<'a> Widget for Simulator<'a> {
                             }));
                             if game_state.is_final(self.settings) {
                                 if progress < max_progress {
-                                    ui.label(
-                                        egui::RichText::new(format!("Synthesis failed")).strong(),
-                                    );
-                                } else if self.item.is_collectable {
+                                    ui.label(egui::RichText::new("Synthesis failed").strong());
+                                } else if item.is_collectable {
                                     let t1 = QualityTarget::CollectableT1
                                         .get_target(self.settings.max_quality);
                                     let t2 = QualityTarget::CollectableT2
@@ -179,15 +240,114 @@ impl<'a> Widget for Simulator<'a> {
                             }
                         });
                     });
+                    let mut diagnostic_settings = *self.settings;
+                    diagnostic_settings.max_quality =
+                        max_quality.saturating_sub(self.initial_quality);
+                    if self.settings.adversarial {
+                        // in adversarial mode, the simulated Quality is already the worst case over all conditions
+                        ui.label(format!("Guaranteed quality (worst conditions): {quality}"));
+                    }
+                    for diagnostic in solvers::diagnose(&diagnostic_settings, &game_state) {
+                        ui.label(
+                            egui::RichText::new(format!("ℹ {diagnostic}"))
+                                .small()
+                                .color(ui.visuals().weak_text_color()),
+                        );
+                    }
+                    if let Some(limit) =
+                        solvers::limiting_resource(&diagnostic_settings, &game_state)
+                    {
+                        // the bounds are computed by the worker, `Some(None)` means that they are still being computed
+                        let bounds_id = Id::new("RELAXED_QUALITY_BOUNDS");
+                        let bounds = ui.ctx().data(|data| {
+                            data.get_temp::<(Settings, Option<solvers::RelaxedQualityBounds>)>(
+                                bounds_id,
+                            )
+                            .filter(|(settings, _)| *settings == diagnostic_settings)
+                            .map(|(_, bounds)| bounds)
+                        });
+                        let weak_color = ui.visuals().weak_text_color();
+                        let weak_text =
+                            |text: String| egui::RichText::new(text).small().color(weak_color);
+                        match bounds {
+                            Some(Some(bounds)) => {
+                                ui.label(weak_text(format!("Quality is limited by {limit}.")));
+                                ui.label(weak_text(format!(
+                                    "Upper bound: {}, with +{} CP: {}, with unlimited durability: {}",
+                                    bounds.baseline + self.initial_quality,
+                                    EXTRA_CP,
+                                    bounds.extra_cp + self.initial_quality,
+                                    bounds.unlimited_durability + self.initial_quality,
+                                )));
+                            }
+                            Some(None) => {
+                                ui.horizontal(|ui| {
+                                    ui.spinner();
+                                    ui.label(weak_text(String::from("Computing upper bounds…")));
+                                });
+                            }
+                            None => {
+                                if ui
+                                    .small_button("Why not higher?")
+                                    .on_hover_text("Compare the Quality upper bound with more CP or unlimited durability")
+                                    .clicked()
+                                {
+                                    ui.ctx().data_mut(|data| {
+                                        data.insert_temp(
+                                            bounds_id,
+                                            (
+                                                diagnostic_settings,
+                                                None::<solvers::RelaxedQualityBounds>,
+                                            ),
+                                        );
+                                        data.insert_temp(
+                                            Id::new("RELAXED_QUALITY_BOUNDS_REQUEST"),
+                                            (diagnostic_settings, EXTRA_CP),
+                                        );
+                                    });
+                                }
+                            }
+                        }
+                    }
                 });
             });
             ui.add_space(5.5);
+            ui.horizontal(|ui| {
+                let shown_steps = step.unwrap_or(self.actions.len());
+                if ui
+                    .add_enabled(shown_steps != 0, egui::Button::new("⏴ Step back"))
+                    .clicked()
+                {
+                    step = Some(shown_steps - 1);
+                }
+                if ui
+                    .add_enabled(
+                        shown_steps < self.actions.len(),
+                        egui::Button::new("Step forward ⏵"),
+                    )
+                    .clicked()
+                {
+                    step = Some(shown_steps + 1);
+                }
+                if ui
+                    .add_enabled(step.is_some(), egui::Button::new("Show all"))
+                    .clicked()
+                {
+                    step = None;
+                }
+                if step.is_some() {
+                    ui.label(format!("Step {} / {}", shown_steps, self.actions.len()));
+                }
+            });
             ui.group(|ui| {
                 egui::ScrollArea::horizontal().show(ui, |ui| {
                     ui.set_height(30.0);
                     ui.set_width(ui.available_width());
                     ui.horizontal(|ui| {
-                        for (action, error) in self.actions.iter().zip(errors.into_iter()) {
+                        let mut removed_action = None;
+                        let breakdowns = step_breakdowns(self.settings, self.actions);
+                        for (index, (action, error)) in self.actions.iter().zip(errors).enumerate()
+                        {
                             let image_path = format!(
                                 "{}/action-icons/{}/{}.png",
                                 BASE_ASSET_PATH,
@@ -195,21 +355,111 @@ impl<'a> Widget for Simulator<'a> {
                                 action_name(*action, Locale::EN)
                             );
 
-                            ui.add(
-                                egui::Image::new(image_path)
-                                    .fit_to_exact_size(egui::Vec2::new(30.0, 30.0))
-                                    .rounding(4.0)
-                                    .tint(match error {
-                                        Ok(_) => Color32::WHITE,
-                                        Err(_) => Color32::from_rgb(255, 96, 96),
-                                    }),
-                            )
-                            .on_hover_text(action_name(*action, self.locale));
+                            let response = ui
+                                .add(
+                                    egui::Image::new(image_path)
+                                        .fit_to_exact_size(egui::Vec2::new(30.0, 30.0))
+                                        .rounding(4.0)
+                                        .tint(match error {
+                                            // actions that are not simulated while stepping through the macro
+                                            _ if step.is_some_and(|step| index >= step) => {
+                                                Color32::from_gray(96)
+                                            }
+                                            Ok(_) => Color32::WHITE,
+                                            Err(_) => Color32::from_rgb(255, 96, 96),
+                                        })
+                                        .sense(egui::Sense::click()),
+                                )
+                                .on_hover_text(match &breakdowns[index] {
+                                    Some(breakdown) => format!(
+                                        "{}\n{}Click to remove",
+                                        action_name(*action, self.locale),
+                                        step_summary(breakdown)
+                                    ),
+                                    None => format!(
+                                        "{}\nClick to remove",
+                                        action_name(*action, self.locale)
+                                    ),
+                                });
+                            if response.clicked() {
+                                removed_action = Some(index);
+                            }
+                        }
+                        if let Some(index) = removed_action {
+                            self.actions.remove(index);
                         }
                     });
                 });
             });
+            ui.ctx().data_mut(|data| {
+                data.insert_temp(Id::new("SIMULATION_STEP"), step);
+            });
         })
         .response
     }
 }
+
+/// Breakdown of each step, `None` for the actions that fail and are skipped by `from_macro_continue_on_error`.
+fn step_breakdowns(settings: &Settings, actions: &[Action]) -> Vec<Option<StepBreakdown>> {
+    let mut state = SimulationState::new(settings);
+    actions
+        .iter()
+        .map(|action| {
+            let new_state = state
+                .use_action(*action, Condition::Normal, settings)
+                .ok()?;
+            let breakdown = StepBreakdown::new(*action, &state, &new_state);
+            state = new_state;
+            Some(breakdown)
+        })
+        .collect()
+}
+
+/// Gains of the step and the buffs that boosted them, one line each, e.g. "+330 Quality (Inner Quiet 1, Innovation)\n"
+fn step_summary(breakdown: &StepBreakdown) -> String {
+    let effects = breakdown.effects_applied;
+    let mut summary = String::new();
+    if breakdown.progress_delta != 0 {
+        let buffs: Vec<&str> = [
+            (effects.muscle_memory, "Muscle Memory"),
+            (effects.veneration, "Veneration"),
+        ]
+        .into_iter()
+        .filter_map(|(active, name)| active.then_some(name))
+        .collect();
+        summary.push_str(&format!("+{} Progress", breakdown.progress_delta));
+        if !buffs.is_empty() {
+            summary.push_str(&format!(" ({})", buffs.join(", ")));
+        }
+        summary.push('\n');
+    }
+    if breakdown.quality_delta != 0 {
+        let inner_quiet = format!("Inner Quiet {}", effects.inner_quiet);
+        let buffs: Vec<&str> = [
+            (effects.inner_quiet != 0, inner_quiet.as_str()),
+            (effects.innovation, "Innovation"),
+            (effects.great_strides, "Great Strides"),
+        ]
+        .into_iter()
+        .filter_map(|(active, name)| active.then_some(name))
+        .collect();
+        summary.push_str(&format!("+{} Quality", breakdown.quality_delta));
+        if !buffs.is_empty() {
+            summary.push_str(&format!(" ({})", buffs.join(", ")));
+        }
+        summary.push('\n');
+    }
+    summary
+}
+
+/// CP added to the settings when explaining what limits the Quality of a macro.
+const EXTRA_CP: i16 = 50;
+
+fn lerp_color(from: Color32, to: Color32, t: f32) -> Color32 {
+    let lerp = |from: u8, to: u8| egui::lerp(from as f32..=to as f32, t).round() as u8;
+    Color32::from_rgb(
+        lerp(from.r(), to.r()),
+        lerp(from.g(), to.g()),
+        lerp(from.b(), to.b()),
+    )
+}
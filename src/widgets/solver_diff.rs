@@ -0,0 +1,74 @@
+use egui::{Color32, Widget};
+use simulator::{Action, Settings, SimulationState};
+
+const BETTER_COLOR: Color32 = Color32::from_rgb(96, 192, 96);
+const WORSE_COLOR: Color32 = Color32::from_rgb(255, 96, 96);
+
+/// Compares the (possibly edited) working macro against the macro returned by the solver.
+pub struct SolverDiff<'a> {
+    settings: &'a Settings,
+    actions: &'a mut Vec<Action>,
+    solver_actions: &'a [Action],
+}
+
+impl<'a> SolverDiff<'a> {
+    pub fn new(
+        settings: &'a Settings,
+        actions: &'a mut Vec<Action>,
+        solver_actions: &'a [Action],
+    ) -> Self {
+        Self {
+            settings,
+            actions,
+            solver_actions,
+        }
+    }
+}
+
+fn delta_label(ui: &mut egui::Ui, name: &str, delta: i32, higher_is_better: bool) {
+    let color = match delta.signum() * if higher_is_better { 1 } else { -1 } {
+        1 => BETTER_COLOR,
+        -1 => WORSE_COLOR,
+        _ => ui.visuals().text_color(),
+    };
+    ui.label(egui::RichText::new(format!("{name}: {delta:+}")).color(color));
+}
+
+impl<'a> Widget for SolverDiff<'a> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let (edited_state, _) =
+            SimulationState::from_macro_continue_on_error(self.settings, self.actions);
+        let (solver_state, _) =
+            SimulationState::from_macro_continue_on_error(self.settings, self.solver_actions);
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Compared to solver result").strong());
+                ui.separator();
+                delta_label(
+                    ui,
+                    "Quality",
+                    edited_state.get_quality() as i32 - solver_state.get_quality() as i32,
+                    true,
+                );
+                delta_label(
+                    ui,
+                    "Steps",
+                    self.actions.len() as i32 - self.solver_actions.len() as i32,
+                    false,
+                );
+                delta_label(
+                    ui,
+                    "CP left",
+                    edited_state.cp as i32 - solver_state.cp as i32,
+                    true,
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("Reset").clicked() {
+                        *self.actions = self.solver_actions.to_vec();
+                    }
+                });
+            });
+        })
+        .response
+    }
+}
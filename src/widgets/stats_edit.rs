@@ -1,5 +1,5 @@
 use egui::Widget;
-use game_data::{get_job_name, Locale};
+use game_data::{get_job_name, CrafterStats, Locale};
 
 use crate::config::CrafterConfig;
 
@@ -34,18 +34,30 @@ impl<'a> Widget for StatsEdit<'a> {
                 let stats = &mut self.crafter_config.crafter_stats[job_id as usize];
                 ui.horizontal(|ui| {
                     ui.label("Craftsmanship:");
-                    ui.add(egui::DragValue::new(&mut stats.craftsmanship));
+                    ui.add(
+                        egui::DragValue::new(&mut stats.craftsmanship)
+                            .clamp_range(0..=CrafterStats::MAX_CRAFTSMANSHIP),
+                    );
                     ui.label("Control:");
-                    ui.add(egui::DragValue::new(&mut stats.control));
+                    ui.add(
+                        egui::DragValue::new(&mut stats.control)
+                            .clamp_range(0..=CrafterStats::MAX_CONTROL),
+                    );
                     ui.label("CP:");
-                    ui.add(egui::DragValue::new(&mut stats.cp));
+                    ui.add(
+                        egui::DragValue::new(&mut stats.cp).clamp_range(0..=CrafterStats::MAX_CP),
+                    );
                     ui.label("Level:");
-                    ui.add(egui::DragValue::new(&mut stats.level).clamp_range(1..=100));
+                    ui.add(
+                        egui::DragValue::new(&mut stats.level)
+                            .clamp_range(1..=CrafterStats::MAX_LEVEL),
+                    );
                 });
                 ui.horizontal(|ui| {
                     ui.checkbox(&mut stats.manipulation, "Manipulation");
                     ui.checkbox(&mut stats.heart_and_soul, "Heart and Soul");
                     ui.checkbox(&mut stats.quick_innovation, "Quick Innovation");
+                    ui.checkbox(&mut stats.careful_observation, "Careful Observation");
                 });
             }
         })
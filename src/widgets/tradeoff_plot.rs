@@ -0,0 +1,121 @@
+use egui::{Id, Widget};
+use egui_plot::{Line, Plot, PlotPoints, Points};
+use serde::{Deserialize, Serialize};
+use simulator::{Action, Settings, SimulationState};
+
+/// Which property of the solutions is plotted against Quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum TradeoffAxis {
+    #[default]
+    Cp,
+    Steps,
+}
+
+/// Plots Quality against CP or steps of the alternative solutions, to show the diminishing returns of spending more CP or steps.
+pub struct TradeoffPlot<'a> {
+    settings: &'a Settings,
+    initial_quality: u16,
+    solutions: &'a [Vec<Action>],
+    selected: &'a [Action],
+}
+
+impl<'a> TradeoffPlot<'a> {
+    pub fn new(
+        settings: &'a Settings,
+        initial_quality: u16,
+        solutions: &'a [Vec<Action>],
+        selected: &'a [Action],
+    ) -> Self {
+        Self {
+            settings,
+            initial_quality,
+            solutions,
+            selected,
+        }
+    }
+}
+
+impl<'a> Widget for TradeoffPlot<'a> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let axis_id = Id::new("TRADEOFF_PLOT_AXIS");
+        let mut axis = ui
+            .ctx()
+            .data_mut(|data| data.get_persisted(axis_id))
+            .unwrap_or_default();
+
+        let points: Vec<[f64; 2]> = self
+            .solutions
+            .iter()
+            .map(|solution| {
+                let state =
+                    SimulationState::from_macro_continue_on_error(self.settings, solution).0;
+                let x = match axis {
+                    TradeoffAxis::Cp => game_data::cp_used(
+                        self.settings,
+                        &SimulationState::new(self.settings),
+                        solution,
+                    )
+                    .unwrap_or_default(),
+                    TradeoffAxis::Steps => solution.len() as i16,
+                };
+                let quality = state.get_quality() + self.initial_quality;
+                [x as f64, quality as f64]
+            })
+            .collect();
+        let selected: Vec<[f64; 2]> = self
+            .solutions
+            .iter()
+            .zip(points.iter())
+            .filter(|(solution, _)| solution.as_slice() == self.selected)
+            .map(|(_, point)| *point)
+            .collect();
+        let mut line = points.clone();
+        line.sort_by(|lhs, rhs| lhs[0].total_cmp(&rhs[0]));
+
+        let response = ui
+            .vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Quality vs");
+                    ui.selectable_value(&mut axis, TradeoffAxis::Cp, "CP");
+                    ui.selectable_value(&mut axis, TradeoffAxis::Steps, "Steps");
+                });
+                if points.len() < 2 {
+                    ui.label("Solve for more than one solution to compare them.");
+                    return;
+                }
+                let mut plot = Plot::new("TRADEOFF_PLOT")
+                    .height(150.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .allow_boxed_zoom(false)
+                    .x_axis_label(match axis {
+                        TradeoffAxis::Cp => "CP",
+                        TradeoffAxis::Steps => "Steps",
+                    })
+                    .y_axis_label("Quality");
+                // pad the bounds so that solutions with equal values don't collapse the plot to a line
+                for [x, y] in points.iter() {
+                    plot = plot.include_x(x - 1.0).include_x(x + 1.0);
+                    plot = plot.include_y(y - 10.0).include_y(y + 10.0);
+                }
+                let selected_color = ui.visuals().selection.bg_fill;
+                plot.show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(PlotPoints::from(line)));
+                    plot_ui.points(Points::new(points).radius(3.0).name("Solutions"));
+                    plot_ui.points(
+                        Points::new(selected)
+                            .radius(5.0)
+                            .color(selected_color)
+                            .name("Selected"),
+                    );
+                });
+            })
+            .response;
+
+        ui.ctx().data_mut(|data| {
+            data.insert_persisted(axis_id, axis);
+        });
+        response
+    }
+}
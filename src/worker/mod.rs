@@ -1,6 +1,22 @@
 use crate::app::{SolverConfig, SolverEvent};
-use simulator::{Action, Settings, SimulationState};
+use crate::config::BaseValueOverride;
+use game_data::{Consumable, CrafterStats, Recipe};
+use simulator::{Settings, SimulationState};
+use solvers::{SolutionTrace, SolverCache};
+use std::cell::Cell;
 use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod native;
@@ -20,12 +36,120 @@ use gloo_worker::WorkerBridge;
 #[cfg(target_arch = "wasm32")]
 pub type BridgeType = WorkerBridge<Worker>;
 
-type Input = (Settings, SolverConfig);
+/// Jobs that are too expensive to run on the GUI thread.
+#[derive(Debug, Clone)]
+#[cfg_attr(target_arch = "wasm32", derive(serde::Serialize, serde::Deserialize))]
+pub enum WorkerInput {
+    Solve(Settings, SolverConfig, CancelFlag),
+    /// See `solvers::RelaxedQualityBounds`, the second value is the extra CP.
+    RelaxedQualityBounds(Settings, i16),
+    /// See `game_data::suggest_consumables`, with the selected food and potion.
+    /// The suggested consumables must be enough to complete the recipe.
+    SuggestConsumables(
+        Recipe,
+        CrafterStats,
+        Option<Consumable>,
+        Option<Consumable>,
+        BaseValueOverride,
+    ),
+}
+
+type Input = WorkerInput;
 type Output = SolverEvent;
 
+/// Minimum time between two intermediate solutions sent to the GUI.
+/// The final solution is always sent, so skipped intermediate solutions are never lost.
+const INTERMEDIATE_SOLUTION_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set by the GUI to stop a solver job whose result is no longer needed.
+/// The solver checks it periodically and returns without a result once it is set.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CancelFlag {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// On the web, the flag lives in a `SharedArrayBuffer` that is shared with the web worker.
+/// Shared memory requires a cross-origin isolated page, without it the flag is `undefined`
+/// and the job can't be cancelled once it has been sent (see `is_shared`).
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CancelFlag(#[serde(with = "serde_wasm_bindgen::preserve")] wasm_bindgen::JsValue);
+
+#[cfg(target_arch = "wasm32")]
+impl Default for CancelFlag {
+    fn default() -> Self {
+        let shared_memory_supported =
+            js_sys::Reflect::has(&js_sys::global(), &"SharedArrayBuffer".into()).unwrap_or(false);
+        if shared_memory_supported {
+            let buffer = js_sys::SharedArrayBuffer::new(4);
+            Self(js_sys::Int32Array::new(&buffer).into())
+        } else {
+            Self(wasm_bindgen::JsValue::UNDEFINED)
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl CancelFlag {
+    pub fn cancel(&self) {
+        if let Some(array) = self.array() {
+            let _ = js_sys::Atomics::store(array, 0, 1);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.array()
+            .is_some_and(|array| js_sys::Atomics::load(array, 0).is_ok_and(|value| value != 0))
+    }
+
+    /// Whether cancelling reaches the web worker.
+    pub fn is_shared(&self) -> bool {
+        self.array().is_some()
+    }
+
+    fn array(&self) -> Option<&js_sys::Int32Array> {
+        use wasm_bindgen::JsCast;
+        self.0.dyn_ref()
+    }
+}
+
+/// Messages are converted with `serde_wasm_bindgen` instead of gloo's default bincode encoding,
+/// so that the `SharedArrayBuffer` of a `CancelFlag` is passed to the web worker as is.
+#[cfg(target_arch = "wasm32")]
+pub struct Codec;
+
+#[cfg(target_arch = "wasm32")]
+impl gloo_worker::Codec for Codec {
+    fn encode<I>(input: I) -> wasm_bindgen::JsValue
+    where
+        I: serde::Serialize,
+    {
+        serde_wasm_bindgen::to_value(&input).expect("failed to encode worker message")
+    }
+
+    fn decode<O>(input: wasm_bindgen::JsValue) -> O
+    where
+        O: for<'de> serde::Deserialize<'de>,
+    {
+        serde_wasm_bindgen::from_value(input).expect("failed to decode worker message")
+    }
+}
+
 pub struct Worker {
     input: Option<Input>,
     tx: Option<Sender<Output>>,
+    // memoized bounds of the previous job, which are reused if the next job solves the same recipe
+    cache: Cell<Option<SolverCache>>,
 }
 
 impl Worker {
@@ -36,57 +160,134 @@ impl Worker {
         input: Option<Input>,
     ) {
         let input = if cfg!(not(target_arch = "wasm32")) {
-            self.input.unwrap()
+            self.input.clone().unwrap()
         } else {
             input.unwrap()
         };
 
-        let settings = input.0;
-        let config = input.1;
+        let (settings, config, cancel_flag) = match input {
+            WorkerInput::Solve(settings, config, cancel_flag) => (settings, config, cancel_flag),
+            WorkerInput::RelaxedQualityBounds(settings, extra_cp) => {
+                let bounds = solvers::RelaxedQualityBounds::new(&settings, extra_cp);
+                self.send_event(
+                    self.tx.clone(),
+                    scope,
+                    id,
+                    SolverEvent::RelaxedQualityBounds(settings, bounds),
+                );
+                return;
+            }
+            WorkerInput::SuggestConsumables(recipe, crafter_stats, food, potion, base_override) => {
+                let suggestion = game_data::suggest_consumables(
+                    recipe,
+                    crafter_stats,
+                    food,
+                    potion,
+                    |settings| solvers::can_complete(&base_override.apply(*settings)),
+                );
+                self.send_event(
+                    self.tx.clone(),
+                    scope,
+                    id,
+                    SolverEvent::ConsumableSuggestion(suggestion),
+                );
+                return;
+            }
+        };
 
         let tx = self.tx.clone();
-        let solution_callback = move |actions: &[Action]| {
+        let solution_cancel_flag = cancel_flag.clone();
+        let last_sent: Cell<Option<Instant>> = Cell::new(None);
+        let best_quality: Cell<Option<u16>> = Cell::new(None);
+        let best_quality = &best_quality;
+        let solution_callback = move |trace: &SolutionTrace<'_>| {
+            // later solves of top-k and min-CP searches can report worse solutions than earlier ones
+            best_quality.set(std::cmp::max(best_quality.get(), Some(trace.quality())));
+            // the web worker shares its event channel with the next job, which may already be queued
+            if solution_cancel_flag.is_cancelled() {
+                return;
+            }
+            // only materialize the actions if the GUI is actually going to receive them
+            if last_sent
+                .get()
+                .is_some_and(|instant| instant.elapsed() < INTERMEDIATE_SOLUTION_INTERVAL)
+            {
+                return;
+            }
+            last_sent.set(Some(Instant::now()));
             self.send_event(
                 tx.clone(),
                 scope,
                 id,
-                SolverEvent::IntermediateSolution(actions.to_vec()),
+                SolverEvent::IntermediateSolution(trace.to_vec()),
             );
         };
 
         let tx = self.tx.clone();
+        let progress_cancel_flag = cancel_flag.clone();
         let progress_callback = move |progress: f32| {
-            self.send_event(tx.clone(), scope, id, SolverEvent::Progress(progress));
+            if progress_cancel_flag.is_cancelled() {
+                return;
+            }
+            self.send_event(
+                tx.clone(),
+                scope,
+                id,
+                SolverEvent::Progress(progress, best_quality.get()),
+            );
         };
 
-        let final_solution = solvers::MacroSolver::new(
+        let is_cancelled = {
+            let cancel_flag = cancel_flag.clone();
+            move || cancel_flag.is_cancelled()
+        };
+
+        let solver_config = solvers::SolverConfig {
+            backload_progress: config.backload_progress,
+            minimize_steps: config.minimize_steps,
+            max_steps: config.max_steps,
+            quality_overflow: config.quality_overflow,
+            timeout: config
+                .time_limit
+                .map(|seconds| Duration::from_secs(seconds as u64)),
+            ..Default::default()
+        };
+        let mut solver = solvers::MacroSolver::new(
             settings,
+            solver_config,
             Box::new(solution_callback),
             Box::new(progress_callback),
         )
-        .solve(
-            SimulationState::new(&settings),
-            config.backload_progress,
-            config.minimize_steps,
-        );
+        .with_cancellation(Box::new(is_cancelled));
+        if let Some(cache) = self.cache.take() {
+            solver = solver.with_cache(cache);
+        }
+        let initial_state = SimulationState::new(&settings);
+        let solutions = if config.minimize_cp {
+            solver
+                .solve_min_cp(initial_state)
+                .map(|result| vec![result.actions])
+                .unwrap_or_default()
+        } else {
+            solver.solve_top_k(initial_state, config.solution_count as usize)
+        };
 
-        let tx = self.tx.clone();
-        match final_solution {
-            Some(actions) => {
-                self.send_event(tx.clone(), scope, id, SolverEvent::FinalSolution(actions));
-            }
-            None => {
-                self.send_event(
-                    tx.clone(),
-                    scope,
-                    id,
-                    SolverEvent::FinalSolution(Vec::new()),
-                );
-            }
+        // the GUI already went back to editing, so the result of a cancelled job isn't sent
+        if !cancel_flag.is_cancelled() {
+            let tx = self.tx.clone();
+            self.send_event(
+                tx.clone(),
+                scope,
+                id,
+                SolverEvent::FinalSolution(solutions, solver.stats()),
+            );
         }
+        // cancelling only interrupts the main search loop, so the memoized bounds are complete and can be reused
+        self.cache.set(Some(solver.into_cache()));
     }
 
     // Adapter to unify both implementations
+    #[allow(clippy::unit_arg)] // worker::Id is () on native
     fn send_event(
         &self,
         tx: Option<Sender<SolverEvent>>,
@@ -97,7 +298,8 @@ impl Worker {
         if cfg!(target_arch = "wasm32") {
             scope.unwrap().respond(id.unwrap(), event);
         } else {
-            tx.unwrap().send(event).unwrap();
+            // the receiver is dropped once the job is cancelled or a newer job is sent to the bridge
+            let _ = tx.unwrap().send(event);
         }
     }
 }
@@ -1,6 +1,8 @@
 use crate::worker::Input;
 use crate::worker::Output;
 use crate::Worker;
+use solvers::SolverCache;
+use std::cell::Cell;
 use std::sync::mpsc::{self, Receiver, Sender};
 
 pub(crate) type Scope = DummyScope;
@@ -11,32 +13,60 @@ impl DummyScope {
     pub fn respond(&self, _id: Id, _event: Output) {}
 }
 
+type Job = (Input, Sender<Output>);
+
 pub struct NativeBridge {
     pub(crate) rx: Option<Receiver<Output>>,
+    job_tx: Sender<Job>,
 }
 
 impl NativeBridge {
     pub fn new() -> Self {
-        Self { rx: None }
+        Self {
+            rx: None,
+            job_tx: spawn_worker_thread(),
+        }
     }
 
     pub fn send(&mut self, input: Input) {
         let (tx, rx) = mpsc::channel::<Output>();
-
-        let worker = Worker::new(input, tx);
-        std::thread::spawn(move || {
-            worker.solver_callback(None, None, None);
-        });
-
+        if let Err(mpsc::SendError(job)) = self.job_tx.send((input, tx)) {
+            // the worker thread is gone (e.g. the solver panicked), start a new one
+            self.job_tx = spawn_worker_thread();
+            self.job_tx.send(job).unwrap();
+        }
+        // events of the previous job are dropped together with its receiver
         self.rx = Some(rx);
     }
+
+    /// Drop the events of the running job. The job itself is stopped through its `CancelFlag`.
+    pub fn cancel(&mut self) {
+        self.rx = None;
+    }
+}
+
+/// Spawn a persistent thread that runs solver jobs one at a time.
+fn spawn_worker_thread() -> Sender<Job> {
+    let (job_tx, job_rx) = mpsc::channel::<Job>();
+    std::thread::spawn(move || {
+        let mut cache = None;
+        while let Ok(job) = job_rx.recv() {
+            // only the most recent job is relevant, all jobs queued before it are stale
+            let (input, tx) = job_rx.try_iter().last().unwrap_or(job);
+            let worker = Worker::new(input, tx, cache.take());
+            worker.solver_callback(None, None, None);
+            cache = worker.cache.take();
+        }
+    });
+    job_tx
 }
 
 impl Worker {
-    fn new(input: Input, tx: Sender<Output>) -> Worker {
+    fn new(input: Input, tx: Sender<Output>, cache: Option<SolverCache>) -> Worker {
         Worker {
             input: Some(input),
             tx: Some(tx),
+            cache: Cell::new(cache),
         }
     }
 }
@@ -16,6 +16,7 @@ impl gloo_worker::Worker for Worker {
         Self {
             input: None,
             tx: None,
+            cache: Default::default(),
         }
     }
 